@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A current ban, as listed by [`BouncerLayer::entries`], [`banlist_router`] and the admin
+ * endpoints.
+ */
+export type BanEntry = { ip: string, 
+/**
+ * RFC 3339 timestamp.
+ */
+expires_at: string, reason: string, 
+/**
+ * Requests rejected while banned, counted by this instance.
+ */
+hits: number, };
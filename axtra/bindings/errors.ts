@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Enum of all possible error codes.
+ */
+export type ErrorCode = "authentication" | "authorization" | "badRequest" | "database" | "exception" | "notFound" | "validation";
+
+export type ErrorResponse = { status: string, message: string, code: ErrorCode, validationErrors?: SerializableValidationErrors, };
+
+/**
+ * Represents all validation errors in a serializable form.
+ */
+export type SerializableValidationErrors = { errors: Array<ValidationFieldError>, };
+
+/**
+ * Represents a single field validation error.
+ */
+export type ValidationFieldError = { field: string, code: string, message: string, params: { [key in string]?: string }, };
@@ -0,0 +1,135 @@
+//! JSON endpoints for managing bouncer bans.
+
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Request, State, rejection::JsonRejection},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+};
+use serde::Deserialize;
+
+use crate::{
+    app_error,
+    bouncer::{
+        BouncerLayer,
+        export::BanEntry,
+        store::{MAX_BAN_DURATION, StoreError},
+    },
+    error_location,
+    errors::{AppError, ErrorFormat},
+    net::constant_time_eq,
+    response::{Deleted, WrappedJson},
+};
+
+struct Admin {
+    bouncer: BouncerLayer,
+    token: String,
+}
+
+/// Body of `POST /bans`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManualBan {
+    pub ip: IpAddr,
+    /// Defaults to the bouncer's ban duration. At most [`MAX_BAN_DURATION`].
+    pub duration_secs: Option<u64>,
+    pub reason: Option<String>,
+}
+
+/// Endpoints for listing and managing the bouncer's bans, requiring
+/// `Authorization: Bearer <token>`:
+///
+/// - `GET /bans`: current bans, as `{ "bans": [{ "ip", "expires_at", "reason", "hits" }] }`.
+/// - `POST /bans`: ban `{ "ip", "duration_secs"?, "reason"? }`, returning the new ban.
+/// - `DELETE /bans/{ip}`: lift a ban, returning `{ "deleted": { "id": ip } }`.
+///
+/// ```rust, ignore
+/// let bouncer = BouncerLayer::new(config);
+/// let app = Router::new()
+///     .nest("/admin", admin_router(&bouncer, env::var("BOUNCER_ADMIN_TOKEN")?))
+///     .layer(bouncer);
+/// ```
+///
+/// Panics if `token` is empty.
+pub fn admin_router(bouncer: &BouncerLayer, token: impl Into<String>) -> Router {
+    let token = token.into();
+    assert!(!token.is_empty(), "admin_router needs a non-empty token");
+    let admin = Arc::new(Admin {
+        bouncer: bouncer.clone(),
+        token,
+    });
+
+    Router::new()
+        .route("/bans", get(list).post(ban))
+        .route("/bans/{ip}", delete(unban))
+        .route_layer(middleware::from_fn_with_state(admin.clone(), authorize))
+        .with_state(admin)
+}
+
+async fn authorize(State(admin): State<Arc<Admin>>, req: Request, next: Next) -> Response {
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(admin.token.as_bytes(), token.trim().as_bytes()));
+    if !authorized {
+        return app_error!(unauthenticated, json).into_response();
+    }
+    next.run(req).await
+}
+
+async fn list(State(admin): State<Arc<Admin>>) -> Result<WrappedJson<Vec<BanEntry>>, AppError> {
+    let entries = admin.bouncer.entries().await.map_err(store_error)?;
+    Ok(WrappedJson(entries))
+}
+
+async fn ban(
+    State(admin): State<Arc<Admin>>,
+    body: Result<Json<ManualBan>, JsonRejection>,
+) -> Result<(StatusCode, WrappedJson<BanEntry>), AppError> {
+    let Json(ban) = body.map_err(app_error!(bad_request, json, with_error, "Invalid ban"))?;
+    let reason = ban.reason.as_deref().unwrap_or("Manual ban");
+    let duration = ban.duration_secs.map(Duration::from_secs);
+    if duration.is_some_and(|duration| duration > MAX_BAN_DURATION) {
+        let msg = format!(
+            "duration_secs can be at most {}",
+            MAX_BAN_DURATION.as_secs()
+        );
+        return Err(app_error!(bad_request, json, msg));
+    }
+    let ban = admin
+        .bouncer
+        .ban_ip(ban.ip, duration, reason)
+        .await
+        .map_err(store_error)?;
+    Ok((StatusCode::CREATED, WrappedJson(ban.into())))
+}
+
+async fn unban(
+    State(admin): State<Arc<Admin>>,
+    Path(ip): Path<String>,
+) -> Result<Deleted<IpAddr>, AppError> {
+    let ip: IpAddr = ip.parse().map_err(app_error!(
+        bad_request,
+        json,
+        with_error,
+        "Invalid IP address"
+    ))?;
+    if !admin.bouncer.unban_ip(ip).await.map_err(store_error)? {
+        return Err(app_error!(not_found, json, "ban"));
+    }
+    Ok(Deleted::new(ip))
+}
+
+fn store_error(e: StoreError) -> AppError {
+    AppError::exception(
+        "Ban store unavailable",
+        Some(e),
+        error_location!(),
+        ErrorFormat::Json,
+    )
+}
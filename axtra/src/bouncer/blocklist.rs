@@ -0,0 +1,196 @@
+//! External IP blocklists merged into the bouncer's network bans.
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt,
+    sync::{Arc, Weak},
+    time::Duration,
+};
+
+use crate::{
+    bouncer::{BouncerConfig, networks::NetworkBans},
+    metrics,
+    net::IpNet,
+};
+
+// How soon a failed fetch is retried, unless the refresh interval is shorter
+const RETRY_INTERVAL: Duration = Duration::from_secs(300);
+
+/// A feed of known-bad IPs and CIDR ranges, one per line. Text after `;` or `#` is ignored,
+/// which covers Spamhaus DROP, AbuseIPDB's plaintext blacklist and most other feeds.
+///
+/// ```rust, ignore
+/// let config = BouncerConfig::from_preset_rules(&["wordpress"])
+///     .blocklist(Blocklist::spamhaus_drop())
+///     .blocklist(Blocklist::abuseipdb(env::var("ABUSEIPDB_KEY")?, 90))
+///     .blocklist(Blocklist::new("internal", "https://intel.example.com/bad-ips.txt"));
+/// ```
+#[derive(Clone)]
+pub struct Blocklist {
+    name: String,
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Blocklist {
+    /// A plaintext feed at `url`; `name` labels its logs and metrics.
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Spamhaus DROP: IPv4 ranges hijacked or leased by spammers and cybercriminals.
+    pub fn spamhaus_drop() -> Self {
+        Self::new("spamhaus-drop", "https://www.spamhaus.org/drop/drop.txt")
+    }
+
+    /// Spamhaus DROPv6, the IPv6 counterpart of [`spamhaus_drop`](Self::spamhaus_drop).
+    pub fn spamhaus_drop_v6() -> Self {
+        Self::new(
+            "spamhaus-drop-v6",
+            "https://www.spamhaus.org/drop/dropv6.txt",
+        )
+    }
+
+    /// AbuseIPDB's blacklist of IPs reported with at least `confidence_minimum` (25-100)
+    /// abuse confidence. Needs an API key.
+    pub fn abuseipdb(api_key: impl Into<String>, confidence_minimum: u8) -> Self {
+        Self::new(
+            "abuseipdb",
+            format!(
+                "https://api.abuseipdb.com/api/v2/blacklist?confidenceMinimum={confidence_minimum}&plaintext"
+            ),
+        )
+        .header("Key", api_key)
+        .header("Accept", "text/plain")
+    }
+
+    /// Send a header with every fetch, e.g. an API key.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// The name used in logs and metrics.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch(
+        &self,
+        client: &reqwest::Client,
+    ) -> Result<Vec<IpNet>, Box<dyn Error + Send + Sync>> {
+        let mut request = client.get(&self.url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let body = request.send().await?.error_for_status()?.text().await?;
+        let networks = parse(&body);
+        // An empty list is more likely an error page than a feed with nothing in it
+        if networks.is_empty() {
+            return Err("no IP addresses or CIDR ranges in response".into());
+        }
+        Ok(networks)
+    }
+}
+
+// Headers may carry API keys, so only the name and URL are shown
+impl fmt::Debug for Blocklist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blocklist")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .finish_non_exhaustive()
+    }
+}
+
+// Every IP or CIDR range in a feed, skipping comments and lines that don't parse
+fn parse(body: &str) -> Vec<IpNet> {
+    body.lines()
+        .filter_map(|line| line.split([';', '#']).next())
+        .filter_map(|entry| IpNet::parse(entry.trim()))
+        .collect()
+}
+
+/// Fetch `list` now and every `refresh` after, banning its networks until they leave the
+/// list. A failed fetch keeps the previous entries and is retried sooner. Stops once the
+/// layer's config is dropped.
+pub(crate) fn spawn_refresh(
+    list: Blocklist,
+    config: Weak<BouncerConfig>,
+    networks: Arc<NetworkBans>,
+    refresh: Duration,
+    max_entries: usize,
+) {
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_default();
+        // Networks this list banned, so entries dropped from the feed can be lifted
+        // without touching bans made elsewhere
+        let mut owned: HashSet<IpNet> = HashSet::new();
+        loop {
+            if config.strong_count() == 0 {
+                return;
+            }
+            let wait = match list.fetch(&client).await {
+                Ok(mut fetched) => {
+                    if fetched.len() > max_entries {
+                        tracing::warn!(
+                            list = list.name,
+                            entries = fetched.len(),
+                            max_entries,
+                            "Blocklist over its size cap, truncating"
+                        );
+                        fetched.truncate(max_entries);
+                    }
+                    owned = merge(&networks, owned, fetched);
+                    metrics::gauge(
+                        "axtra_bouncer_blocklist_entries",
+                        "Networks banned from external blocklists.",
+                    )
+                    .set_with(owned.len() as f64, &[("list", &list.name)]);
+                    tracing::info!(
+                        list = list.name,
+                        entries = owned.len(),
+                        "Blocklist refreshed"
+                    );
+                    refresh
+                }
+                Err(e) => {
+                    tracing::warn!(list = list.name, error = %e, "Blocklist fetch failed, keeping previous entries");
+                    RETRY_INTERVAL.min(refresh)
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    });
+}
+
+// Ban the fetched networks and lift the ones no longer listed, returning what the list
+// now owns
+fn merge(networks: &NetworkBans, owned: HashSet<IpNet>, fetched: Vec<IpNet>) -> HashSet<IpNet> {
+    let fetched: HashSet<IpNet> = fetched.into_iter().collect();
+    for network in owned.difference(&fetched) {
+        networks.remove(network);
+    }
+    fetched
+        .into_iter()
+        .filter(|network| {
+            if owned.contains(network) {
+                return true;
+            }
+            // Already banned by hand or another list; leave that ban alone
+            if networks.contains(network) {
+                return false;
+            }
+            networks.insert(*network, None);
+            true
+        })
+        .collect()
+}
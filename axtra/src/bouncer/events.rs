@@ -0,0 +1,50 @@
+//! Bouncer events passed to application hooks.
+
+use std::{fmt, future::Future, net::IpAddr, pin::Pin, sync::Arc, time::Duration};
+
+/// A blocked request or a ban, passed to [`on_block`] and [`on_ban`] hooks.
+///
+/// [`on_block`]: crate::bouncer::BouncerConfig::on_block
+/// [`on_ban`]: crate::bouncer::BouncerConfig::on_ban
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BouncerEvent {
+    pub ip: IpAddr,
+    pub path: String,
+    /// The blocked path rule that matched, or `None` for bans from other routes (such as
+    /// path traversal attempts on static files).
+    pub rule: Option<String>,
+    /// The preset the matched rule belongs to, if any.
+    pub preset: Option<&'static str>,
+    /// Human-readable reason, as recorded with the ban.
+    pub reason: String,
+    /// How long the IP is banned for; only set for bans.
+    pub ban_duration: Option<Duration>,
+}
+
+type HookFn = dyn Fn(BouncerEvent) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync;
+
+/// An async callback run for bouncer events.
+///
+/// Hooks run on their own task, so a slow database or SIEM doesn't hold up the request.
+#[derive(Clone)]
+pub struct Hook(Arc<HookFn>);
+
+impl Hook {
+    pub fn new<F, Fut>(hook: F) -> Self
+    where
+        F: Fn(BouncerEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self(Arc::new(move |event| Box::pin(hook(event))))
+    }
+
+    pub(crate) fn fire(&self, event: BouncerEvent) {
+        tokio::spawn((self.0)(event));
+    }
+}
+
+impl fmt::Debug for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Hook")
+    }
+}
@@ -0,0 +1,87 @@
+//! The banlist as structured, serializable data.
+
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{Router, extract::State, routing::get};
+use serde::Serialize;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use ts_rs::TS;
+
+use crate::{
+    bouncer::{BouncerLayer, store::Ban},
+    error_location,
+    errors::{AppError, ErrorFormat},
+    response::{ResponseKey, WrappedJson},
+};
+
+/// A current ban, as listed by [`BouncerLayer::entries`], [`banlist_router`] and the admin
+/// endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[ts(export, export_to = "bouncer.ts")]
+pub struct BanEntry {
+    #[ts(type = "string")]
+    pub ip: IpAddr,
+    /// RFC 3339 timestamp.
+    pub expires_at: String,
+    pub reason: String,
+    /// Requests rejected while banned, counted by this instance.
+    #[ts(type = "number")]
+    pub hits: u64,
+}
+
+impl BanEntry {
+    pub(crate) fn new(ban: Ban, hits: u64) -> Self {
+        Self {
+            ip: ban.ip,
+            expires_at: OffsetDateTime::from(ban.expires_at)
+                .format(&Rfc3339)
+                .unwrap_or_default(),
+            reason: ban.reason,
+            hits,
+        }
+    }
+}
+
+impl ResponseKey for BanEntry {
+    fn response_key() -> &'static str {
+        "ban"
+    }
+}
+
+impl From<Ban> for BanEntry {
+    fn from(ban: Ban) -> Self {
+        Self::new(ban, 0)
+    }
+}
+
+/// `GET /bans`, listing current bans as `{ "bans": [{ "ip", "expires_at", "reason", "hits" }] }`.
+///
+/// The route has no authentication of its own, so put it behind yours, or use
+/// [`admin_router`](crate::bouncer::admin_router) for token-protected endpoints that
+/// can also add and lift bans.
+///
+/// ```rust, ignore
+/// let bouncer = BouncerLayer::new(config);
+/// let app = Router::new()
+///     .nest("/internal", banlist_router(&bouncer).route_layer(require_staff))
+///     .layer(bouncer);
+/// ```
+pub fn banlist_router(bouncer: &BouncerLayer) -> Router {
+    Router::new()
+        .route("/bans", get(list))
+        .with_state(Arc::new(bouncer.clone()))
+}
+
+async fn list(
+    State(bouncer): State<Arc<BouncerLayer>>,
+) -> Result<WrappedJson<Vec<BanEntry>>, AppError> {
+    let entries = bouncer.entries().await.map_err(|e| {
+        AppError::exception(
+            "Ban store unavailable",
+            Some(e),
+            error_location!(),
+            ErrorFormat::Json,
+        )
+    })?;
+    Ok(WrappedJson(entries))
+}
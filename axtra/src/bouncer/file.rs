@@ -0,0 +1,125 @@
+//! Bouncer rules loaded from a TOML or JSON file.
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    bouncer::{
+        BouncerConfig,
+        rules::{self, PRESETS},
+    },
+    net::IpNet,
+};
+
+/// A bouncer rules file. Every key is optional:
+///
+/// ```toml
+/// presets = ["wordpress", "git"]
+/// paths = ["/admin.php", "/wp-*"]
+/// regex = ['^/[a-z0-9]{8}\.php$']
+/// allow = ["10.0.0.0/8"]
+/// ban_networks = ["198.51.100.0/24"]
+/// ban_duration_secs = 3600
+/// strikes = 3
+/// strike_window_secs = 600
+/// ```
+///
+/// JSON files use the same keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RulesFile {
+    /// Where the file was loaded from.
+    #[serde(skip)]
+    pub path: PathBuf,
+    pub presets: Vec<String>,
+    /// Exact paths and glob patterns.
+    pub paths: Vec<String>,
+    pub regex: Vec<String>,
+    pub allow: Vec<String>,
+    pub ban_networks: Vec<String>,
+    pub ban_duration_secs: Option<u64>,
+    pub strikes: Option<u32>,
+    pub strike_window_secs: Option<u64>,
+}
+
+impl RulesFile {
+    /// Read a `.toml` or `.json` rules file, checking its presets, regexes and networks.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+        let mut file: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)
+                .map_err(|e| format!("invalid rules file {}: {e}", path.display()))?,
+            Some("json") => serde_json::from_str(&contents)
+                .map_err(|e| format!("invalid rules file {}: {e}", path.display()))?,
+            _ => {
+                return Err(
+                    format!("rules file {} must end in .toml or .json", path.display()).into(),
+                );
+            }
+        };
+        file.validate()
+            .map_err(|e| format!("invalid rules file {}: {e}", path.display()))?;
+        file.path = path.to_path_buf();
+        Ok(file)
+    }
+
+    /// Paths blocked by the file's presets and paths.
+    pub fn blocked_paths(&self) -> HashSet<String> {
+        let presets: Vec<&str> = self.presets.iter().map(String::as_str).collect();
+        let paths: Vec<&str> = self.paths.iter().map(String::as_str).collect();
+        rules::from_rules(&presets, &paths)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(preset) = self.presets.iter().find(|p| !PRESETS.contains(&p.as_str())) {
+            return Err(format!("unknown preset {preset:?}"));
+        }
+        for pattern in &self.regex {
+            regex::Regex::new(pattern).map_err(|e| format!("invalid regex {pattern:?}: {e}"))?;
+        }
+        let mut networks = self.allow.iter().chain(&self.ban_networks);
+        if let Some(network) = networks.find(|n| IpNet::parse(n).is_none()) {
+            return Err(format!("not an IP address or CIDR range: {network:?}"));
+        }
+        Ok(())
+    }
+}
+
+impl BouncerConfig {
+    /// Build a config from a rules file (see [`RulesFile`] for the format), so rules can
+    /// change without a rebuild. Builder methods can still be chained to add to it.
+    ///
+    /// With [`BouncerLayer::watch`] or [`BouncerLayer::reload_on_sighup`], the file's
+    /// presets, paths, regexes and allowlist are reloaded while running; ban durations,
+    /// strikes and banned networks are only read here.
+    ///
+    /// [`BouncerLayer::watch`]: crate::bouncer::BouncerLayer::watch
+    /// [`BouncerLayer::reload_on_sighup`]: crate::bouncer::BouncerLayer::reload_on_sighup
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let file = RulesFile::load(path)?;
+        let mut config = Self::from_rules(&[], &[]);
+        config.blocked_paths = file.blocked_paths();
+        config.blocked_regexes = file.regex.clone();
+        config.allowlist = file.allow.clone();
+        config.banned_networks = file.ban_networks.clone();
+        if let Some(secs) = file.ban_duration_secs {
+            config.ban_duration = Duration::from_secs(secs);
+        }
+        if let Some(strikes) = file.strikes {
+            config.strikes = strikes.max(1);
+        }
+        if let Some(secs) = file.strike_window_secs {
+            config.strike_window = Duration::from_secs(secs);
+        }
+        config.rules_file = Some(file);
+        Ok(config)
+    }
+}
@@ -0,0 +1,77 @@
+//! A handle for banning from application code.
+
+use std::{error::Error, net::IpAddr, time::Duration};
+
+use crate::bouncer::{
+    BouncerLayer,
+    export::BanEntry,
+    store::{Ban, StoreError},
+};
+
+/// Bans and unbans through the same store and network bans the bouncer enforces, for
+/// application code that spots abuse itself. Cheap to clone into handler state.
+///
+/// ```rust, ignore
+/// let bouncer = BouncerLayer::new(config);
+/// let state = AppState { bouncer: bouncer.handle(), .. };
+///
+/// // In a login handler
+/// if failed_attempts > 20 {
+///     state.bouncer.ban_ip(ip, Some(Duration::from_secs(86400)), "Credential stuffing").await?;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BouncerHandle {
+    bouncer: BouncerLayer,
+}
+
+impl BouncerHandle {
+    pub(crate) fn new(bouncer: BouncerLayer) -> Self {
+        Self { bouncer }
+    }
+
+    /// Ban an IP for `duration`, or the configured ban duration, returning the ban.
+    pub async fn ban_ip(
+        &self,
+        ip: IpAddr,
+        duration: Option<Duration>,
+        reason: &str,
+    ) -> Result<Ban, StoreError> {
+        self.bouncer.ban_ip(ip, duration, reason).await
+    }
+
+    /// Lift an IP ban, returning whether the IP was banned.
+    pub async fn unban_ip(&self, ip: IpAddr) -> Result<bool, StoreError> {
+        self.bouncer.unban_ip(ip).await
+    }
+
+    /// Whether requests from `ip` are rejected as banned, by an IP or network ban.
+    /// Allowlisted IPs never are.
+    pub async fn is_banned(&self, ip: IpAddr) -> Result<bool, StoreError> {
+        self.bouncer.is_banned(ip).await
+    }
+
+    /// Every current IP ban.
+    pub async fn bans(&self) -> Result<Vec<Ban>, StoreError> {
+        self.bouncer.bans().await
+    }
+
+    /// Every current IP ban with its reason and hit count, ready to serialize.
+    pub async fn entries(&self) -> Result<Vec<BanEntry>, StoreError> {
+        self.bouncer.entries().await
+    }
+
+    /// Ban an IP or CIDR range (`"198.51.100.0/24"`), for `duration` or permanently.
+    pub fn ban_network(
+        &self,
+        network: &str,
+        duration: Option<Duration>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.bouncer.ban_network(network, duration)
+    }
+
+    /// Lift a network ban, returning whether the network was banned.
+    pub fn unban_network(&self, network: &str) -> bool {
+        self.bouncer.unban_network(network)
+    }
+}
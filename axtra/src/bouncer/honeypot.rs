@@ -0,0 +1,200 @@
+//! Decoy routes that ban whoever requests them.
+
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::Response,
+    routing::any,
+};
+
+use crate::{
+    bouncer::{
+        BouncerLayer,
+        events::BouncerEvent,
+        layer::{BYPASS_HEADER, Exempt},
+        response::RejectionBody,
+    },
+    metrics,
+};
+
+// A bare login form, plausible enough for a scanner to keep going
+const DECOY_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Log In</title></head>
+<body>
+<form method="post">
+<label>Username <input type="text" name="log"></label>
+<label>Password <input type="password" name="pwd"></label>
+<button type="submit">Log In</button>
+</form>
+</body>
+</html>
+"#;
+
+// Request bodies up to this size are logged with a honeypot hit
+const MAX_LOGGED_BODY: usize = 4096;
+
+// Headers whose values are replaced before a honeypot hit is logged, since a misdirected
+// browser or client sends real credentials with them
+const REDACTED_HEADERS: [header::HeaderName; 4] = [
+    header::AUTHORIZATION,
+    header::PROXY_AUTHORIZATION,
+    header::COOKIE,
+    header::SET_COOKIE,
+];
+
+/// Honeypot routes: paths no legitimate client requests, answered with a decoy page after a
+/// delay while the caller is banned. Catches scanners probing paths the presets miss.
+///
+/// ```rust, ignore
+/// let bouncer = BouncerLayer::new(config);
+/// let app = Router::new()
+///     .merge(
+///         Honeypot::new(&["/admin.bak", "/backup/db.sql"])
+///             .delay(Duration::from_secs(5))
+///             .decoy(RejectionBody::html_file("dist/fake-admin.html"))
+///             .router(&bouncer),
+///     )
+///     .layer(bouncer);
+/// ```
+///
+/// Honeypot paths shouldn't also be blocked paths, or the bouncer rejects them before
+/// the decoy is served.
+#[derive(Debug, Clone)]
+pub struct Honeypot {
+    paths: Vec<String>,
+    delay: Duration,
+    status: StatusCode,
+    decoy: RejectionBody,
+}
+
+impl Honeypot {
+    /// Panics if `paths` is empty or a path doesn't start with `/`.
+    pub fn new(paths: &[&str]) -> Self {
+        assert!(!paths.is_empty(), "Honeypot needs at least one path");
+        for path in paths {
+            assert!(
+                path.starts_with('/'),
+                "Honeypot paths must start with '/', got {path:?}"
+            );
+        }
+        Self {
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+            delay: Duration::from_secs(2),
+            status: StatusCode::OK,
+            decoy: RejectionBody::html(DECOY_PAGE),
+        }
+    }
+
+    /// How long to wait before responding, tying up the scanner. Defaults to 2 seconds.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Status of the decoy response. Defaults to `200 OK`.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// The decoy response. Defaults to a bare login page.
+    pub fn decoy(mut self, decoy: RejectionBody) -> Self {
+        self.decoy = decoy;
+        self
+    }
+
+    /// Routes for every honeypot path and method, banning callers through `bouncer`.
+    pub fn router(self, bouncer: &BouncerLayer) -> Router {
+        let paths = self.paths.clone();
+        let trap = Arc::new(Trap {
+            honeypot: self,
+            bouncer: bouncer.clone(),
+        });
+        paths
+            .iter()
+            .fold(Router::new(), |router, path| {
+                router.route(path, any(spring))
+            })
+            .with_state(trap)
+    }
+}
+
+/// Honeypot routes for `paths` with the default delay and decoy page; see [`Honeypot`].
+pub fn honeypot_router(bouncer: &BouncerLayer, paths: &[&str]) -> Router {
+    Honeypot::new(paths).router(bouncer)
+}
+
+struct Trap {
+    honeypot: Honeypot,
+    bouncer: BouncerLayer,
+}
+
+async fn spring(State(trap): State<Arc<Trap>>, req: Request) -> Response {
+    let (mut parts, body) = req.into_parts();
+    let path = parts.uri.path().to_owned();
+    // Checked before logging so the bypass token never reaches the logs
+    let exempt =
+        parts.extensions.get::<Exempt>().is_some() || trap.bouncer.exempts(&path, &parts.headers);
+    parts.headers.remove(BYPASS_HEADER);
+    let body = match axum::body::to_bytes(body, MAX_LOGGED_BODY).await {
+        Ok(body) => String::from_utf8_lossy(&body).into_owned(),
+        Err(_) => format!("<over {MAX_LOGGED_BODY} bytes>"),
+    };
+    let client = trap.bouncer.client_ip(&parts.headers, &parts.extensions);
+    let user_agent = parts
+        .headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    tracing::warn!(
+        ip = ?client,
+        method = %parts.method,
+        uri = %parts.uri,
+        user_agent,
+        headers = ?redacted(&parts.headers),
+        body,
+        "Honeypot hit"
+    );
+    metrics::counter(
+        "axtra_bouncer_honeypot_hits_total",
+        "Requests to bouncer honeypot routes.",
+    )
+    .inc();
+
+    let event = BouncerEvent {
+        ip: client.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        path: path.clone(),
+        rule: None,
+        preset: None,
+        reason: format!("Honeypot {path}"),
+        ban_duration: None,
+    };
+    if let Some(ip) = client.filter(|ip| !exempt && !trap.bouncer.allows(*ip))
+        && trap.bouncer.ban_offender(event.clone()).await
+    {
+        tracing::warn!(ip = %ip, path, "Honeypot hit, IP banned");
+    }
+
+    tokio::time::sleep(trap.honeypot.delay).await;
+    trap.honeypot.decoy.render(trap.honeypot.status, &event)
+}
+
+// A copy of `headers` safe to log, with credential values replaced
+fn redacted(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for name in REDACTED_HEADERS {
+        // Removes every value, so repeated headers collapse into one placeholder
+        if headers.remove(&name).is_some() {
+            headers.insert(name, HeaderValue::from_static("[redacted]"));
+        }
+    }
+    headers
+}
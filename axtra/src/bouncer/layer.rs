@@ -1,26 +1,126 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
+    error::Error,
     future::Future,
-    net::{IpAddr, SocketAddr},
+    net::IpAddr,
     pin::Pin,
-    sync::Arc,
-    time::{Duration, Instant},
+    sync::{Arc, RwLock},
+    time::{Duration, Instant, SystemTime},
 };
 
-use axum::http::{self, Request, Response};
+use axum::{
+    body::Body,
+    http::{self, Request, Response},
+};
 use dashmap::DashMap;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
 use tower::{Layer, Service};
 
+#[cfg(feature = "bouncer-blocklists")]
+use crate::bouncer::blocklist::{self, Blocklist};
+#[cfg(feature = "bouncer-file")]
+use crate::bouncer::file::RulesFile;
+#[cfg(feature = "notifier")]
+use crate::{bouncer::notify::BanNotifier, notifier::Notifier};
+use crate::{
+    bouncer::{
+        events::{BouncerEvent, Hook},
+        export::BanEntry,
+        handle::BouncerHandle,
+        networks::NetworkBans,
+        response::RejectionBody,
+        rules::{Action, PRESETS, PathRules, Rule, preset_for},
+        store::{Ban, BanStore, Eviction, MAX_BAN_DURATION, MemoryBanStore, StoreError},
+    },
+    metrics,
+    net::{IpNet, client_ip, constant_time_eq},
+};
+
 pub type BanList = Arc<DashMap<IpAddr, Instant>>;
 
+// Blocked hits per IP that hasn't been banned yet, with when its window started
+type StrikeList = Arc<DashMap<IpAddr, (u32, Instant)>>;
+
+// Requests per banned IP while banned, with when the ban expires
+type HitList = Arc<DashMap<IpAddr, (u64, SystemTime)>>;
+
+/// Header carrying the [bypass token](BouncerConfig::bypass_token).
+pub const BYPASS_HEADER: &str = "x-bouncer-bypass";
+
+// Marks a request the bouncer let through by exempt path or bypass token, since the
+// token header is stripped before the app sees it
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Exempt;
+
+// Expired strike windows are swept once the strike list grows past this
+const STRIKE_SWEEP_THRESHOLD: usize = 1024;
+
+// How long after a ban notification further bans are batched, unless set
+#[cfg(feature = "notifier")]
+const DEFAULT_NOTIFY_BATCH: Duration = Duration::from_secs(60);
+
+// Add a hit for `ip` to `hits`, starting a new window if its last one is over, and return
+// the hits in its current window
+fn count_hit(hits: &StrikeList, ip: IpAddr, window: Duration, now: Instant) -> u32 {
+    if hits.len() >= STRIKE_SWEEP_THRESHOLD {
+        hits.retain(|_, (_, started)| now.duration_since(*started) < window);
+    }
+    let mut entry = hits.entry(ip).or_insert((0, now));
+    let (count, started) = entry.value_mut();
+    if now.duration_since(*started) >= window {
+        *count = 0;
+        *started = now;
+    }
+    *count += 1;
+    *count
+}
+
 #[derive(Debug, Clone)]
 pub struct BouncerConfig {
     pub blocked_paths: HashSet<String>,
+    pub blocked_regexes: Vec<String>,
+    /// Actions for individual rules, keyed by path, glob pattern or regex.
+    pub rule_actions: HashMap<String, Action>,
+    /// Actions for every rule in a preset, keyed by preset name.
+    pub preset_actions: HashMap<String, Action>,
     pub ban_duration: Duration,
     pub banned_status: http::StatusCode,
     pub blocked_status: http::StatusCode,
+    pub banned_body: RejectionBody,
+    pub blocked_body: RejectionBody,
+    pub ban_headers: bool,
+    pub dry_run: bool,
+    pub sweep_interval: Option<Duration>,
+    pub max_bans: usize,
+    pub eviction: Eviction,
     pub log_level: tracing::Level,
     pub trust_proxy: bool,
+    pub strikes: u32,
+    pub strike_window: Duration,
+    pub status_bans: u32,
+    pub status_window: Duration,
+    pub ban_statuses: Vec<http::StatusCode>,
+    pub allowlist: Vec<String>,
+    pub exempt_paths: HashSet<String>,
+    pub bypass_token: Option<String>,
+    pub banned_networks: Vec<String>,
+    pub range_bans: u32,
+    pub range_window: Duration,
+    pub range_prefix_v4: u8,
+    pub range_prefix_v6: u8,
+    pub ipv6_ban_prefix: u8,
+    pub on_block: Option<Hook>,
+    pub on_ban: Option<Hook>,
+    /// The rules file this config was loaded from, reloaded by
+    /// [`BouncerLayer::watch`] and [`BouncerLayer::reload_on_sighup`].
+    #[cfg(feature = "bouncer-file")]
+    pub rules_file: Option<RulesFile>,
+    #[cfg(feature = "bouncer-blocklists")]
+    pub blocklists: Vec<Blocklist>,
+    #[cfg(feature = "bouncer-blocklists")]
+    pub blocklist_refresh: Duration,
+    #[cfg(feature = "bouncer-blocklists")]
+    pub max_blocklist_entries: usize,
 }
 
 impl BouncerConfig {
@@ -28,11 +128,45 @@ impl BouncerConfig {
         let blocked_paths = crate::bouncer::rules::from_rules(presets, custom);
         Self {
             blocked_paths,
+            blocked_regexes: Vec::new(),
+            rule_actions: HashMap::new(),
+            preset_actions: HashMap::new(),
             ban_duration: Duration::from_secs(3600),
             banned_status: http::StatusCode::FORBIDDEN,
             blocked_status: http::StatusCode::FORBIDDEN,
+            banned_body: RejectionBody::Empty,
+            blocked_body: RejectionBody::Empty,
+            ban_headers: false,
+            dry_run: false,
+            sweep_interval: Some(Duration::from_secs(60)),
+            max_bans: 0,
+            eviction: Eviction::SoonestExpiry,
             log_level: tracing::Level::DEBUG,
             trust_proxy: false,
+            strikes: 1,
+            strike_window: Duration::from_secs(600),
+            status_bans: 0,
+            status_window: Duration::from_secs(600),
+            ban_statuses: vec![http::StatusCode::NOT_FOUND],
+            allowlist: Vec::new(),
+            exempt_paths: HashSet::new(),
+            bypass_token: None,
+            banned_networks: Vec::new(),
+            range_bans: 0,
+            range_window: Duration::from_secs(3600),
+            range_prefix_v4: 24,
+            range_prefix_v6: 48,
+            ipv6_ban_prefix: 64,
+            on_block: None,
+            on_ban: None,
+            #[cfg(feature = "bouncer-file")]
+            rules_file: None,
+            #[cfg(feature = "bouncer-blocklists")]
+            blocklists: Vec::new(),
+            #[cfg(feature = "bouncer-blocklists")]
+            blocklist_refresh: Duration::from_secs(6 * 3600),
+            #[cfg(feature = "bouncer-blocklists")]
+            max_blocklist_entries: 50_000,
         }
     }
 
@@ -44,6 +178,46 @@ impl BouncerConfig {
         Self::from_rules(&[], custom)
     }
 
+    /// Block paths matching `rule`, on top of the presets and custom paths.
+    pub fn rule(mut self, rule: Rule) -> Self {
+        match rule {
+            Rule::Regex(pattern) => self.blocked_regexes.push(pattern),
+            rule => {
+                self.blocked_paths.insert(rule.pattern());
+            }
+        }
+        self
+    }
+
+    /// Add `rule` with its own action, e.g. a longer ban for `.env` probes or no ban for
+    /// a retired internal path. Other rules [`Ban`](Action::Ban).
+    pub fn rule_action(mut self, rule: Rule, action: Action) -> Self {
+        self.rule_actions.insert(rule.pattern(), action);
+        self.rule(rule)
+    }
+
+    /// Set the action for every rule in `preset`. Rules given their own action with
+    /// [`rule_action`](Self::rule_action) keep it. The preset still has to be enabled.
+    ///
+    /// Panics if `preset` isn't a built-in preset.
+    pub fn preset_action(mut self, preset: &str, action: Action) -> Self {
+        assert!(
+            PRESETS.contains(&preset),
+            "BouncerConfig::preset_action needs a built-in preset, got {preset:?}"
+        );
+        self.preset_actions.insert(preset.to_string(), action);
+        self
+    }
+
+    // The action for a matched rule: its own, its preset's, or a ban
+    fn action_for(&self, rule: &str, preset: Option<&str>) -> Action {
+        self.rule_actions
+            .get(rule)
+            .or_else(|| self.preset_actions.get(preset?))
+            .copied()
+            .unwrap_or_default()
+    }
+
     pub fn banned_response(mut self, status: http::StatusCode) -> Self {
         self.banned_status = status;
         self
@@ -54,6 +228,55 @@ impl BouncerConfig {
         self
     }
 
+    /// Body for requests from banned IPs, e.g. a branded "Access denied" page.
+    pub fn banned_body(mut self, body: RejectionBody) -> Self {
+        self.banned_body = body;
+        self
+    }
+
+    /// Tell banned clients when access resumes, with `Retry-After` (seconds) and
+    /// `X-Ban-Expires` (RFC 3339) headers. Off by default, so bans stay opaque; permanent
+    /// network bans never send them.
+    pub fn ban_headers(mut self, enabled: bool) -> Self {
+        self.ban_headers = enabled;
+        self
+    }
+
+    /// Observe without enforcing: matches are logged, counted, count strikes and run
+    /// `on_block` hooks, but nothing is banned and every request is let through; would-be
+    /// bans are only logged and counted. For tuning rules in production before turning
+    /// enforcement on. Off by default.
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+
+    /// How often expired bans and strike counts are swept from memory in the background,
+    /// or `None` to only drop them when the same IP returns. Defaults to every minute.
+    pub fn sweep_interval(mut self, interval: Option<Duration>) -> Self {
+        assert!(
+            interval.is_none_or(|interval| !interval.is_zero()),
+            "sweep_interval must be non-zero"
+        );
+        self.sweep_interval = interval;
+        self
+    }
+
+    /// Hold at most `max` in-memory IP bans, so a distributed scan can't use unbounded
+    /// memory. Once full, expired bans are dropped first, then bans chosen by `eviction`.
+    /// `0` (the default) means unlimited. Doesn't apply to other [`BanStore`]s.
+    pub fn max_bans(mut self, max: usize, eviction: Eviction) -> Self {
+        self.max_bans = max;
+        self.eviction = eviction;
+        self
+    }
+
+    /// Body for requests to blocked paths, e.g. a honeypot response.
+    pub fn blocked_body(mut self, body: RejectionBody) -> Self {
+        self.blocked_body = body;
+        self
+    }
+
     pub fn duration(mut self, duration: Duration) -> Self {
         self.ban_duration = duration;
         self
@@ -68,26 +291,702 @@ impl BouncerConfig {
         self.trust_proxy = trust;
         self
     }
+
+    /// Never block or ban these IPs or CIDR ranges (`"10.0.0.0/8"`, `"203.0.113.7"`),
+    /// e.g. health checkers, office ranges and uptime monitors.
+    ///
+    /// Panics if an entry isn't an IP address or CIDR range.
+    pub fn allow(mut self, networks: &[&str]) -> Self {
+        for network in networks {
+            assert!(
+                IpNet::parse(network).is_some(),
+                "BouncerConfig::allow needs IP addresses or CIDR ranges, got {network:?}"
+            );
+            self.allowlist.push((*network).to_string());
+        }
+        self
+    }
+
+    /// Never check requests to these paths or glob patterns (`"/health"`, `"/internal/*"`),
+    /// even from banned IPs, so probes and scrapers always get through.
+    pub fn exempt_paths(mut self, paths: &[&str]) -> Self {
+        self.exempt_paths
+            .extend(paths.iter().map(|path| path.to_string()));
+        self
+    }
+
+    /// Skip every check for requests sending `X-Bouncer-Bypass: <token>`, for smoke tests
+    /// and pentests that would otherwise get the runner banned. The header is removed
+    /// before the request reaches the app.
+    ///
+    /// Panics if `token` is empty.
+    pub fn bypass_token(mut self, token: impl Into<String>) -> Self {
+        let token = token.into();
+        assert!(
+            !token.is_empty(),
+            "BouncerConfig::bypass_token needs a non-empty token"
+        );
+        self.bypass_token = Some(token);
+        self
+    }
+
+    /// Permanently ban these IPs or CIDR ranges (`"198.51.100.0/24"`, `"2001:db8::/32"`).
+    ///
+    /// Panics if an entry isn't an IP address or CIDR range.
+    pub fn ban_networks(mut self, networks: &[&str]) -> Self {
+        for network in networks {
+            assert!(
+                IpNet::parse(network).is_some(),
+                "BouncerConfig::ban_networks needs IP addresses or CIDR ranges, got {network:?}"
+            );
+            self.banned_networks.push((*network).to_string());
+        }
+        self
+    }
+
+    /// Ban a whole range for the ban duration once `count` IPs in it are banned within
+    /// `window`, for scanners rotating through a subnet. Ranges are /24 for IPv4 and /48
+    /// for IPv6 unless set with [`range_prefixes`](Self::range_prefixes). Off by default.
+    pub fn range_bans(mut self, count: u32, window: Duration) -> Self {
+        self.range_bans = count;
+        self.range_window = window;
+        self
+    }
+
+    /// Prefix lengths of the ranges counted by [`range_bans`](Self::range_bans).
+    pub fn range_prefixes(mut self, v4: u8, v6: u8) -> Self {
+        assert!(
+            v4 <= 32 && v6 <= 128,
+            "BouncerConfig::range_prefixes needs prefixes of at most 32 (IPv4) and 128 (IPv6) bits"
+        );
+        self.range_prefix_v4 = v4;
+        self.range_prefix_v6 = v6;
+        self
+    }
+
+    /// Ban an IP once the app answers it with `count` 404s within `window`, catching
+    /// scanners probing paths no rule covers. Other statuses can be counted with
+    /// [`ban_statuses`](Self::ban_statuses). Off by default.
+    pub fn status_bans(mut self, count: u32, window: Duration) -> Self {
+        self.status_bans = count;
+        self.status_window = window;
+        self
+    }
+
+    /// Response statuses counted by [`status_bans`](Self::status_bans), 404 by default.
+    ///
+    /// Panics if `statuses` is empty.
+    pub fn ban_statuses(mut self, statuses: &[http::StatusCode]) -> Self {
+        assert!(
+            !statuses.is_empty(),
+            "BouncerConfig::ban_statuses needs at least one status"
+        );
+        self.ban_statuses = statuses.to_vec();
+        self
+    }
+
+    /// Ban IPv6 clients by their /`prefix` network rather than the single address, since
+    /// a client can rotate through its whole allocation at will. Defaults to /64, the
+    /// usual per-subscriber allocation; 128 bans single addresses. IPv4 bans are always
+    /// per address.
+    ///
+    /// Panics if `prefix` is 0 or over 128.
+    pub fn ipv6_ban_prefix(mut self, prefix: u8) -> Self {
+        assert!(
+            (1..=128).contains(&prefix),
+            "BouncerConfig::ipv6_ban_prefix needs a prefix of 1 to 128 bits, got {prefix}"
+        );
+        self.ipv6_ban_prefix = prefix;
+        self
+    }
+
+    /// Run `hook` for every request blocked by a path rule, e.g. to record it in your own
+    /// database, metrics or SIEM. Hooks run on their own task.
+    ///
+    /// ```rust, ignore
+    /// let config = BouncerConfig::from_preset_rules(&["wordpress"])
+    ///     .on_block(move |event| {
+    ///         let db = db.clone();
+    ///         async move { record_block(&db, &event.ip, &event.path, event.rule).await }
+    ///     });
+    /// ```
+    pub fn on_block<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(BouncerEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_block = Some(Hook::new(hook));
+        self
+    }
+
+    /// Run `hook` whenever an IP is banned, with the ban duration set on the event.
+    pub fn on_ban<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(BouncerEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_ban = Some(Hook::new(hook));
+        self
+    }
+
+    /// Reject networks on an external blocklist, fetched when the layer is created and on
+    /// the [refresh interval](Self::blocklist_refresh). Entries are network bans lifted
+    /// when they leave the list; failed fetches keep the previous entries.
+    #[cfg(feature = "bouncer-blocklists")]
+    pub fn blocklist(mut self, list: Blocklist) -> Self {
+        self.blocklists.push(list);
+        self
+    }
+
+    /// How often blocklists are fetched. Defaults to every 6 hours; most feeds ask for no
+    /// more than once an hour.
+    #[cfg(feature = "bouncer-blocklists")]
+    pub fn blocklist_refresh(mut self, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "blocklist_refresh must be non-zero");
+        self.blocklist_refresh = interval;
+        self
+    }
+
+    /// Most entries taken from each blocklist, 50,000 by default.
+    #[cfg(feature = "bouncer-blocklists")]
+    pub fn max_blocklist_entries(mut self, max: usize) -> Self {
+        self.max_blocklist_entries = max;
+        self
+    }
+
+    /// Only ban an IP after `count` blocked hits within `window`, fail2ban-style, so one
+    /// stray request from a shared NAT doesn't ban everyone behind it. Hits below the
+    /// threshold are still blocked. Defaults to banning on the first hit.
+    pub fn strikes(mut self, count: u32, window: Duration) -> Self {
+        self.strikes = count.max(1);
+        self.strike_window = window;
+        self
+    }
+}
+
+// Blocked path rules and the allowlist, swapped together when a rules file is reloaded
+#[derive(Debug)]
+struct Filters {
+    rules: PathRules,
+    allowlist: Vec<IpNet>,
+}
+
+impl Filters {
+    fn new(paths: &HashSet<String>, regexes: &[String], allowlist: &[String]) -> Self {
+        Self {
+            rules: PathRules::new(paths, regexes),
+            allowlist: allowlist
+                .iter()
+                .filter_map(|network| IpNet::parse(network))
+                .collect(),
+        }
+    }
 }
 
 // BouncerLayer factory
 #[derive(Debug, Clone)]
 pub struct BouncerLayer {
-    config: BouncerConfig,
-    banlist: BanList,
+    config: Arc<BouncerConfig>,
+    filters: Arc<RwLock<Arc<Filters>>>,
+    exempt: Arc<PathRules>,
+    memory: MemoryBanStore,
+    store: Arc<dyn BanStore>,
+    strikes: StrikeList,
+    status_hits: StrikeList,
+    hits: HitList,
+    networks: Arc<NetworkBans>,
+    #[cfg(feature = "notifier")]
+    notifier: Option<BanNotifier>,
+    #[cfg(feature = "notifier")]
+    notify_batch: Duration,
 }
 
 impl BouncerLayer {
     pub fn new(config: BouncerConfig) -> Self {
-        Self {
-            config,
-            banlist: Arc::new(DashMap::new()),
+        let store = MemoryBanStore::new().max_entries(config.max_bans, config.eviction);
+        let layer = Self {
+            filters: Arc::new(RwLock::new(Arc::new(Filters::new(
+                &config.blocked_paths,
+                &config.blocked_regexes,
+                &config.allowlist,
+            )))),
+            exempt: Arc::new(PathRules::new(&config.exempt_paths, &[])),
+            networks: Arc::new(NetworkBans::new(
+                config
+                    .banned_networks
+                    .iter()
+                    .filter_map(|network| IpNet::parse(network)),
+            )),
+            config: Arc::new(config),
+            memory: store.clone(),
+            store: Arc::new(store),
+            strikes: Arc::new(DashMap::new()),
+            status_hits: Arc::new(DashMap::new()),
+            hits: Arc::new(DashMap::new()),
+            #[cfg(feature = "notifier")]
+            notifier: None,
+            #[cfg(feature = "notifier")]
+            notify_batch: DEFAULT_NOTIFY_BATCH,
+        };
+        if let Some(interval) = layer.config.sweep_interval {
+            layer.spawn_sweeper(interval);
+        }
+        #[cfg(feature = "bouncer-blocklists")]
+        layer.spawn_blocklists();
+        layer
+    }
+
+    // Purge expired entries every `interval` until the last layer clone is dropped. Needs
+    // a Tokio runtime; without one, entries are only dropped as they're looked up.
+    fn spawn_sweeper(&self, interval: Duration) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::debug!("No Tokio runtime, bouncer expiry sweeper not started");
+            return;
+        };
+        // Only layer clones keep the config alive, so the task stops once they're gone
+        let config = Arc::downgrade(&self.config);
+        let (memory, strikes, status_hits, hits, networks) = (
+            self.memory.clone(),
+            self.strikes.clone(),
+            self.status_hits.clone(),
+            self.hits.clone(),
+            self.networks.clone(),
+        );
+        runtime.spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Some(config) = config.upgrade() else {
+                    break;
+                };
+                let purged = purge(&config, &memory, &strikes, &status_hits, &hits, &networks);
+                if purged > 0 {
+                    tracing::debug!(purged, "Purged expired bouncer bans");
+                }
+            }
+        });
+    }
+
+    // Fetch and refresh every configured blocklist, if there's a Tokio runtime
+    #[cfg(feature = "bouncer-blocklists")]
+    fn spawn_blocklists(&self) {
+        if self.config.blocklists.is_empty() {
+            return;
+        }
+        if tokio::runtime::Handle::try_current().is_err() {
+            tracing::warn!("No Tokio runtime, bouncer blocklists not fetched");
+            return;
         }
+        for list in &self.config.blocklists {
+            blocklist::spawn_refresh(
+                list.clone(),
+                Arc::downgrade(&self.config),
+                self.networks.clone(),
+                self.config.blocklist_refresh,
+                self.config.max_blocklist_entries,
+            );
+        }
+    }
+
+    // The current rules and allowlist
+    fn filters(&self) -> Arc<Filters> {
+        self.filters
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
     }
 
-    /// Expose banlist for observability
+    /// Re-read the rules file the config was loaded from, replacing its presets, paths,
+    /// regexes and allowlist. Rules added in code are kept. On error, the current rules
+    /// stay in place.
+    #[cfg(feature = "bouncer-file")]
+    pub fn reload(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        reload_rules(&self.config, &self.filters)
+    }
+
+    /// Reload the rules file whenever it changes, checking its modification time every
+    /// `interval`. Stops once every clone of the layer is dropped.
+    ///
+    /// Panics if the config wasn't loaded with [`BouncerConfig::from_file`].
+    #[cfg(feature = "bouncer-file")]
+    pub fn watch(self, interval: Duration) -> Self {
+        let path = self.rules_path("watch");
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("No Tokio runtime, bouncer rules file not watched");
+            return self;
+        };
+        let config = Arc::downgrade(&self.config);
+        let filters = self.filters.clone();
+        runtime.spawn(async move {
+            let modified = || std::fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+            let mut last = modified();
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Some(config) = config.upgrade() else {
+                    break;
+                };
+                let current = modified();
+                if current == last {
+                    continue;
+                }
+                last = current;
+                if let Err(e) = reload_rules(&config, &filters) {
+                    tracing::warn!(error = %e, "Bouncer rules reload failed, keeping current rules");
+                }
+            }
+        });
+        self
+    }
+
+    /// Reload the rules file when the process receives `SIGHUP`.
+    ///
+    /// Panics if the config wasn't loaded with [`BouncerConfig::from_file`].
+    #[cfg(all(feature = "bouncer-file", unix))]
+    pub fn reload_on_sighup(self) -> Self {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        self.rules_path("reload_on_sighup");
+        if tokio::runtime::Handle::try_current().is_err() {
+            tracing::warn!("No Tokio runtime, bouncer rules not reloaded on SIGHUP");
+            return self;
+        }
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                tracing::warn!(error = %e, "Couldn't listen for SIGHUP to reload bouncer rules");
+                return self;
+            }
+        };
+        let config = Arc::downgrade(&self.config);
+        let filters = self.filters.clone();
+        tokio::spawn(async move {
+            while hangups.recv().await.is_some() {
+                let Some(config) = config.upgrade() else {
+                    break;
+                };
+                if let Err(e) = reload_rules(&config, &filters) {
+                    tracing::warn!(error = %e, "Bouncer rules reload failed, keeping current rules");
+                }
+            }
+        });
+        self
+    }
+
+    #[cfg(feature = "bouncer-file")]
+    fn rules_path(&self, method: &str) -> std::path::PathBuf {
+        match &self.config.rules_file {
+            Some(file) => file.path.clone(),
+            None => panic!("BouncerLayer::{method} needs a config from BouncerConfig::from_file"),
+        }
+    }
+
+    /// Keep bans in `store` instead of in memory, e.g. a [`RedisBanStore`] or
+    /// [`PostgresBanStore`] so bans survive restarts and are shared across replicas.
+    ///
+    /// [`RedisBanStore`]: crate::bouncer::store::RedisBanStore
+    /// [`PostgresBanStore`]: crate::bouncer::store::PostgresBanStore
+    pub fn store(mut self, store: impl BanStore + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// Post a message through `notifier` when an IP is banned, with its reason and ban
+    /// duration. Bans within a minute of the last message are batched into one summary;
+    /// change the window with [`notify_batch`](Self::notify_batch).
+    #[cfg(feature = "notifier")]
+    pub fn notify(mut self, notifier: Notifier) -> Self {
+        self.notifier = Some(BanNotifier::new(notifier, self.notify_batch));
+        self
+    }
+
+    /// How long after a ban notification further bans are batched, whether set before or
+    /// after [`notify`](Self::notify). Defaults to one minute.
+    #[cfg(feature = "notifier")]
+    pub fn notify_batch(mut self, window: Duration) -> Self {
+        self.notify_batch = window;
+        if let Some(notifier) = &mut self.notifier {
+            notifier.window(window);
+        }
+        self
+    }
+
+    /// The in-memory banlist. Only holds bans made with the default in-memory store; use
+    /// [`entries`](Self::entries) for serializable bans from any store.
     pub fn banlist(&self) -> Arc<DashMap<IpAddr, Instant>> {
-        self.banlist.clone()
+        self.memory.banlist()
+    }
+
+    /// Drop expired in-memory bans, network bans and strike counts, returning how many
+    /// bans were dropped. Runs on the sweep interval; Redis and Postgres stores expire
+    /// their own bans.
+    pub fn purge_expired(&self) -> usize {
+        purge(
+            &self.config,
+            &self.memory,
+            &self.strikes,
+            &self.status_hits,
+            &self.hits,
+            &self.networks,
+        )
+    }
+
+    /// Every current IP ban in the store
+    pub async fn bans(&self) -> Result<Vec<Ban>, StoreError> {
+        self.store.list().await
+    }
+
+    /// Every current IP ban with its reason and hit count, ready to serialize
+    pub async fn entries(&self) -> Result<Vec<BanEntry>, StoreError> {
+        let bans = self.store.list().await?;
+        Ok(bans
+            .into_iter()
+            .map(|ban| {
+                let hits = self.hits.get(&ban.ip).map_or(0, |entry| entry.0);
+                BanEntry::new(ban, hits)
+            })
+            .collect())
+    }
+
+    /// A cloneable handle for banning and unbanning from application code, e.g. an auth
+    /// module detecting credential stuffing
+    pub fn handle(&self) -> BouncerHandle {
+        BouncerHandle::new(self.clone())
+    }
+
+    /// The address bans of `ip` are stored under: IPv6 addresses are masked to the
+    /// configured ban prefix, so every address in it shares one ban
+    pub(crate) fn ban_key(&self, ip: IpAddr) -> IpAddr {
+        match ip.to_canonical() {
+            IpAddr::V4(ip) => IpAddr::V4(ip),
+            IpAddr::V6(_) => {
+                IpNet::new(ip, self.config.ipv6_ban_prefix).map_or(ip, |network| network.addr())
+            }
+        }
+    }
+
+    /// Ban an IP for `duration`, or the configured ban duration, returning the ban. IPv6
+    /// bans cover the IP's [ban prefix](BouncerConfig::ipv6_ban_prefix).
+    pub async fn ban_ip(
+        &self,
+        ip: IpAddr,
+        duration: Option<Duration>,
+        reason: &str,
+    ) -> Result<Ban, StoreError> {
+        let duration = duration
+            .unwrap_or(self.config.ban_duration)
+            .min(MAX_BAN_DURATION);
+        let ip = self.ban_key(ip);
+        self.store.ban(ip, duration, reason).await?;
+        Ok(Ban {
+            ip,
+            expires_at: SystemTime::now() + duration,
+            reason: reason.to_string(),
+        })
+    }
+
+    /// Lift an IP ban, returning whether the IP was banned
+    pub async fn unban_ip(&self, ip: IpAddr) -> Result<bool, StoreError> {
+        let key = self.ban_key(ip);
+        self.hits.remove(&key);
+        self.store.unban(key).await
+    }
+
+    /// Whether requests from `ip` are rejected as banned, by an IP or network ban.
+    /// Allowlisted IPs are let through regardless, so they're never reported as banned.
+    pub async fn is_banned(&self, ip: IpAddr) -> Result<bool, StoreError> {
+        if self.allows(ip) {
+            return Ok(false);
+        }
+        Ok(self.store.banned_until(self.ban_key(ip)).await?.is_some()
+            || self.networks.find(ip).is_some())
+    }
+
+    /// Ban an IP or CIDR range (`"198.51.100.0/24"`), for `duration` or permanently
+    pub fn ban_network(
+        &self,
+        network: &str,
+        duration: Option<Duration>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let network = IpNet::parse(network)
+            .ok_or_else(|| format!("not an IP address or CIDR range: {network:?}"))?;
+        let expiry = duration.map(|duration| Instant::now() + duration.min(MAX_BAN_DURATION));
+        self.networks.insert(network, expiry);
+        Ok(())
+    }
+
+    /// Lift a network ban, returning whether the network was banned
+    pub fn unban_network(&self, network: &str) -> bool {
+        IpNet::parse(network).is_some_and(|network| self.networks.remove(&network))
+    }
+
+    /// Banned networks in CIDR notation, with their expiry (`None` if permanent)
+    pub fn banned_networks(&self) -> Vec<(String, Option<Instant>)> {
+        self.networks
+            .list()
+            .into_iter()
+            .map(|(network, expiry)| (network.to_string(), expiry))
+            .collect()
+    }
+
+    /// Count a strike against the client making a request, for other routes that catch
+    /// attacks, banning it once it reaches the configured strikes
+    pub(crate) async fn ban_client(
+        &self,
+        headers: &http::HeaderMap,
+        extensions: &http::Extensions,
+        path: &str,
+        reason: &str,
+    ) {
+        let Some(ip) = self
+            .client_ip(headers, extensions)
+            .filter(|ip| !self.allows(*ip))
+        else {
+            return;
+        };
+        let event = BouncerEvent {
+            ip,
+            path: path.to_string(),
+            rule: None,
+            preset: None,
+            reason: format!("{reason} ({path})"),
+            ban_duration: None,
+        };
+        if self.strike(event).await {
+            let msg = format!("{reason}, IP banned");
+            log_event(self.config.log_level, &ip, path, &msg, false, true);
+        }
+    }
+
+    /// The client making a request, from proxy headers if they're trusted
+    pub(crate) fn client_ip(
+        &self,
+        headers: &http::HeaderMap,
+        extensions: &http::Extensions,
+    ) -> Option<IpAddr> {
+        client_ip(headers, extensions, self.config.trust_proxy)
+    }
+
+    /// Whether `ip` is allowlisted
+    pub(crate) fn allows(&self, ip: IpAddr) -> bool {
+        allowed(&self.filters().allowlist, ip)
+    }
+
+    /// Whether a request skips the bouncer, by exempt path or bypass token
+    pub(crate) fn exempts(&self, path: &str, headers: &http::HeaderMap) -> bool {
+        if self.exempt.matched(path).is_some() {
+            return true;
+        }
+        let Some(token) = &self.config.bypass_token else {
+            return false;
+        };
+        let bypassed = headers
+            .get(BYPASS_HEADER)
+            .is_some_and(|value| constant_time_eq(token.as_bytes(), value.as_bytes()));
+        if bypassed {
+            tracing::debug!(path, "Bouncer bypassed with token");
+        }
+        bypassed
+    }
+
+    // Record a blocked hit from `ip`, banning it once it reaches the configured strikes
+    // within the window. Returns whether the IP was banned.
+    async fn strike(&self, event: BouncerEvent) -> bool {
+        let config = &self.config;
+        let ip = self.ban_key(event.ip);
+        let now = Instant::now();
+        let hits = match config.strikes {
+            0 | 1 => 1,
+            _ => count_hit(&self.strikes, ip, config.strike_window, now),
+        };
+        if hits < config.strikes {
+            return false;
+        }
+        self.strikes.remove(&ip);
+        self.ban_offender(event).await
+    }
+
+    // Count a response with a ban status to `ip`, banning it once it gets the configured
+    // number within the window
+    async fn count_status(&self, ip: IpAddr, path: &str, status: http::StatusCode) {
+        let config = &self.config;
+        let key = self.ban_key(ip);
+        let hits = count_hit(&self.status_hits, key, config.status_window, Instant::now());
+        if hits < config.status_bans {
+            return;
+        }
+        self.status_hits.remove(&key);
+        let event = BouncerEvent {
+            ip,
+            path: path.to_string(),
+            rule: None,
+            preset: None,
+            reason: format!("Repeated {} responses ({path})", status.as_u16()),
+            ban_duration: None,
+        };
+        if self.ban_offender(event).await {
+            let msg = format!("{hits} {} responses, IP banned", status.as_u16());
+            log_event(config.log_level, &ip, path, &msg, false, true);
+        }
+    }
+
+    /// Ban the IP behind `event` for `event.ban_duration`, or the configured duration if
+    /// it's unset, notifying and running the `on_ban` hook, and ban its range once enough
+    /// of its IPs are banned. Returns whether the ban was stored. In dry run the ban is
+    /// only logged and counted.
+    pub(crate) async fn ban_offender(&self, mut event: BouncerEvent) -> bool {
+        let config = &self.config;
+        let ip = self.ban_key(event.ip);
+        let duration = event
+            .ban_duration
+            .unwrap_or(config.ban_duration)
+            .min(MAX_BAN_DURATION);
+        if config.dry_run {
+            let msg = format!("IP would be banned for {}s (dry run)", duration.as_secs());
+            log_event(config.log_level, &ip, &event.path, &msg, false, true);
+            dry_run_matches().inc_with(&[("reason", "ban")]);
+            return false;
+        }
+        if let Err(e) = self.store.ban(ip, duration, &event.reason).await {
+            tracing::warn!(ip = %ip, error = %e, "Ban store update failed");
+            return false;
+        }
+        metrics::counter("axtra_bouncer_bans_total", "IPs banned by the bouncer.").inc();
+        event.ban_duration = Some(duration);
+        #[cfg(feature = "notifier")]
+        if let Some(notifier) = &self.notifier {
+            notifier.observe(event.clone());
+        }
+        if let Some(hook) = &config.on_ban {
+            hook.fire(event);
+        }
+
+        if config.range_bans > 0 {
+            let prefix = match ip.to_canonical() {
+                IpAddr::V4(_) => config.range_prefix_v4,
+                IpAddr::V6(_) => config.range_prefix_v6,
+            };
+            if let Some(range) = self.networks.record_offense(
+                ip,
+                prefix,
+                config.range_bans,
+                config.range_window,
+                config.ban_duration.min(MAX_BAN_DURATION),
+            ) {
+                metrics::counter(
+                    "axtra_bouncer_network_bans_total",
+                    "Networks banned by the bouncer.",
+                )
+                .inc();
+                let msg = format!("Repeated bans from {range}, network banned");
+                log_event(config.log_level, &ip, "", &msg, false, true);
+            }
+        }
+        true
     }
 }
 
@@ -97,8 +996,7 @@ impl<S> Layer<S> for BouncerLayer {
     fn layer(&self, inner: S) -> Self::Service {
         BouncerMiddleware {
             inner,
-            config: self.config.clone(),
-            banlist: self.banlist.clone(),
+            bouncer: self.clone(),
         }
     }
 }
@@ -107,8 +1005,7 @@ impl<S> Layer<S> for BouncerLayer {
 #[derive(Debug, Clone)]
 pub struct BouncerMiddleware<S> {
     inner: S,
-    config: BouncerConfig,
-    banlist: BanList,
+    bouncer: BouncerLayer,
 }
 
 impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for BouncerMiddleware<S>
@@ -116,7 +1013,7 @@ where
     S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
     S::Future: Send + 'static,
     ReqBody: Send + 'static,
-    ResBody: Default + Send + 'static,
+    ResBody: From<Body> + Send + 'static,
 {
     type Response = Response<ResBody>;
     type Error = S::Error;
@@ -129,57 +1026,224 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
-        let config = self.config.clone();
-        let banlist = self.banlist.clone();
-
-        let ip = extract_ip(&req, config.trust_proxy);
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let bouncer = self.bouncer.clone();
+        let config = bouncer.config.clone();
+        let filters = bouncer.filters();
         let path = req.uri().path().to_owned();
 
+        // Exempt and allowlisted requests skip the bouncer entirely, before the banlist is
+        // consulted
+        let exempt = bouncer.exempts(&path, req.headers());
+        req.headers_mut().remove(BYPASS_HEADER);
+        if exempt {
+            req.extensions_mut().insert(Exempt);
+        }
+        let ip = client_ip(req.headers(), req.extensions(), config.trust_proxy)
+            .filter(|ip| !exempt && !allowed(&filters.allowlist, *ip));
+
         let clone = self.inner.clone();
         let mut inner = std::mem::replace(&mut self.inner, clone);
 
         Box::pin(async move {
             if let Some(ip) = ip {
-                if let Some(&expiry) = banlist.get(&ip).as_deref() {
-                    if Instant::now() < expiry {
-                        log_event(
-                            config.log_level,
-                            &ip,
-                            &path,
-                            "Banned IP attempted access",
-                            true,
-                            false,
-                        );
-                        let mut res = Response::default();
-                        *res.status_mut() = config.banned_status;
-                        return Ok(res);
-                    } else {
-                        banlist.remove(&ip);
+                // An unreachable store lets requests through rather than taking the site down
+                let key = bouncer.ban_key(ip);
+                let banned = match bouncer.store.banned_until(key).await {
+                    Ok(until) => until.map(|until| {
+                        let mut hits = bouncer.hits.entry(key).or_insert((0, until));
+                        *hits = (hits.0 + 1, until);
+                        ("Banned IP attempted access", Some(until))
+                    }),
+                    Err(e) => {
+                        tracing::warn!(ip = %ip, error = %e, "Ban store lookup failed");
+                        None
                     }
+                };
+                let banned = banned.or_else(|| {
+                    let (_, expiry) = bouncer.networks.find(ip)?;
+                    let until = expiry.map(|expiry| {
+                        SystemTime::now() + expiry.saturating_duration_since(Instant::now())
+                    });
+                    Some(("Banned network attempted access", until))
+                });
+                if let Some((msg, until)) = banned {
+                    // Banned IPs skip the path rules, as they would if rejected
+                    if config.dry_run {
+                        let msg = format!("{msg} (dry run, allowed)");
+                        log_event(config.log_level, &ip, &path, &msg, true, false);
+                        dry_run_matches().inc_with(&[("reason", "banned")]);
+                        return inner.call(req).await;
+                    }
+                    log_event(config.log_level, &ip, &path, msg, true, false);
+                    rejections().inc_with(&[("reason", "banned")]);
+                    let event = BouncerEvent {
+                        ip,
+                        path,
+                        rule: None,
+                        preset: None,
+                        reason: msg.to_string(),
+                        ban_duration: None,
+                    };
+                    let mut res = config.banned_body.render(config.banned_status, &event);
+                    if config.ban_headers
+                        && let Some(until) = until
+                    {
+                        set_ban_headers(res.headers_mut(), until);
+                    }
+                    return Ok(res.map(ResBody::from));
                 }
 
-                if config.blocked_paths.contains(&path) {
-                    banlist.insert(ip, Instant::now() + config.ban_duration);
-                    log_event(
-                        config.log_level,
-                        &ip,
-                        &path,
-                        "Blocked path accessed, IP banned",
-                        false,
-                        true,
-                    );
-                    let mut res = Response::default();
-                    *res.status_mut() = config.blocked_status;
-                    return Ok(res);
+                if let Some(rule) = filters.rules.matched(&path) {
+                    let preset = preset_for(rule);
+                    let reason = match preset {
+                        Some(preset) => format!("Blocked path {path} (preset {preset})"),
+                        None if rule == path => format!("Blocked path {path}"),
+                        None => format!("Blocked path {path} (rule {rule})"),
+                    };
+                    let event = BouncerEvent {
+                        ip,
+                        path: path.clone(),
+                        rule: Some(rule.to_string()),
+                        preset,
+                        reason,
+                        ban_duration: None,
+                    };
+                    if let Some(hook) = &config.on_block {
+                        hook.fire(event.clone());
+                    }
+                    let banned = match config.action_for(rule, preset) {
+                        Action::Ban => bouncer.strike(event.clone()).await,
+                        Action::BanFor(duration) => {
+                            let ban = BouncerEvent {
+                                ban_duration: Some(duration),
+                                ..event.clone()
+                            };
+                            bouncer.strike(ban).await
+                        }
+                        Action::Block => false,
+                    };
+                    let msg = match banned {
+                        true => "Blocked path accessed, IP banned",
+                        false => "Blocked path accessed",
+                    };
+                    if config.dry_run {
+                        let msg = format!("{msg} (dry run, allowed)");
+                        log_event(config.log_level, &ip, &path, &msg, false, true);
+                        dry_run_matches().inc_with(&[("reason", "blocked")]);
+                        return inner.call(req).await;
+                    }
+                    log_event(config.log_level, &ip, &path, msg, false, true);
+                    rejections().inc_with(&[("reason", "blocked")]);
+                    let res = config.blocked_body.render(config.blocked_status, &event);
+                    return Ok(res.map(ResBody::from));
                 }
             }
 
-            inner.call(req).await
+            let res = inner.call(req).await?;
+            if let Some(ip) = ip
+                && config.status_bans > 0
+                && config.ban_statuses.contains(&res.status())
+            {
+                bouncer.count_status(ip, &path, res.status()).await;
+            }
+            Ok(res)
         })
     }
 }
 
+// `Retry-After` in whole seconds, rounded up, and `X-Ban-Expires` as RFC 3339
+fn set_ban_headers(headers: &mut http::HeaderMap, until: SystemTime) {
+    let remaining = until.duration_since(SystemTime::now()).unwrap_or_default();
+    let retry_after = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    headers.insert(http::header::RETRY_AFTER, retry_after.into());
+    if let Ok(expires) = OffsetDateTime::from(until)
+        .replace_nanosecond(0)
+        .map_err(|_| ())
+        .and_then(|until| until.format(&Rfc3339).map_err(|_| ()))
+        && let Ok(value) = http::HeaderValue::from_str(&expires)
+    {
+        headers.insert("x-ban-expires", value);
+    }
+}
+
+// Drop expired in-memory bans, network bans, strike, status and hit counts, returning
+// how many bans were dropped
+fn purge(
+    config: &BouncerConfig,
+    memory: &MemoryBanStore,
+    strikes: &StrikeList,
+    status_hits: &StrikeList,
+    hits: &HitList,
+    networks: &NetworkBans,
+) -> usize {
+    let wall = SystemTime::now();
+    hits.retain(|_, (_, until)| *until > wall);
+    let now = Instant::now();
+    strikes.retain(|_, (_, started)| now.duration_since(*started) < config.strike_window);
+    status_hits.retain(|_, (_, started)| now.duration_since(*started) < config.status_window);
+    memory.purge_expired() + networks.purge_expired(config.range_window)
+}
+
+// Rebuild the filters from the config, swapping what its rules file contributed at
+// startup for the file's current contents
+#[cfg(feature = "bouncer-file")]
+fn reload_rules(
+    config: &BouncerConfig,
+    filters: &RwLock<Arc<Filters>>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(loaded) = &config.rules_file else {
+        return Err("the bouncer config wasn't loaded from a rules file".into());
+    };
+    let file = RulesFile::load(&loaded.path)?;
+    let startup = loaded.blocked_paths();
+    let mut paths: HashSet<String> = config.blocked_paths.difference(&startup).cloned().collect();
+    paths.extend(file.blocked_paths());
+    let regexes: Vec<String> = config
+        .blocked_regexes
+        .iter()
+        .filter(|regex| !loaded.regex.contains(regex))
+        .chain(&file.regex)
+        .cloned()
+        .collect();
+    let allowlist: Vec<String> = config
+        .allowlist
+        .iter()
+        .filter(|network| !loaded.allow.contains(network))
+        .chain(&file.allow)
+        .cloned()
+        .collect();
+
+    let reloaded = Filters::new(&paths, &regexes, &allowlist);
+    *filters.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(reloaded);
+    tracing::info!(
+        path = %loaded.path.display(),
+        paths = paths.len(),
+        regexes = regexes.len(),
+        allowlist = allowlist.len(),
+        "Reloaded bouncer rules"
+    );
+    Ok(())
+}
+
+fn allowed(allowlist: &[IpNet], ip: IpAddr) -> bool {
+    allowlist.iter().any(|network| network.contains(ip))
+}
+
+fn rejections() -> metrics::Counter {
+    metrics::counter(
+        "axtra_bouncer_rejections_total",
+        "Requests rejected by the bouncer.",
+    )
+}
+
+fn dry_run_matches() -> metrics::Counter {
+    metrics::counter(
+        "axtra_bouncer_dry_run_matches_total",
+        "Requests the bouncer would have rejected, let through in dry-run mode.",
+    )
+}
+
 fn log_event(
     level: tracing::Level,
     ip: &IpAddr,
@@ -207,43 +1271,60 @@ fn log_event(
     }
 }
 
-// Extract the real client IP
-// Trust proxy must be set to use proxy headers as they can be spoofed
-fn extract_ip<B>(req: &Request<B>, trust_proxy: bool) -> Option<IpAddr> {
-    // Check proxy headers if configured to trust them, otherwise skip to direct connection IP
-    if trust_proxy {
-        // 1. Cloudflare
-        if let Some(ip) = req
-            .headers()
-            .get("cf-connecting-ip")
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.parse().ok())
-        {
-            return Some(ip);
-        }
-        // 2. X-Forwarded-For (first in list)
-        if let Some(ip) = req
-            .headers()
-            .get("x-forwarded-for")
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.split(',').next())
-            .and_then(|s| s.trim().parse().ok())
-        {
-            return Some(ip);
-        }
-        // 3. X-Real-IP
-        if let Some(ip) = req
-            .headers()
-            .get("x-real-ip")
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.parse().ok())
-        {
-            return Some(ip);
-        }
-    }
-
-    // Fallback to direct connection IP if available
-    req.extensions()
-        .get::<axum::extract::ConnectInfo<SocketAddr>>()
-        .map(|info| info.0.ip())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+    #[tokio::test]
+    async fn allowlisted_ips_are_never_banned() {
+        let layer = BouncerLayer::new(BouncerConfig::from_rules(&[], &[]).allow(&["10.0.0.0/8"]));
+        layer.ban_ip(ip("10.1.2.3"), None, "test").await.unwrap();
+        layer.ban_network("10.0.0.0/16", None).unwrap();
+        assert!(layer.allows(ip("10.200.0.1")));
+        assert!(layer.allows(ip("::ffff:10.200.0.1")));
+        assert!(!layer.allows(ip("11.0.0.1")));
+        assert!(!layer.is_banned(ip("10.0.0.1")).await.unwrap());
+        assert!(!layer.is_banned(ip("10.1.2.3")).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn network_bans_cover_the_range() {
+        let layer = BouncerLayer::new(BouncerConfig::from_rules(&[], &[]));
+        layer.ban_network("198.51.100.0/24", None).unwrap();
+        assert!(layer.is_banned(ip("198.51.100.9")).await.unwrap());
+        assert!(!layer.is_banned(ip("198.51.101.9")).await.unwrap());
+        assert!(layer.ban_network("not a network", None).is_err());
+
+        assert!(layer.unban_network("198.51.100.7/24"));
+        assert!(!layer.is_banned(ip("198.51.100.9")).await.unwrap());
+    }
+
+    #[test]
+    fn ipv6_bans_cover_the_ban_prefix() {
+        let layer = BouncerLayer::new(BouncerConfig::from_rules(&[], &[]));
+        assert_eq!(
+            layer.ban_key(ip("2001:db8:1:2:3:4:5:6")),
+            ip("2001:db8:1:2::")
+        );
+        assert_eq!(layer.ban_key(ip("203.0.113.7")), ip("203.0.113.7"));
+        assert_eq!(layer.ban_key(ip("::ffff:203.0.113.7")), ip("203.0.113.7"));
+
+        let layer = BouncerLayer::new(BouncerConfig::from_rules(&[], &[]).ipv6_ban_prefix(128));
+        assert_eq!(layer.ban_key(ip("2001:db8::6")), ip("2001:db8::6"));
+    }
+
+    #[tokio::test]
+    async fn ipv6_bans_match_addresses_in_the_prefix() {
+        let layer = BouncerLayer::new(BouncerConfig::from_rules(&[], &[]));
+        let ban = layer.ban_ip(ip("2001:db8::1"), None, "test").await.unwrap();
+        assert_eq!(ban.ip, ip("2001:db8::"));
+        assert!(layer.is_banned(ip("2001:db8::ffff")).await.unwrap());
+        assert!(!layer.is_banned(ip("2001:db8:0:1::1")).await.unwrap());
+
+        assert!(layer.unban_ip(ip("2001:db8::2")).await.unwrap());
+        assert!(!layer.is_banned(ip("2001:db8::1")).await.unwrap());
+    }
 }
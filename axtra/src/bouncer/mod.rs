@@ -14,10 +14,30 @@
 //! ## Features
 //!
 //! - Ban IPs for a configurable duration when they access blocked paths.
-//! - Use presets (e.g., "wordpress", "php", "config") or custom paths for filtering.
+//! - Allowlist IPs and CIDR ranges that are never blocked or banned.
+//! - Exempt paths and a bypass token header for probes, smoke tests and pentests.
+//! - Keep bans in memory, Redis or Postgres via the [`BanStore`] trait.
+//! - Ban whole CIDR ranges, manually or after repeated bans from one range.
+//! - Ban IPv6 clients by their /64 (configurable), so rotating addresses doesn't help.
+//! - Import external blocklists like Spamhaus DROP and AbuseIPDB (`bouncer-blocklists` feature).
+//! - Optionally wait for several blocked hits within a window before banning (fail2ban-style).
+//! - Ban IPs the app answers with too many 404s (or other statuses) within a window.
+//! - Use presets (e.g., "wordpress", "php", "config", "git", "cloud") or custom paths for filtering.
+//! - Match whole families of scanner paths with glob patterns (`/wp-*`, `*/.git/*`, `*.php`).
+//! - Load rules from a TOML or JSON file, reloaded on change or `SIGHUP` (`bouncer-file` feature).
+//! - Choose per rule or preset whether to ban, and for how long.
 //! - Customize HTTP status and body for banned and blocked responses.
+//! - Observe without enforcing in dry-run mode, to tune rules in production.
+//! - Optionally tell banned clients when access resumes (`Retry-After`, `X-Ban-Expires`).
 //! - Set log level for event tracing (`trace`, `debug`, `info`, etc).
-//! - Expose the banlist for observability and monitoring.
+//! - Expose the banlist for observability and monitoring, as JSON or TypeScript-typed data.
+//! - Sweep expired bans from memory in the background, so large scans don't grow the banlist.
+//! - Cap the in-memory banlist, evicting by soonest expiry or least recently seen.
+//! - Manage bans over JSON admin endpoints.
+//! - Ban and unban from application code through a cloneable [`BouncerHandle`].
+//! - Serve honeypot routes that ban their callers behind a decoy page.
+//! - Run async hooks on blocked requests and bans.
+//! - Notify Slack or Discord of bans, batched during scans (`notifier` feature).
 //!
 //! ## Usage Example
 //!
@@ -50,6 +70,11 @@
 //! - `"wordpress"`
 //! - `"php"`
 //! - `"config"`
+//! - `"git"`: exposed VCS metadata (`/.git/config`, `/.svn`).
+//! - `"backup"`: database dumps and site archives (`*.sql`, `*.bak`, `/backup.tar.gz`).
+//! - `"cloud"`: credential files (`/.aws/credentials`, `/.ssh/id_rsa`).
+//! - `"java"`: Java admin consoles (`/actuator`, `/console`, `/jmx-console`).
+//! - `"cgi"`: CGI scripts (`/cgi-bin/*`).
 //!
 //! ## Advanced Usage
 //!
@@ -59,14 +84,277 @@
 //! let config = BouncerConfig::from_custom_rules(&["/admin", "/hidden"]);
 //! ```
 //!
+//! ## Glob Patterns
+//!
+//! Rules containing `*` (any run of characters, including `/`) or `?` (one character) are
+//! globs, compiled into a single matcher when the layer is built:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_custom_rules(&["/wp-*", "*/.git/*", "*.php"]);
+//! ```
+//!
+//! ## Prefix and Extension Rules
+//!
+//! [`Rule`] covers common policies without listing every path:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .rule(Rule::prefix("/cgi-bin/"))
+//!     .rule(Rule::extension("php"));
+//! ```
+//!
+//! ## Rule Actions
+//!
+//! Every rule bans by default. An [`Action`] per rule or preset changes that:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress", "config"])
+//!     .preset_action("config", Action::BanFor(Duration::from_secs(7 * 86400)))
+//!     .rule_action(Rule::prefix("/legacy/"), Action::Block);
+//! ```
+//!
+//! ## Rules Files
+//!
+//! With the `bouncer-file` feature, presets, paths, regexes, the allowlist and durations
+//! can live in a [`RulesFile`], so rule changes don't need a redeploy:
+//! ```rust, ignore
+//! let layer = BouncerLayer::new(BouncerConfig::from_file("bouncer.toml")?)
+//!     .watch(Duration::from_secs(5))
+//!     .reload_on_sighup();
+//! ```
+//!
+//! ## Dry Run
+//!
+//! In dry-run mode the bouncer logs, counts, strikes and runs `on_block` hooks as usual,
+//! but bans nobody and lets every request through, so new rules can be tuned against real
+//! traffic first:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress", "php"]).dry_run(true);
+//! ```
+//!
+//! ## Strikes
+//!
+//! Behind shared NATs, one stray request shouldn't ban a whole office. With strikes, an IP
+//! is only banned after several blocked hits within a window (each hit is still blocked):
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .strikes(3, Duration::from_secs(600));
+//! ```
+//!
+//! ## Status Bans
+//!
+//! Scanners probing paths no rule covers mostly get 404s back. Status bans count those
+//! responses per IP and ban once there are too many within a window:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .status_bans(20, Duration::from_secs(60))
+//!     .ban_statuses(&[StatusCode::NOT_FOUND, StatusCode::METHOD_NOT_ALLOWED]);
+//! ```
+//!
+//! ## Allowlist
+//!
+//! Health checkers, office ranges and uptime monitors can be exempted entirely; they're
+//! checked before the banlist, so they're never blocked or banned:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .allow(&["10.0.0.0/8", "203.0.113.7"]);
+//! ```
+//!
+//! ## Exempt Paths and Bypass Token
+//!
+//! Exempt paths are never checked, and requests sending the bypass token in
+//! [`BYPASS_HEADER`] (`X-Bouncer-Bypass`) skip the bouncer from any IP:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .exempt_paths(&["/health", "/metrics"])
+//!     .bypass_token(env::var("BOUNCER_BYPASS_TOKEN")?);
+//! ```
+//!
+//! ## CIDR Bans
+//!
+//! Networks can be banned up front, or automatically once several IPs in the same range
+//! (/24 for IPv4, /48 for IPv6 by default) are banned within a window. Lookups check each
+//! banned prefix length once, most specific first:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .ban_networks(&["198.51.100.0/24"])
+//!     .range_bans(5, Duration::from_secs(3600))
+//!     .range_prefixes(24, 48);
+//! let layer = BouncerLayer::new(config);
+//!
+//! // At runtime
+//! layer.ban_network("203.0.113.0/24", Some(Duration::from_secs(86400)))?;
+//! layer.unban_network("198.51.100.0/24");
+//! ```
+//!
+//! ## External Blocklists
+//!
+//! With the `bouncer-blocklists` feature, [`Blocklist`] feeds are fetched at startup and
+//! every 6 hours, their entries banned as networks until they leave the feed:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .blocklist(Blocklist::spamhaus_drop())
+//!     .blocklist(Blocklist::abuseipdb(api_key, 90))
+//!     .blocklist_refresh(Duration::from_secs(12 * 3600))
+//!     .max_blocklist_entries(10_000);
+//! ```
+//!
+//! ## IPv6 Bans
+//!
+//! IPv6 clients are banned by their /64, since they can rotate through every address in
+//! it; strikes are counted per /64 too. IPv4 bans stay per address:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .ipv6_ban_prefix(56); // or 128 to ban single addresses
+//! ```
+//!
+//! ## Persistent Bans
+//!
+//! Bans are in memory by default. Use a [`store`] backend so they survive restarts and
+//! are shared across replicas (`bouncer-redis` or `bouncer-postgres` features):
+//! ```rust, ignore
+//! let layer = BouncerLayer::new(config).store(RedisBanStore::new(manager));
+//! let bans = layer.bans().await?;
+//! ```
+//!
+//! ## Banning From Application Code
+//!
+//! A [`BouncerHandle`] feeds bans from elsewhere in the app into the bouncer:
+//! ```rust, ignore
+//! let handle = layer.handle();
+//! handle.ban_ip(ip, Some(Duration::from_secs(86400)), "Credential stuffing").await?;
+//! assert!(handle.is_banned(ip).await?);
+//! ```
+//!
+//! ## Honeypots
+//!
+//! [`Honeypot`] routes answer with a decoy page after a delay, log the full request and
+//! ban the caller, catching scanners the presets miss:
+//! ```rust, ignore
+//! let app = Router::new()
+//!     .merge(honeypot_router(&bouncer, &["/admin.bak", "/old-admin"]))
+//!     .layer(bouncer);
+//! ```
+//!
+//! ## Admin Endpoints
+//!
+//! [`admin_router`] lists bans, lifts them and adds manual bans, behind a bearer token:
+//! ```rust, ignore
+//! let app = Router::new()
+//!     .nest("/admin", admin_router(&bouncer, token))
+//!     .layer(bouncer);
+//! ```
+//!
+//! ## Banlist Export
+//!
+//! [`BouncerLayer::entries`] returns current bans as serializable [`BanEntry`] values with
+//! their hit counts, and [`banlist_router`] serves them behind your own auth:
+//! ```rust, ignore
+//! let entries = bouncer.entries().await?; // ip, expires_at, reason, hits
+//! let app = Router::new()
+//!     .nest("/internal", banlist_router(&bouncer).route_layer(require_staff))
+//!     .layer(bouncer);
+//! ```
+//!
+//! ## Ban Notifications
+//!
+//! With the `notifier` feature, bans can be posted to Slack or Discord. The first goes out
+//! immediately and later ones within the batch window are summarized in one message:
+//! ```rust, ignore
+//! let layer = BouncerLayer::new(config)
+//!     .notify(Notifier::with_discord(webhook_url))
+//!     .notify_batch(Duration::from_secs(300));
+//! ```
+//!
+//! ## Event Hooks
+//!
+//! Async callbacks receive a [`BouncerEvent`] for every blocked request and every ban:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .on_block(|event| async move { tracing::info!(ip = %event.ip, "blocked") })
+//!     .on_ban(|event| async move { siem::send(event).await });
+//! ```
+//!
+//! ## Custom Responses
+//!
+//! Banned and blocked responses can carry a branded page or a honeypot response:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .banned_body(RejectionBody::html_file("dist/403.html"))
+//!     .blocked_body(RejectionBody::custom(|event| fake_login_page(&event.path)));
+//! ```
+//!
+//! ## Ban Headers
+//!
+//! Banned responses are opaque by default. Enable ban headers so clients banned by mistake
+//! know when to retry, via `Retry-After` (seconds) and `X-Ban-Expires` (RFC 3339):
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"]).ban_headers(true);
+//! ```
+//!
+//! ## Expiry Sweeper
+//!
+//! `BouncerLayer::new` starts a background task (on the current Tokio runtime) that drops
+//! expired bans and strike counts every minute. Change or disable it, or purge by hand:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .sweep_interval(Some(Duration::from_secs(300)));
+//! let purged = layer.purge_expired();
+//! ```
+//!
+//! ## Banlist Size
+//!
+//! Cap the in-memory banlist so a distributed scan can't exhaust memory. The size and
+//! evictions are exported as `axtra_bouncer_banlist_size` and `axtra_bouncer_evictions_total`:
+//! ```rust, ignore
+//! let config = BouncerConfig::from_preset_rules(&["wordpress"])
+//!     .max_bans(100_000, Eviction::Lru);
+//! ```
+//!
 //! ## Re-exports
 //!
 //! - [`BouncerConfig`]: Configuration for the bouncer middleware.
 //! - [`BouncerLayer`]: Axum layer for IP banning and path filtering.
+//! - [`Rule`]: Exact, prefix, extension, glob and regex path rules.
+//! - [`Action`]: Ban, ban for a set duration, or only block, per rule or preset.
+//! - [`RulesFile`]: Bouncer rules loaded from TOML or JSON (`bouncer-file` feature).
+//! - [`Blocklist`]: External IP blocklist feeds (`bouncer-blocklists` feature).
+//! - [`BanStore`]: Storage backend for IP bans.
+//! - [`MemoryBanStore`]: The default in-memory ban store.
+//! - [`Eviction`]: Which bans a full in-memory banlist evicts.
+//! - [`BouncerEvent`] and [`Hook`]: Events passed to `on_block` and `on_ban` callbacks.
+//! - [`RejectionBody`]: Bodies for banned and blocked responses.
+//! - [`BouncerHandle`]: Cloneable handle to ban and unban from application code.
+//! - [`Honeypot`] and [`honeypot_router`]: Decoy routes that ban their callers.
+//! - [`BanEntry`] and [`banlist_router`]: Serializable bans and a route listing them.
+//! - [`admin_router`]: Authenticated endpoints to list, add and lift bans.
 //!
 //! See the README and docs.rs for more details.
 
+mod admin;
+#[cfg(feature = "bouncer-blocklists")]
+mod blocklist;
+mod events;
+mod export;
+#[cfg(feature = "bouncer-file")]
+mod file;
+mod handle;
+mod honeypot;
 mod layer;
-mod rules;
+mod networks;
+#[cfg(feature = "notifier")]
+mod notify;
+mod response;
+pub mod rules;
+pub mod store;
 
-pub use layer::{BouncerConfig, BouncerLayer};
+pub use admin::{ManualBan, admin_router};
+#[cfg(feature = "bouncer-blocklists")]
+pub use blocklist::Blocklist;
+pub use events::{BouncerEvent, Hook};
+pub use export::{BanEntry, banlist_router};
+#[cfg(feature = "bouncer-file")]
+pub use file::RulesFile;
+pub use handle::BouncerHandle;
+pub use honeypot::{Honeypot, honeypot_router};
+pub use layer::{BYPASS_HEADER, BouncerConfig, BouncerLayer};
+pub use response::RejectionBody;
+pub use rules::{Action, Rule};
+pub use store::{Ban, BanStore, Eviction, MAX_BAN_DURATION, MemoryBanStore};
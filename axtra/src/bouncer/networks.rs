@@ -0,0 +1,184 @@
+//! Bans covering whole networks, for botnets rotating within a subnet.
+
+use std::{
+    cmp::Reverse,
+    collections::BTreeSet,
+    net::IpAddr,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+
+use crate::net::IpNet;
+
+// Expired offense windows are swept once the offense list grows past this
+const OFFENSE_SWEEP_THRESHOLD: usize = 1024;
+
+/// Banned networks, with their expiry (`None` for permanent bans).
+#[derive(Debug, Default)]
+pub(crate) struct NetworkBans {
+    bans: DashMap<IpNet, Option<Instant>>,
+    // Prefix lengths in use, longest first, so a lookup is one probe per length
+    prefixes: RwLock<BTreeSet<(bool, Reverse<u8>)>>,
+    // IP bans per range that hasn't been banned yet, with when its window started
+    offenses: DashMap<IpNet, (u32, Instant)>,
+}
+
+impl NetworkBans {
+    pub(crate) fn new(permanent: impl IntoIterator<Item = IpNet>) -> Self {
+        let bans = Self::default();
+        for network in permanent {
+            bans.insert(network, None);
+        }
+        bans
+    }
+
+    pub(crate) fn insert(&self, network: IpNet, expiry: Option<Instant>) {
+        self.prefixes
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((network.is_ipv4(), Reverse(network.prefix())));
+        self.bans.insert(network, expiry);
+    }
+
+    #[cfg(feature = "bouncer-blocklists")]
+    pub(crate) fn contains(&self, network: &IpNet) -> bool {
+        self.bans.contains_key(network)
+    }
+
+    pub(crate) fn remove(&self, network: &IpNet) -> bool {
+        self.bans.remove(network).is_some()
+    }
+
+    /// The most specific banned network containing `ip` and its expiry, dropping expired
+    /// bans on the way.
+    pub(crate) fn find(&self, ip: IpAddr) -> Option<(IpNet, Option<Instant>)> {
+        let ipv4 = ip.to_canonical().is_ipv4();
+        let prefixes = self.prefixes.read().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        prefixes
+            .iter()
+            .filter(|(family, _)| *family == ipv4)
+            .filter_map(|(_, prefix)| IpNet::new(ip, prefix.0))
+            .find_map(
+                |network| match self.bans.get(&network).map(|expiry| *expiry) {
+                    Some(Some(expiry)) if expiry <= now => {
+                        self.bans.remove(&network);
+                        None
+                    }
+                    Some(expiry) => Some((network, expiry)),
+                    None => None,
+                },
+            )
+    }
+
+    /// Every current ban, with its expiry.
+    pub(crate) fn list(&self) -> Vec<(IpNet, Option<Instant>)> {
+        let now = Instant::now();
+        self.bans
+            .iter()
+            .filter(|entry| entry.value().is_none_or(|expiry| expiry > now))
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect()
+    }
+
+    /// Drop expired bans, and offense counts whose `window` has passed. Returns how many
+    /// bans were dropped.
+    pub(crate) fn purge_expired(&self, window: Duration) -> usize {
+        let now = Instant::now();
+        let before = self.bans.len();
+        self.bans
+            .retain(|_, expiry| expiry.is_none_or(|expiry| expiry > now));
+        self.offenses
+            .retain(|_, (_, started)| now.duration_since(*started) < window);
+        before.saturating_sub(self.bans.len())
+    }
+
+    /// Count a ban of `ip` against its `prefix` range, banning the range for `duration`
+    /// once `threshold` IPs in it were banned within `window`. Returns the banned range.
+    pub(crate) fn record_offense(
+        &self,
+        ip: IpAddr,
+        prefix: u8,
+        threshold: u32,
+        window: Duration,
+        duration: Duration,
+    ) -> Option<IpNet> {
+        let range = IpNet::new(ip, prefix)?;
+        let now = Instant::now();
+        if self.offenses.len() >= OFFENSE_SWEEP_THRESHOLD {
+            self.offenses
+                .retain(|_, (_, started)| now.duration_since(*started) < window);
+        }
+        let offenses = {
+            let mut entry = self.offenses.entry(range).or_insert((0, now));
+            let (offenses, started) = entry.value_mut();
+            if now.duration_since(*started) >= window {
+                *offenses = 0;
+                *started = now;
+            }
+            *offenses += 1;
+            *offenses
+        };
+        if offenses < threshold {
+            return None;
+        }
+        self.offenses.remove(&range);
+        self.insert(range, Some(now + duration));
+        Some(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn net(value: &str) -> IpNet {
+        IpNet::parse(value).unwrap()
+    }
+    #[test]
+    fn finds_the_most_specific_ban() {
+        let bans = NetworkBans::new([net("10.0.0.0/8")]);
+        let expiry = Instant::now() + Duration::from_secs(60);
+        bans.insert(net("10.1.0.0/16"), Some(expiry));
+
+        assert_eq!(
+            bans.find(ip("10.1.2.3")),
+            Some((net("10.1.0.0/16"), Some(expiry)))
+        );
+        assert_eq!(bans.find(ip("10.2.0.1")), Some((net("10.0.0.0/8"), None)));
+        assert_eq!(
+            bans.find(ip("::ffff:10.2.0.1")).map(|(n, _)| n),
+            Some(net("10.0.0.0/8"))
+        );
+        assert_eq!(bans.find(ip("11.0.0.1")), None);
+        assert_eq!(bans.find(ip("::a00:1")), None);
+    }
+
+    #[test]
+    fn drops_expired_bans_when_found() {
+        let bans = NetworkBans::default();
+        bans.insert(net("2001:db8::/32"), Some(Instant::now()));
+        assert_eq!(bans.find(ip("2001:db8::1")), None);
+        assert!(!bans.remove(&net("2001:db8::/32")));
+    }
+
+    #[test]
+    fn bans_a_range_once_enough_of_its_ips_are_banned() {
+        let bans = NetworkBans::default();
+        let (window, duration) = (Duration::from_secs(60), Duration::from_secs(600));
+        let offend = |address| bans.record_offense(ip(address), 24, 3, window, duration);
+
+        assert_eq!(offend("198.51.100.1"), None);
+        assert_eq!(offend("198.51.100.2"), None);
+        assert_eq!(offend("198.51.101.3"), None);
+        assert_eq!(offend("198.51.100.3"), Some(net("198.51.100.0/24")));
+        assert!(bans.find(ip("198.51.100.200")).is_some());
+        assert!(bans.find(ip("198.51.101.3")).is_none());
+    }
+}
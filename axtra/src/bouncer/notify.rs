@@ -0,0 +1,203 @@
+//! Notifications when the bouncer bans an IP.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    bouncer::events::BouncerEvent,
+    notifier::{Notifier, app_name, skipped},
+};
+
+// Bans listed in a batched message before the rest are only counted
+const MAX_LISTED: usize = 20;
+
+// When a message last went out, and bans waiting for the next one: the first
+// `MAX_LISTED` in full, the rest only counted
+#[derive(Debug, Default)]
+struct State {
+    last_sent: Option<Instant>,
+    pending: Vec<BouncerEvent>,
+    overflow: usize,
+}
+
+/// Sends a message through a [`Notifier`] when an IP is banned. The first ban goes out
+/// straight away; bans within the batch window after it are collected into one summary,
+/// so a scan banning hundreds of IPs doesn't flood the channel.
+#[derive(Clone)]
+pub(crate) struct BanNotifier {
+    notifier: Arc<Notifier>,
+    window: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl std::fmt::Debug for BanNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BanNotifier")
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BanNotifier {
+    pub(crate) fn new(notifier: Notifier, window: Duration) -> Self {
+        Self {
+            notifier: Arc::new(notifier),
+            window,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    pub(crate) fn window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    pub(crate) fn observe(&self, event: BouncerEvent) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let quiet = state
+            .last_sent
+            .is_none_or(|sent| now.duration_since(sent) >= self.window);
+        if quiet && state.pending.is_empty() {
+            state.last_sent = Some(now);
+            drop(state);
+            self.send(single_message(&event));
+            return;
+        }
+
+        if state.pending.len() >= MAX_LISTED {
+            // A flush is already scheduled, and the message only counts the rest
+            state.overflow += 1;
+            return;
+        }
+        state.pending.push(event);
+        if state.pending.len() > 1 {
+            // A flush is already scheduled
+            return;
+        }
+        let flush_at = state.last_sent.map_or(now, |sent| sent + self.window);
+        drop(state);
+
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep_until(flush_at.into()).await;
+            let (events, overflow) = {
+                let mut state = notifier.state.lock().unwrap_or_else(|e| e.into_inner());
+                state.last_sent = Some(Instant::now());
+                (
+                    std::mem::take(&mut state.pending),
+                    std::mem::take(&mut state.overflow),
+                )
+            };
+            notifier.send(batch_message(&events, overflow));
+        });
+    }
+
+    fn send(&self, message: String) {
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            match notifier.notify(&message).await {
+                Err(e) if skipped(&*e) => {
+                    tracing::debug!("Bouncer ban notification not sent: {e}")
+                }
+                Err(e) => tracing::warn!("Failed to send bouncer ban notification: {e}"),
+                Ok(()) => {}
+            }
+        });
+    }
+}
+
+fn single_message(event: &BouncerEvent) -> String {
+    format!("[{}] Banned {}", app_name(), describe(event))
+}
+
+// `events` are listed, and `overflow` more bans only counted
+fn batch_message(events: &[BouncerEvent], overflow: usize) -> String {
+    let total = events.len() + overflow;
+    let mut message = format!(
+        "[{}] Banned {} more IP{}",
+        app_name(),
+        total,
+        if total == 1 { "" } else { "s" }
+    );
+    for event in events {
+        message.push_str(&format!("\n- {}", describe(event)));
+    }
+    if overflow > 0 {
+        message.push_str(&format!("\n…and {overflow} more"));
+    }
+    message
+}
+
+// `203.0.113.7 for 1h: Blocked path /wp-login.php (preset wordpress)`
+fn describe(event: &BouncerEvent) -> String {
+    match event.ban_duration {
+        Some(duration) => format!(
+            "{} for {}: {}",
+            event.ip,
+            format_duration(duration),
+            event.reason
+        ),
+        None => format!("{}: {}", event.ip, event.reason),
+    }
+}
+
+// `1h 30m`, `45s`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let parts: Vec<String> = [
+        (secs / 86400, "d"),
+        (secs / 3600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ]
+    .into_iter()
+    .filter(|(value, _)| *value > 0)
+    .map(|(value, unit)| format!("{value}{unit}"))
+    .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::*;
+
+    fn event(n: u8) -> BouncerEvent {
+        BouncerEvent {
+            ip: IpAddr::V4(Ipv4Addr::new(203, 0, 113, n)),
+            path: "/.env".to_string(),
+            rule: None,
+            preset: None,
+            reason: "Blocked path /.env".to_string(),
+            ban_duration: Some(Duration::from_secs(3600)),
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_keeps_at_most_max_listed_events() {
+        let notifier = BanNotifier::new(Notifier::new(), Duration::from_secs(60));
+        for n in 0..200 {
+            notifier.observe(event(n));
+        }
+        let state = notifier.state.lock().unwrap();
+        assert_eq!(state.pending.len(), MAX_LISTED);
+        // The first ban is sent straight away
+        assert_eq!(state.overflow, 199 - MAX_LISTED);
+    }
+
+    #[test]
+    fn batch_message_counts_overflow() {
+        let events: Vec<_> = (0..3).map(event).collect();
+        let message = batch_message(&events, 5);
+        assert!(message.contains("Banned 8 more IPs"));
+        assert_eq!(message.matches("\n- ").count(), 3);
+        assert!(message.ends_with("…and 5 more"));
+    }
+}
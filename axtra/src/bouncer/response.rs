@@ -0,0 +1,110 @@
+//! Response bodies for banned and blocked requests.
+
+use std::{fmt, path::Path, sync::Arc};
+
+use axum::{
+    body::{Body, Bytes},
+    http::{HeaderValue, StatusCode, header},
+    response::Response,
+};
+
+use crate::bouncer::events::BouncerEvent;
+
+type RenderFn = dyn Fn(&BouncerEvent) -> Response + Send + Sync;
+
+/// The body sent with banned or blocked responses.
+///
+/// ```rust, ignore
+/// let config = BouncerConfig::from_preset_rules(&["wordpress"])
+///     .banned_body(RejectionBody::html_file("dist/403.html"))
+///     // Keep scanners guessing with a plausible login page
+///     .blocked_body(RejectionBody::custom(|_event| {
+///         (StatusCode::OK, Html("<form action=\"/wp-login.php\">")).into_response()
+///     }));
+/// ```
+#[derive(Clone, Default)]
+pub enum RejectionBody {
+    /// No body, just the configured status. The default.
+    #[default]
+    Empty,
+    /// A fixed body and content type, sent with the configured status.
+    Static {
+        body: Bytes,
+        content_type: HeaderValue,
+    },
+    /// A closure building the whole response, status included.
+    Custom(Arc<RenderFn>),
+}
+
+impl RejectionBody {
+    /// A fixed body with `content_type`.
+    ///
+    /// Panics if `content_type` isn't a valid header value.
+    pub fn new(body: impl Into<Bytes>, content_type: &str) -> Self {
+        let content_type = HeaderValue::from_str(content_type)
+            .unwrap_or_else(|_| panic!("invalid content type for RejectionBody: {content_type:?}"));
+        Self::Static {
+            body: body.into(),
+            content_type,
+        }
+    }
+
+    pub fn text(body: impl Into<Bytes>) -> Self {
+        Self::new(body, "text/plain; charset=utf-8")
+    }
+
+    pub fn html(body: impl Into<Bytes>) -> Self {
+        Self::new(body, "text/html; charset=utf-8")
+    }
+
+    /// An HTML page read from disk once, when the config is built.
+    ///
+    /// Panics if the file can't be read.
+    pub fn html_file(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let body = std::fs::read(path).unwrap_or_else(|e| {
+            panic!(
+                "RejectionBody::html_file couldn't read {}: {e}",
+                path.display()
+            )
+        });
+        Self::html(body)
+    }
+
+    /// Build the response from the rejected request's event, e.g. for honeypot responses.
+    pub fn custom<F>(render: F) -> Self
+    where
+        F: Fn(&BouncerEvent) -> Response + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(render))
+    }
+
+    pub(crate) fn render(&self, status: StatusCode, event: &BouncerEvent) -> Response {
+        let mut res = match self {
+            Self::Empty => Response::new(Body::empty()),
+            Self::Static { body, content_type } => {
+                let mut res = Response::new(Body::from(body.clone()));
+                res.headers_mut()
+                    .insert(header::CONTENT_TYPE, content_type.clone());
+                res
+            }
+            Self::Custom(render) => return render(event),
+        };
+        *res.status_mut() = status;
+        res
+    }
+}
+
+impl fmt::Debug for RejectionBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Empty"),
+            Self::Static { body, content_type } => f
+                .debug_struct("Static")
+                .field("len", &body.len())
+                .field("content_type", content_type)
+                .finish(),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
@@ -1,4 +1,99 @@
-use std::collections::HashSet;
+//! Path rules for the bouncer: presets, custom paths and pattern helpers.
+
+use std::{collections::HashSet, time::Duration};
+
+use regex::RegexSet;
+
+/// A blocked path rule, for policies that would otherwise need long path lists.
+///
+/// ```rust, ignore
+/// let config = BouncerConfig::from_preset_rules(&["wordpress"])
+///     .rule(Rule::prefix("/cgi-bin/"))
+///     .rule(Rule::extension("php"))
+///     .rule(Rule::extension("asp"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// Exactly this path.
+    Path(String),
+    /// Every path starting with this prefix.
+    Prefix(String),
+    /// Every path ending in `.{extension}`.
+    Extension(String),
+    /// A glob pattern: `*` matches any run of characters (including `/`), `?` one character.
+    Glob(String),
+    /// A regular expression, matched anywhere in the path unless anchored with `^` and `$`.
+    Regex(String),
+}
+
+impl Rule {
+    pub fn path(path: impl Into<String>) -> Self {
+        Self::Path(path.into())
+    }
+
+    pub fn prefix(prefix: impl Into<String>) -> Self {
+        Self::Prefix(prefix.into())
+    }
+
+    /// Paths ending in `.{extension}`; a leading dot is optional (`"php"` or `".php"`).
+    pub fn extension(extension: impl AsRef<str>) -> Self {
+        Self::Extension(extension.as_ref().trim_start_matches('.').to_string())
+    }
+
+    pub fn glob(pattern: impl Into<String>) -> Self {
+        Self::Glob(pattern.into())
+    }
+
+    /// Panics if `pattern` isn't a valid regex.
+    pub fn regex(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        if let Err(e) = regex::Regex::new(&pattern) {
+            panic!("invalid regex rule {pattern:?}: {e}");
+        }
+        Self::Regex(pattern)
+    }
+
+    /// The rule as a blocked path entry, using glob patterns for prefixes and extensions.
+    /// Regexes can't be expressed as paths and are returned as written.
+    pub fn pattern(&self) -> String {
+        match self {
+            Self::Path(path) | Self::Glob(path) | Self::Regex(path) => path.clone(),
+            Self::Prefix(prefix) => format!("{prefix}*"),
+            Self::Extension(extension) => format!("*.{extension}"),
+        }
+    }
+}
+
+/// What the bouncer does when a rule matches, set per rule or preset.
+///
+/// ```rust, ignore
+/// let config = BouncerConfig::from_preset_rules(&["wordpress", "config"])
+///     .preset_action("config", Action::BanFor(Duration::from_secs(7 * 86400)))
+///     .rule_action(Rule::prefix("/legacy/"), Action::Block);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Action {
+    /// Block the request and ban the IP for the configured ban duration, after the
+    /// configured strikes.
+    #[default]
+    Ban,
+    /// Like [`Ban`](Self::Ban), for this long instead of the configured duration.
+    BanFor(Duration),
+    /// Block the request without counting a strike or banning.
+    Block,
+}
+
+/// Names of the built-in presets.
+pub const PRESETS: &[&str] = &[
+    "wordpress",
+    "php",
+    "config",
+    "git",
+    "backup",
+    "cloud",
+    "java",
+    "cgi",
+];
 
 /// Returns the rule paths for a single preset name.
 pub fn preset_rules(name: &str) -> &'static [&'static str] {
@@ -28,10 +123,62 @@ pub fn preset_rules(name: &str) -> &'static [&'static str] {
             "/config.yaml",
             "/config.inc.php",
         ],
+        "git" => &[
+            "/.git",
+            "/.git/*",
+            "/.gitconfig",
+            "/.svn",
+            "/.svn/*",
+            "/.hg/*",
+            "/.bzr/*",
+        ],
+        // Database dumps and site archives left in the web root
+        "backup" => &[
+            "*.sql",
+            "*.sql.gz",
+            "*.bak",
+            "/backup.zip",
+            "/backup.tar.gz",
+            "/backup.tgz",
+            "/site.zip",
+            "/site.tar.gz",
+            "/www.zip",
+            "/www.tar.gz",
+        ],
+        "cloud" => &[
+            "/.aws/credentials",
+            "/.aws/config",
+            "/.ssh/id_rsa",
+            "/.ssh/id_ed25519",
+            "/.ssh/authorized_keys",
+            "/.docker/config.json",
+            "/.kube/config",
+            "/.boto",
+            "/.s3cfg",
+        ],
+        "java" => &[
+            "/actuator",
+            "/actuator/*",
+            "/console",
+            "/jmx-console",
+            "/jmx-console/*",
+            "/web-console",
+            "/manager/html",
+            "/invoker/JMXInvokerServlet",
+        ],
+        "cgi" => &["/cgi-bin/*", "/cgi-sys/*", "/scripts/*.cgi"],
         _ => &[],
     }
 }
 
+/// The first preset containing `rule`, if it came from one.
+pub fn preset_for(rule: &str) -> Option<&'static str> {
+    PRESETS
+        .iter()
+        .copied()
+        .find(|preset| preset_rules(preset).contains(&rule))
+}
+
 /// Generate a ruleset from a list of preset names.
 pub fn from_preset_rules(presets: &[&str]) -> HashSet<String> {
     let mut set = HashSet::new();
@@ -54,3 +201,106 @@ pub fn from_rules(presets: &[&str], custom: &[&str]) -> HashSet<String> {
     set.extend(from_custom_rules(custom));
     set
 }
+
+/// Blocked path rules compiled for matching: exact paths in a set, and glob patterns
+/// (`*` for any run of characters, including `/`, and `?` for one character) and regex
+/// rules in one regex set, so a request is checked against every pattern in a single pass.
+#[derive(Debug, Clone)]
+pub(crate) struct PathRules {
+    exact: HashSet<String>,
+    set: RegexSet,
+    // Glob patterns, then regexes, in the regex set's order, to report which one matched
+    patterns: Vec<String>,
+}
+
+impl PathRules {
+    /// Panics if a regex rule isn't valid; [`Rule::regex`] and rules files check them first.
+    pub(crate) fn new(rules: &HashSet<String>, regexes: &[String]) -> Self {
+        let (globs, exact): (Vec<&String>, Vec<&String>) =
+            rules.iter().partition(|rule| rule.contains(['*', '?']));
+        let glob_count = globs.len();
+        let patterns: Vec<String> = globs.into_iter().chain(regexes).cloned().collect();
+        let set =
+            RegexSet::new(
+                patterns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pattern)| match i < glob_count {
+                        true => glob_regex(pattern),
+                        false => pattern.clone(),
+                    }),
+            )
+            .expect("escaped globs and checked regex rules are valid regexes");
+        Self {
+            exact: exact.into_iter().cloned().collect(),
+            set,
+            patterns,
+        }
+    }
+
+    /// The rule matching `path`, if any: the path itself or the first matching glob or regex.
+    pub(crate) fn matched(&self, path: &str) -> Option<&str> {
+        if let Some(rule) = self.exact.get(path) {
+            return Some(rule);
+        }
+        let index = self.set.matches(path).into_iter().next()?;
+        Some(&self.patterns[index])
+    }
+}
+
+// An anchored regex matching the same paths as `glob`
+fn glob_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(globs: &[&str]) -> PathRules {
+        PathRules::new(&globs.iter().map(|glob| glob.to_string()).collect(), &[])
+    }
+    #[test]
+    fn glob_star_spans_slashes_and_question_mark_one_character() {
+        let rules = rules(&["/wp-*", "/backup.?ip"]);
+        assert_eq!(rules.matched("/wp-admin/setup.php"), Some("/wp-*"));
+        assert_eq!(rules.matched("/backup.zip"), Some("/backup.?ip"));
+        assert_eq!(rules.matched("/backup.gzip"), None);
+        assert_eq!(rules.matched("/blog/wp-login.php"), None);
+    }
+
+    #[test]
+    fn glob_escapes_regex_characters() {
+        let rules = rules(&["/.env*", "/a+b(1)"]);
+        assert_eq!(rules.matched("/.env.local"), Some("/.env*"));
+        assert_eq!(rules.matched("/xenv"), None);
+        assert_eq!(rules.matched("/a+b(1)"), Some("/a+b(1)"));
+        assert_eq!(rules.matched("/aab1"), None);
+    }
+
+    #[test]
+    fn matches_exact_paths_and_rule_patterns() {
+        let paths = [
+            "/.git/config".to_string(),
+            Rule::prefix("/cgi-bin/").pattern(),
+            Rule::extension(".php").pattern(),
+        ];
+        let rules = PathRules::new(&paths.into_iter().collect(), &[r"^/admin/\d+$".to_string()]);
+        assert_eq!(rules.matched("/.git/config"), Some("/.git/config"));
+        assert_eq!(rules.matched("/.git/config/x"), None);
+        assert_eq!(rules.matched("/cgi-bin/test.cgi"), Some("/cgi-bin/*"));
+        assert_eq!(rules.matched("/index.php"), Some("*.php"));
+        assert_eq!(rules.matched("/index.phps"), None);
+        assert_eq!(rules.matched("/admin/42"), Some(r"^/admin/\d+$"));
+        assert_eq!(rules.matched("/admin/users"), None);
+    }
+}
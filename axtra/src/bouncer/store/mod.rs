@@ -0,0 +1,334 @@
+//! Where the bouncer keeps IP bans.
+//!
+//! - [`MemoryBanStore`]: per-process bans in a `DashMap` (the default).
+//! - [`RedisBanStore`]: bans shared through Redis, with native key expiry and pub/sub sync
+//!   between replicas (`bouncer-redis` feature).
+//! - [`PostgresBanStore`]: bans in a Postgres table (`bouncer-postgres` feature).
+//!
+//! Persistent stores keep bans across restarts and share them between replicas behind a
+//! load balancer. Implement [`BanStore`] for anything else.
+
+use std::{
+    error::Error,
+    fmt,
+    future::{Future, ready},
+    net::IpAddr,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use dashmap::DashMap;
+
+use crate::{bouncer::layer::BanList, metrics};
+
+#[cfg(feature = "bouncer-postgres")]
+mod postgres;
+#[cfg(feature = "bouncer-redis")]
+mod redis;
+
+#[cfg(feature = "bouncer-postgres")]
+pub use postgres::PostgresBanStore;
+#[cfg(feature = "bouncer-redis")]
+pub use redis::RedisBanStore;
+
+/// Error returned by a [`BanStore`].
+pub type StoreError = Box<dyn Error + Send + Sync>;
+
+/// Boxed future returned by [`BanStore`] methods.
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, StoreError>> + Send + 'a>>;
+
+/// Longest ban a store keeps. Longer durations are clamped to it, so an expiry far in the
+/// future can't overflow.
+pub const MAX_BAN_DURATION: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
+/// A current IP ban.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ban {
+    pub ip: IpAddr,
+    pub expires_at: SystemTime,
+    /// Why the IP was banned, e.g. the blocked path it requested.
+    pub reason: String,
+}
+
+/// Storage for IP bans.
+///
+/// The bouncer checks [`banned_until`](BanStore::banned_until) on every request from a client
+/// that isn't allowlisted, so lookups should be cheap. When a store fails, the bouncer
+/// logs the error and lets the request through rather than taking the site down.
+pub trait BanStore: Send + Sync {
+    /// Ban `ip` for `duration`, replacing any existing ban. The bouncer never passes more
+    /// than [`MAX_BAN_DURATION`].
+    fn ban(&self, ip: IpAddr, duration: Duration, reason: &str) -> StoreFuture<'_, ()>;
+
+    /// When the ban on `ip` expires, or `None` if it isn't banned.
+    fn banned_until(&self, ip: IpAddr) -> StoreFuture<'_, Option<SystemTime>>;
+
+    /// Lift a ban, returning whether `ip` was banned.
+    fn unban(&self, ip: IpAddr) -> StoreFuture<'_, bool>;
+
+    /// Every current ban.
+    fn list(&self) -> StoreFuture<'_, Vec<Ban>>;
+}
+
+impl fmt::Debug for dyn BanStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BanStore")
+    }
+}
+
+// Share of a full banlist evicted at once, so a scan doesn't sort the list on every ban
+const EVICTION_BATCH_DIVISOR: usize = 64;
+
+/// Which bans a full [`MemoryBanStore`] evicts to make room.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Eviction {
+    /// Bans closest to expiring. The default.
+    #[default]
+    SoonestExpiry,
+    /// Bans whose IP was least recently seen, whether banned or turned away.
+    Lru,
+}
+
+/// Per-process bans, lost on restart. The default store.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBanStore {
+    bans: BanList,
+    reasons: Arc<DashMap<IpAddr, String>>,
+    // When each banned IP was last seen, for LRU eviction
+    last_seen: Arc<DashMap<IpAddr, Instant>>,
+    max_entries: usize,
+    eviction: Eviction,
+}
+
+impl MemoryBanStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold at most `max` bans, evicting by `eviction` once full. `0` means unlimited, the
+    /// default. Expired bans are dropped first; then a batch of 1/64th of the list is
+    /// evicted at a time.
+    pub fn max_entries(mut self, max: usize, eviction: Eviction) -> Self {
+        self.max_entries = max;
+        self.eviction = eviction;
+        self
+    }
+
+    /// The underlying map of IPs to ban expiry.
+    pub fn banlist(&self) -> BanList {
+        self.bans.clone()
+    }
+
+    /// Drop expired bans, returning how many were dropped.
+    pub fn purge_expired(&self) -> usize {
+        let now = Instant::now();
+        let before = self.bans.len();
+        self.bans.retain(|_, expiry| *expiry > now);
+        self.reasons.retain(|ip, _| self.bans.contains_key(ip));
+        self.last_seen.retain(|ip, _| self.bans.contains_key(ip));
+        self.record_size();
+        before.saturating_sub(self.bans.len())
+    }
+
+    // Make room in a full banlist: drop expired bans, or failing that a batch by policy
+    fn evict(&self) {
+        if self.purge_expired() > 0 && self.bans.len() < self.max_entries {
+            return;
+        }
+        // IPs never seen since they were banned sort first, as `None`
+        let mut candidates: Vec<(Option<Instant>, IpAddr)> = self
+            .bans
+            .iter()
+            .map(|entry| match self.eviction {
+                Eviction::SoonestExpiry => (Some(*entry.value()), *entry.key()),
+                Eviction::Lru => (
+                    self.last_seen.get(entry.key()).map(|seen| *seen),
+                    *entry.key(),
+                ),
+            })
+            .collect();
+        let count = (self.max_entries / EVICTION_BATCH_DIVISOR)
+            .max(1)
+            .min(candidates.len());
+        if count < candidates.len() {
+            candidates.select_nth_unstable(count);
+        }
+        for (_, ip) in &candidates[..count] {
+            self.remove(ip);
+        }
+        metrics::counter(
+            "axtra_bouncer_evictions_total",
+            "IP bans evicted from a full banlist.",
+        )
+        .inc_by(count as f64);
+        tracing::debug!(evicted = count, policy = ?self.eviction, "Banlist full, evicted bans");
+    }
+
+    fn remove(&self, ip: &IpAddr) -> bool {
+        self.reasons.remove(ip);
+        self.last_seen.remove(ip);
+        self.bans.remove(ip).is_some()
+    }
+
+    fn record_size(&self) {
+        metrics::gauge(
+            "axtra_bouncer_banlist_size",
+            "IP bans held in memory by the bouncer.",
+        )
+        .set(self.bans.len() as f64);
+    }
+}
+
+impl BanStore for MemoryBanStore {
+    fn ban(&self, ip: IpAddr, duration: Duration, reason: &str) -> StoreFuture<'_, ()> {
+        if self.max_entries > 0
+            && self.bans.len() >= self.max_entries
+            && !self.bans.contains_key(&ip)
+        {
+            self.evict();
+        }
+        let now = Instant::now();
+        self.reasons.insert(ip, reason.to_string());
+        if self.eviction == Eviction::Lru {
+            self.last_seen.insert(ip, now);
+        }
+        self.bans.insert(ip, now + duration.min(MAX_BAN_DURATION));
+        self.record_size();
+        Box::pin(ready(Ok(())))
+    }
+
+    fn banned_until(&self, ip: IpAddr) -> StoreFuture<'_, Option<SystemTime>> {
+        // Expired bans are dropped when they're next looked up
+        let now = Instant::now();
+        let until = match self.bans.get(&ip).map(|expiry| *expiry) {
+            Some(expiry) if now < expiry => {
+                if self.eviction == Eviction::Lru {
+                    self.last_seen.insert(ip, now);
+                }
+                Some(SystemTime::now() + (expiry - now))
+            }
+            Some(_) => {
+                self.remove(&ip);
+                self.record_size();
+                None
+            }
+            None => None,
+        };
+        Box::pin(ready(Ok(until)))
+    }
+
+    fn unban(&self, ip: IpAddr) -> StoreFuture<'_, bool> {
+        let removed = self.remove(&ip);
+        self.record_size();
+        Box::pin(ready(Ok(removed)))
+    }
+
+    fn list(&self) -> StoreFuture<'_, Vec<Ban>> {
+        let now = Instant::now();
+        let wall = SystemTime::now();
+        let bans = self
+            .bans
+            .iter()
+            .filter(|entry| *entry.value() > now)
+            .map(|entry| Ban {
+                ip: *entry.key(),
+                expires_at: wall + entry.value().duration_since(now),
+                reason: self
+                    .reasons
+                    .get(entry.key())
+                    .map(|reason| reason.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Box::pin(ready(Ok(bans)))
+    }
+}
+
+impl From<BanList> for MemoryBanStore {
+    fn from(bans: BanList) -> Self {
+        Self {
+            bans,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(n: u8) -> IpAddr {
+        IpAddr::from([192, 0, 2, n])
+    }
+
+    async fn banned(store: &MemoryBanStore, n: u8) -> bool {
+        store.banned_until(ip(n)).await.unwrap().is_some()
+    }
+    #[tokio::test]
+    async fn clamps_ban_durations() {
+        let store = MemoryBanStore::new();
+        store.ban(ip(1), Duration::MAX, "").await.unwrap();
+        assert!(banned(&store, 1).await);
+        let until = store.banned_until(ip(1)).await.unwrap().unwrap();
+        assert!(until <= SystemTime::now() + MAX_BAN_DURATION);
+    }
+
+    #[tokio::test]
+    async fn evicts_the_soonest_expiring_ban() {
+        let store = MemoryBanStore::new().max_entries(3, Eviction::SoonestExpiry);
+        store
+            .ban(ip(1), Duration::from_secs(300), "")
+            .await
+            .unwrap();
+        store
+            .ban(ip(2), Duration::from_secs(100), "")
+            .await
+            .unwrap();
+        store
+            .ban(ip(3), Duration::from_secs(200), "")
+            .await
+            .unwrap();
+        store.ban(ip(4), Duration::from_secs(50), "").await.unwrap();
+
+        assert!(!banned(&store, 2).await);
+        assert!(banned(&store, 1).await && banned(&store, 3).await && banned(&store, 4).await);
+    }
+
+    #[tokio::test]
+    async fn evicts_the_least_recently_seen_ip() {
+        let store = MemoryBanStore::new().max_entries(3, Eviction::Lru);
+        for n in 1..=3 {
+            store
+                .ban(ip(n), Duration::from_secs(300), "")
+                .await
+                .unwrap();
+        }
+        // Seeing 1 again leaves 2 least recently seen
+        assert!(banned(&store, 1).await);
+        store
+            .ban(ip(4), Duration::from_secs(300), "")
+            .await
+            .unwrap();
+
+        assert!(!banned(&store, 2).await);
+        assert!(banned(&store, 1).await && banned(&store, 3).await && banned(&store, 4).await);
+    }
+
+    #[tokio::test]
+    async fn drops_expired_bans_before_evicting() {
+        let store = MemoryBanStore::new().max_entries(2, Eviction::SoonestExpiry);
+        store.ban(ip(1), Duration::ZERO, "").await.unwrap();
+        store
+            .ban(ip(2), Duration::from_secs(300), "")
+            .await
+            .unwrap();
+        store
+            .ban(ip(3), Duration::from_secs(300), "")
+            .await
+            .unwrap();
+
+        assert_eq!(store.banlist().len(), 2);
+        assert!(banned(&store, 2).await && banned(&store, 3).await);
+    }
+}
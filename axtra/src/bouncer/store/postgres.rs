@@ -0,0 +1,114 @@
+//! Postgres ban store.
+
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use sqlx::PgPool;
+use time::OffsetDateTime;
+
+use crate::bouncer::store::{Ban, BanStore, MAX_BAN_DURATION, StoreFuture};
+
+/// Bans kept in an `axtra_bouncer_bans` table, shared by every replica using the database.
+///
+/// Create the table with [`create_table`](Self::create_table) at startup, or with an
+/// equivalent migration of your own:
+///
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS axtra_bouncer_bans (
+///     ip TEXT PRIMARY KEY,
+///     expires_at TIMESTAMPTZ NOT NULL,
+///     reason TEXT NOT NULL DEFAULT ''
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct PostgresBanStore {
+    pool: PgPool,
+}
+
+impl PostgresBanStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create the bans table if it doesn't exist.
+    pub async fn create_table(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS axtra_bouncer_bans (
+                ip TEXT PRIMARY KEY,
+                expires_at TIMESTAMPTZ NOT NULL,
+                reason TEXT NOT NULL DEFAULT ''
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+impl BanStore for PostgresBanStore {
+    fn ban(&self, ip: IpAddr, duration: Duration, reason: &str) -> StoreFuture<'_, ()> {
+        let reason = reason.to_string();
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO axtra_bouncer_bans (ip, expires_at, reason) VALUES ($1, $2, $3)
+                ON CONFLICT (ip) DO UPDATE
+                SET expires_at = EXCLUDED.expires_at, reason = EXCLUDED.reason",
+            )
+            .bind(ip.to_string())
+            .bind(OffsetDateTime::from(
+                SystemTime::now() + duration.min(MAX_BAN_DURATION),
+            ))
+            .bind(reason)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn banned_until(&self, ip: IpAddr) -> StoreFuture<'_, Option<SystemTime>> {
+        Box::pin(async move {
+            let expires_at: Option<OffsetDateTime> = sqlx::query_scalar(
+                "SELECT expires_at FROM axtra_bouncer_bans WHERE ip = $1 AND expires_at > now()",
+            )
+            .bind(ip.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(expires_at.map(Into::into))
+        })
+    }
+
+    fn unban(&self, ip: IpAddr) -> StoreFuture<'_, bool> {
+        Box::pin(async move {
+            let result = sqlx::query("DELETE FROM axtra_bouncer_bans WHERE ip = $1")
+                .bind(ip.to_string())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+    }
+
+    fn list(&self) -> StoreFuture<'_, Vec<Ban>> {
+        Box::pin(async move {
+            // Expired rows are only ever read here, so clear them out first
+            sqlx::query("DELETE FROM axtra_bouncer_bans WHERE expires_at <= now()")
+                .execute(&self.pool)
+                .await?;
+            let rows: Vec<(String, OffsetDateTime, String)> =
+                sqlx::query_as("SELECT ip, expires_at, reason FROM axtra_bouncer_bans")
+                    .fetch_all(&self.pool)
+                    .await?;
+            Ok(rows
+                .into_iter()
+                .filter_map(|(ip, expires_at, reason)| {
+                    Some(Ban {
+                        ip: ip.parse().ok()?,
+                        expires_at: expires_at.into(),
+                        reason,
+                    })
+                })
+                .collect())
+        })
+    }
+}
@@ -0,0 +1,279 @@
+//! Redis ban store.
+
+use std::{
+    future::ready,
+    net::IpAddr,
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use dashmap::DashMap;
+use futures_util::StreamExt;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use crate::bouncer::store::{Ban, BanStore, MAX_BAN_DURATION, StoreError, StoreFuture};
+
+// Stored as each ban key's value, for listing
+#[derive(Serialize, Deserialize)]
+struct Record {
+    expires_at: u64,
+    reason: String,
+}
+
+// Published on the events channel whenever a replica bans or unbans an IP
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Event {
+    Ban { ip: IpAddr, expires_at: u64 },
+    Unban { ip: IpAddr },
+}
+
+// Local copy of every ban, kept current from the events channel. Only consulted while
+// subscribed, so a dropped connection falls back to asking Redis.
+#[derive(Debug, Default)]
+struct Mirror {
+    bans: DashMap<IpAddr, SystemTime>,
+    ready: AtomicBool,
+}
+
+/// Bans kept in Redis as one key per IP, expiring with the ban, so every replica sharing
+/// the Redis instance sees the same bans. Bans and unbans are also published on an events
+/// channel; with [`sync`](Self::sync), replicas answer lookups from a local copy kept
+/// current from that channel.
+#[derive(Clone)]
+pub struct RedisBanStore {
+    manager: ConnectionManager,
+    prefix: String,
+    channel: String,
+    mirror: Option<Arc<Mirror>>,
+}
+
+impl RedisBanStore {
+    pub fn new(manager: ConnectionManager) -> Self {
+        Self {
+            manager,
+            prefix: "axtra:bouncer:ban:".to_string(),
+            channel: "axtra:bouncer:events".to_string(),
+            mirror: None,
+        }
+    }
+
+    /// Prefix for ban keys, `axtra:bouncer:ban:` by default.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Channel ban and unban events are published on, `axtra:bouncer:events` by default.
+    pub fn channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = channel.into();
+        self
+    }
+
+    /// Keep every ban in memory, updated over Redis pub/sub as any replica bans or unbans
+    /// an IP, so lookups don't go to Redis on every request. `client` opens the subscriber
+    /// connection; on connection errors it resubscribes, reloading every ban, and lookups
+    /// go to Redis until it has.
+    ///
+    /// Call after [`prefix`](Self::prefix) and [`channel`](Self::channel), from within a
+    /// Tokio runtime.
+    pub fn sync(mut self, client: redis::Client) -> Self {
+        let mirror = Arc::new(Mirror::default());
+        tokio::spawn(subscribe(self.clone(), client, Arc::downgrade(&mirror)));
+        self.mirror = Some(mirror);
+        self
+    }
+
+    fn key(&self, ip: IpAddr) -> String {
+        format!("{}{ip}", self.prefix)
+    }
+}
+
+// Mirror bans until every clone of the store is dropped, resubscribing after errors
+async fn subscribe(store: RedisBanStore, client: redis::Client, mirror: Weak<Mirror>) {
+    loop {
+        if let Err(e) = mirror_bans(&store, &client, &mirror).await {
+            tracing::warn!(error = %e, "Redis ban sync interrupted, resubscribing");
+        }
+        match mirror.upgrade() {
+            Some(mirror) => mirror.ready.store(false, Ordering::Release),
+            None => return,
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+// Load every ban into the mirror, then apply events as they arrive. Returns `Ok` once
+// the mirror is dropped.
+async fn mirror_bans(
+    store: &RedisBanStore,
+    client: &redis::Client,
+    mirror: &Weak<Mirror>,
+) -> Result<(), StoreError> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    // Subscribe before loading, so bans made in between aren't missed
+    pubsub.subscribe(&store.channel).await?;
+    let bans = store.list().await?;
+    {
+        let Some(mirror) = mirror.upgrade() else {
+            return Ok(());
+        };
+        mirror.bans.clear();
+        for ban in bans {
+            mirror.bans.insert(ban.ip, ban.expires_at);
+        }
+        mirror.ready.store(true, Ordering::Release);
+    }
+
+    let mut sweep = tokio::time::interval(Duration::from_secs(60));
+    let mut messages = pubsub.on_message();
+    loop {
+        tokio::select! {
+            message = messages.next() => {
+                let message = message.ok_or("Redis pub/sub connection closed")?;
+                let Some(mirror) = mirror.upgrade() else {
+                    return Ok(());
+                };
+                let payload: String = message.get_payload()?;
+                match serde_json::from_str(&payload) {
+                    Ok(Event::Ban { ip, expires_at }) => {
+                        let until = UNIX_EPOCH + Duration::from_millis(expires_at);
+                        mirror.bans.insert(ip, until);
+                    }
+                    Ok(Event::Unban { ip }) => {
+                        mirror.bans.remove(&ip);
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Ignoring malformed ban event"),
+                }
+            }
+            _ = sweep.tick() => {
+                let Some(mirror) = mirror.upgrade() else {
+                    return Ok(());
+                };
+                let now = SystemTime::now();
+                mirror.bans.retain(|_, until| *until > now);
+            }
+        }
+    }
+}
+
+impl BanStore for RedisBanStore {
+    fn ban(&self, ip: IpAddr, duration: Duration, reason: &str) -> StoreFuture<'_, ()> {
+        let reason = reason.to_string();
+        let duration = duration.min(MAX_BAN_DURATION);
+        Box::pin(async move {
+            let until = SystemTime::now() + duration;
+            let expires_at = until.duration_since(UNIX_EPOCH)?.as_millis() as u64;
+            let record = serde_json::to_string(&Record { expires_at, reason })?;
+            let event = serde_json::to_string(&Event::Ban { ip, expires_at })?;
+            let mut conn = self.manager.clone();
+            redis::pipe()
+                .cmd("SET")
+                .arg(self.key(ip))
+                .arg(record)
+                .arg("PX")
+                .arg((duration.as_millis() as u64).max(1))
+                .ignore()
+                .cmd("PUBLISH")
+                .arg(&self.channel)
+                .arg(event)
+                .ignore()
+                .query_async::<()>(&mut conn)
+                .await?;
+            if let Some(mirror) = &self.mirror {
+                mirror.bans.insert(ip, until);
+            }
+            Ok(())
+        })
+    }
+
+    fn banned_until(&self, ip: IpAddr) -> StoreFuture<'_, Option<SystemTime>> {
+        if let Some(mirror) = self
+            .mirror
+            .as_ref()
+            .filter(|mirror| mirror.ready.load(Ordering::Acquire))
+        {
+            let now = SystemTime::now();
+            let until = match mirror.bans.get(&ip).map(|until| *until) {
+                Some(until) if until > now => Some(until),
+                Some(_) => {
+                    mirror.bans.remove(&ip);
+                    None
+                }
+                None => None,
+            };
+            return Box::pin(ready(Ok(until)));
+        }
+        Box::pin(async move {
+            let mut conn = self.manager.clone();
+            // Milliseconds left, or negative for missing keys (every ban key has a TTL)
+            let ttl: i64 = redis::cmd("PTTL")
+                .arg(self.key(ip))
+                .query_async(&mut conn)
+                .await?;
+            Ok(u64::try_from(ttl)
+                .ok()
+                .map(|ttl| SystemTime::now() + Duration::from_millis(ttl)))
+        })
+    }
+
+    fn unban(&self, ip: IpAddr) -> StoreFuture<'_, bool> {
+        Box::pin(async move {
+            let event = serde_json::to_string(&Event::Unban { ip })?;
+            let mut conn = self.manager.clone();
+            let (removed,): (u64,) = redis::pipe()
+                .cmd("DEL")
+                .arg(self.key(ip))
+                .cmd("PUBLISH")
+                .arg(&self.channel)
+                .arg(event)
+                .ignore()
+                .query_async(&mut conn)
+                .await?;
+            if let Some(mirror) = &self.mirror {
+                mirror.bans.remove(&ip);
+            }
+            Ok(removed > 0)
+        })
+    }
+
+    fn list(&self) -> StoreFuture<'_, Vec<Ban>> {
+        Box::pin(async move {
+            let mut conn = self.manager.clone();
+            let pattern = format!("{}*", self.prefix);
+            let mut bans = Vec::new();
+            let mut cursor = 0u64;
+            loop {
+                let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(100)
+                    .query_async(&mut conn)
+                    .await?;
+                if !keys.is_empty() {
+                    // Keys may expire between the scan and the lookup
+                    let records: Vec<Option<String>> =
+                        redis::cmd("MGET").arg(&keys).query_async(&mut conn).await?;
+                    bans.extend(keys.iter().zip(records).filter_map(|(key, record)| {
+                        let record: Record = serde_json::from_str(&record?).ok()?;
+                        Some(Ban {
+                            ip: key.strip_prefix(&self.prefix)?.parse().ok()?,
+                            expires_at: UNIX_EPOCH + Duration::from_millis(record.expires_at),
+                            reason: record.reason,
+                        })
+                    }));
+                }
+                if next == 0 {
+                    return Ok(bans);
+                }
+                cursor = next;
+            }
+        })
+    }
+}
@@ -5,14 +5,17 @@
 //! - Error construction macros ([`app_error!`])
 //! - TypeScript type generation for error codes
 //! - Notification integration (Slack, Discord, Sentry)
+//! - [`RequestIdLayer`] so notifications can name the failed request
 //! - Automatic error location tracking
 //!
 //! See crate-level docs for usage examples.
 
 mod macros;
-mod notifiers;
+pub(crate) mod notifiers;
+mod request_id;
 mod response;
 mod types;
 
 // Re-export everything users need
+pub use request_id::{RequestIdLayer, request_id};
 pub use types::*;
@@ -1,11 +1,118 @@
-//! Error notification handlers for Slack and Discord
+//! Error notification handlers for Slack, Discord, Teams, Telegram, Matrix, ntfy, Pushover,
+//! email and PagerDuty
 
-#[cfg(any(feature = "notify-error-slack", feature = "notify-error-discord"))]
-use crate::notifier::Notifier;
+#[cfg(feature = "notify-error-pagerduty")]
+use crate::notifier::PagerDuty;
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover"
+))]
+use crate::notifier::config;
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover",
+    feature = "notify-error-email"
+))]
+use crate::notifier::{Message, NotificationBackend, Notifier, NotifyFuture};
 
-#[cfg(any(feature = "notify-error-slack", feature = "notify-error-discord"))]
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover",
+    feature = "notify-error-email"
+))]
+use std::sync::Arc;
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover",
+    feature = "notify-error-email",
+    feature = "notify-error-pagerduty"
+))]
 use std::sync::OnceLock;
 
+// Applies the client or timeout from `notifier::init` to a webhook notifier
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover"
+))]
+fn configured(notifier: Notifier) -> Notifier {
+    let Some(config) = config() else {
+        return notifier;
+    };
+    match (&config.client, config.timeout) {
+        (Some(client), _) => notifier.client(client.clone()),
+        (None, Some(timeout)) => notifier.timeout(timeout),
+        (None, None) => notifier,
+    }
+}
+
+// One of the notifiers below, in a list of backends alongside registered ones
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover",
+    feature = "notify-error-email"
+))]
+pub(crate) fn shared(notifier: &'static Notifier) -> Arc<dyn NotificationBackend> {
+    Arc::new(Shared(notifier))
+}
+
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover",
+    feature = "notify-error-email"
+))]
+struct Shared(&'static Notifier);
+
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover",
+    feature = "notify-error-email"
+))]
+impl NotificationBackend for Shared {
+    fn send(&self, message: Message) -> NotifyFuture<'_> {
+        self.0.send(message)
+    }
+}
+
 // Notification Clients
 #[cfg(feature = "notify-error-slack")]
 static SLACK_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
@@ -14,9 +121,11 @@ static SLACK_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
 pub fn slack_notifier() -> Option<&'static Notifier> {
     SLACK_NOTIFIER
         .get_or_init(|| {
-            std::env::var("SLACK_ERROR_WEBHOOK_URL")
-                .ok()
+            config()
+                .and_then(|config| config.slack.clone())
+                .or_else(|| std::env::var("SLACK_ERROR_WEBHOOK_URL").ok())
                 .map(Notifier::with_slack)
+                .map(configured)
         })
         .as_ref()
 }
@@ -28,9 +137,133 @@ static DISCORD_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
 pub fn discord_notifier() -> Option<&'static Notifier> {
     DISCORD_NOTIFIER
         .get_or_init(|| {
-            std::env::var("DISCORD_ERROR_WEBHOOK_URL")
-                .ok()
+            config()
+                .and_then(|config| config.discord.clone())
+                .or_else(|| std::env::var("DISCORD_ERROR_WEBHOOK_URL").ok())
                 .map(Notifier::with_discord)
+                .map(configured)
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "notify-error-teams")]
+static TEAMS_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
+
+#[cfg(feature = "notify-error-teams")]
+pub fn teams_notifier() -> Option<&'static Notifier> {
+    TEAMS_NOTIFIER
+        .get_or_init(|| {
+            std::env::var("TEAMS_ERROR_WEBHOOK_URL")
+                .ok()
+                .map(Notifier::with_teams)
+                .map(configured)
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "notify-error-telegram")]
+static TELEGRAM_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
+
+#[cfg(feature = "notify-error-telegram")]
+pub fn telegram_notifier() -> Option<&'static Notifier> {
+    TELEGRAM_NOTIFIER
+        .get_or_init(|| {
+            let bot_token = std::env::var("TELEGRAM_ERROR_BOT_TOKEN").ok()?;
+            let chat_id = std::env::var("TELEGRAM_ERROR_CHAT_ID").ok()?;
+            Some(configured(Notifier::with_telegram(bot_token, chat_id)))
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "notify-error-matrix")]
+static MATRIX_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
+
+#[cfg(feature = "notify-error-matrix")]
+pub fn matrix_notifier() -> Option<&'static Notifier> {
+    MATRIX_NOTIFIER
+        .get_or_init(|| {
+            let homeserver = std::env::var("MATRIX_ERROR_HOMESERVER").ok()?;
+            let access_token = std::env::var("MATRIX_ERROR_ACCESS_TOKEN").ok()?;
+            let room_id = std::env::var("MATRIX_ERROR_ROOM_ID").ok()?;
+            Some(configured(Notifier::with_matrix(
+                homeserver,
+                access_token,
+                room_id,
+            )))
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "notify-error-ntfy")]
+static NTFY_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
+
+#[cfg(feature = "notify-error-ntfy")]
+pub fn ntfy_notifier() -> Option<&'static Notifier> {
+    NTFY_NOTIFIER
+        .get_or_init(|| {
+            let url = std::env::var("NTFY_ERROR_URL").ok()?;
+            let notifier = configured(Notifier::with_ntfy(url));
+            Some(match std::env::var("NTFY_ERROR_TOKEN") {
+                Ok(token) => notifier.ntfy_token(token),
+                Err(_) => notifier,
+            })
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "notify-error-pushover")]
+static PUSHOVER_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
+
+#[cfg(feature = "notify-error-pushover")]
+pub fn pushover_notifier() -> Option<&'static Notifier> {
+    PUSHOVER_NOTIFIER
+        .get_or_init(|| {
+            let app_token = std::env::var("PUSHOVER_ERROR_TOKEN").ok()?;
+            let user_key = std::env::var("PUSHOVER_ERROR_USER").ok()?;
+            Some(configured(Notifier::with_pushover(app_token, user_key)))
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "notify-error-email")]
+static EMAIL_NOTIFIER: OnceLock<Option<Notifier>> = OnceLock::new();
+
+#[cfg(feature = "notify-error-email")]
+pub fn email_notifier() -> Option<&'static Notifier> {
+    use crate::notifier::SmtpConfig;
+
+    EMAIL_NOTIFIER
+        .get_or_init(|| {
+            let url = std::env::var("EMAIL_ERROR_SMTP_URL").ok()?;
+            let from = std::env::var("EMAIL_ERROR_FROM").ok()?;
+            let to = std::env::var("EMAIL_ERROR_TO").ok()?;
+
+            let config = to
+                .split(',')
+                .map(str::trim)
+                .filter(|address| !address.is_empty())
+                .fold(SmtpConfig::url(url).from(from), SmtpConfig::to);
+            match Notifier::try_with_email(config) {
+                Ok(notifier) => Some(notifier),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Invalid email error notification config, disabled");
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "notify-error-pagerduty")]
+static PAGERDUTY: OnceLock<Option<PagerDuty>> = OnceLock::new();
+
+#[cfg(feature = "notify-error-pagerduty")]
+pub fn pagerduty() -> Option<&'static PagerDuty> {
+    PAGERDUTY
+        .get_or_init(|| {
+            std::env::var("PAGERDUTY_ROUTING_KEY")
+                .ok()
+                .map(PagerDuty::new)
         })
         .as_ref()
 }
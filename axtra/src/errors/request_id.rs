@@ -0,0 +1,101 @@
+//! The ID of the request being handled, for error notifications and logs.
+
+use std::{future::Future, pin::Pin};
+
+use axum::http::{HeaderName, Request, Response};
+use tower::{Layer, Service};
+
+tokio::task_local! {
+    static REQUEST_ID: Option<String>;
+}
+
+/// The ID of the request being handled, as read by [`RequestIdLayer`], or `None` outside
+/// a request or when the request had none.
+pub fn request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok().flatten()
+}
+
+/// Makes each request's ID available to [`request_id`] and error notifications while the
+/// request is handled.
+///
+/// The ID is read from the `x-request-id` header, so set it at the proxy or with
+/// `tower_http::request_id::SetRequestIdLayer` outside this layer.
+///
+/// ```rust, ignore
+/// let app = Router::new()
+///     .route("/", get(handler))
+///     .layer(RequestIdLayer::new());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestIdLayer {
+    header: HeaderName,
+}
+
+impl Default for RequestIdLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestIdLayer {
+    pub fn new() -> Self {
+        Self {
+            header: HeaderName::from_static("x-request-id"),
+        }
+    }
+
+    /// Read the ID from `header` instead, such as `x-correlation-id`.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdMiddleware {
+            inner,
+            header: self.header.clone(),
+        }
+    }
+}
+
+// Runs the wrapped service with the request's ID in scope
+#[derive(Debug, Clone)]
+pub struct RequestIdMiddleware<S> {
+    inner: S,
+    header: HeaderName,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for RequestIdMiddleware<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let id = req
+            .headers()
+            .get(&self.header)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(REQUEST_ID.scope(id, async move { inner.call(req).await }))
+    }
+}
@@ -1,25 +1,69 @@
 //! Response handling and conversion logic for AppError.
 
-use axum::{
-    Json,
-    response::{Html, IntoResponse, Response},
-};
-use std::{fs, path::Path};
+#[cfg(feature = "notifier")]
+use std::sync::Arc;
+
+use axum::response::{Html, IntoResponse, Response};
 
 use crate::errors::{AppError, ErrorCode, ErrorFormat, ErrorResponse};
+use crate::response::json::json_response;
+use crate::routes::astro::error_page;
 
 #[cfg(feature = "notify-error-discord")]
 use crate::errors::notifiers::discord_notifier;
+#[cfg(feature = "notify-error-email")]
+use crate::errors::notifiers::email_notifier;
+#[cfg(feature = "notify-error-matrix")]
+use crate::errors::notifiers::matrix_notifier;
+#[cfg(feature = "notify-error-ntfy")]
+use crate::errors::notifiers::ntfy_notifier;
+#[cfg(feature = "notify-error-pagerduty")]
+use crate::errors::notifiers::pagerduty;
+#[cfg(feature = "notify-error-pushover")]
+use crate::errors::notifiers::pushover_notifier;
+#[cfg(any(feature = "notify-error-slack", feature = "notify-error-discord"))]
+use crate::errors::notifiers::shared;
 #[cfg(feature = "notify-error-slack")]
 use crate::errors::notifiers::slack_notifier;
+#[cfg(feature = "notify-error-teams")]
+use crate::errors::notifiers::teams_notifier;
+#[cfg(feature = "notify-error-telegram")]
+use crate::errors::notifiers::telegram_notifier;
+#[cfg(feature = "notifier")]
+use crate::errors::request_id;
+#[cfg(feature = "notifier")]
+use crate::notifier::{
+    Level, Message, NotificationBackend, add_to_digest, app_name, config, environment,
+    error_mentions, error_template, registered_backends,
+};
+#[cfg(feature = "notifier")]
+use crate::routes::health::build_info;
 
 macro_rules! notify_critical_error {
     ($self:expr) => {
-        #[cfg(feature = "notify-error-slack")]
-        $self.send_slack_notification();
+        #[cfg(feature = "notifier")]
+        $self.send_backend_notifications();
 
-        #[cfg(feature = "notify-error-discord")]
-        $self.send_discord_notification();
+        #[cfg(feature = "notify-error-teams")]
+        $self.send_teams_notification();
+
+        #[cfg(feature = "notify-error-telegram")]
+        $self.send_telegram_notification();
+
+        #[cfg(feature = "notify-error-matrix")]
+        $self.send_matrix_notification();
+
+        #[cfg(feature = "notify-error-ntfy")]
+        $self.send_ntfy_notification();
+
+        #[cfg(feature = "notify-error-pushover")]
+        $self.send_pushover_notification();
+
+        #[cfg(feature = "notify-error-email")]
+        $self.send_email_notification();
+
+        #[cfg(feature = "notify-error-pagerduty")]
+        $self.send_pagerduty_event();
 
         #[cfg(feature = "sentry")]
         sentry::capture_error(&$self);
@@ -86,69 +130,217 @@ impl AppError {
         }
     }
 
-    #[cfg(feature = "notify-error-discord")]
-    fn send_discord_notification(&self) {
-        if let Some(notifier) = discord_notifier() {
-            let app_name = std::env::var("APP_NAME").unwrap_or_else(|_| "Rust".to_string());
-            let formatted_message = self.formatted_message();
+    /// The alert sent to notification backends for this error, in the error template.
+    #[cfg(feature = "notifier")]
+    fn notification(&self) -> Message {
+        let app_name = app_name();
+        let environment = environment();
+        let code = format!("{:?}", self.code());
+        let request_id = request_id();
+        let release = build_info().map(|info| match info.git_sha {
+            Some(sha) => format!("{} ({})", info.version, sha.get(..7).unwrap_or(sha)),
+            None => info.version.to_string(),
+        });
+        let (title, body) = error_template().render(&[
+            ("app", &app_name),
+            ("environment", &environment),
+            ("code", &code),
+            ("status", self.status_code().as_str()),
+            ("location", self.location()),
+            ("message", &self.log_message()),
+            ("request_id", request_id.as_deref().unwrap_or("-")),
+            ("release", release.as_deref().unwrap_or("-")),
+        ]);
 
-            let embeds = serde_json::json!([
-                {
-                    "title": format!(":red_circle: Exception — {app_name}"),
-                    "color": 16711680, // Red
-                    "fields": [
-                        {
-                            "name": "Details",
-                            "value": format!("```{formatted_message}```"),
-                            "inline": false
-                        },
-                        {
-                            "name": "\u{200B}",
-                            "value": "@oncall",
-                            "inline": false
-                        }
-                    ]
-                }
-            ]);
+        let level = match self.code() {
+            ErrorCode::Database => Level::Critical,
+            _ => Level::Error,
+        };
+        let mut message = Message::default()
+            .title(title)
+            .field("Code", code)
+            .field("Status", self.status_code().as_str())
+            .field("Location", self.location());
+        let optional = [
+            ("Request ID", request_id),
+            (
+                "Environment",
+                Some(environment).filter(|env| !env.is_empty()),
+            ),
+            ("Release", release),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                message = message.field(name, value);
+            }
+        }
+        message
+            .code(body)
+            .level(level)
+            .mentions(error_mentions(level))
+    }
+
+    // Slack, Discord and any backends the app registered
+    #[cfg(feature = "notifier")]
+    fn send_backend_notifications(&self) {
+        let mut backends: Vec<Arc<dyn NotificationBackend>> = Vec::new();
+        #[cfg(feature = "notify-error-slack")]
+        backends.extend(slack_notifier().map(shared));
+        #[cfg(feature = "notify-error-discord")]
+        backends.extend(discord_notifier().map(shared));
+        backends.extend(registered_backends());
+        if backends.is_empty() {
+            return;
+        }
+
+        let message = self.notification();
+        if let Some(interval) = config().and_then(|config| config.digest)
+            && message.level < Level::Critical
+        {
+            let code = format!("{:?}", self.code());
+            add_to_digest(
+                interval,
+                backends,
+                code,
+                self.location(),
+                self.log_message(),
+            );
+            return;
+        }
+        for backend in backends {
+            let message = message.clone();
             tokio::spawn(async move {
-                let _ = notifier.notify_discord_rich(embeds).await;
+                let _ = backend.send(message).await;
             });
         }
     }
 
-    #[cfg(feature = "notify-error-slack")]
-    fn send_slack_notification(&self) {
-        if let Some(notifier) = slack_notifier() {
-            let app_name = std::env::var("APP_NAME").unwrap_or("Rust".to_string());
+    #[cfg(feature = "notify-error-teams")]
+    fn send_teams_notification(&self) {
+        if let Some(notifier) = teams_notifier() {
+            let app_name = app_name();
             let formatted_message = self.formatted_message();
 
-            let blocks = serde_json::json!([
+            let body = serde_json::json!([
                 {
-                    "type": "section",
-                    "text": {
-                        "type": "mrkdwn",
-                        "text": format!(":red_circle: *Exception* — `{app_name}`")
-                    }
+                    "type": "TextBlock",
+                    "text": format!("\u{1F534} Exception — {app_name}"),
+                    "size": "Medium",
+                    "weight": "Bolder",
+                    "color": "Attention",
+                    "wrap": true
                 },
                 {
-                    "type": "section",
-                    "text": {
-                        "type": "mrkdwn",
-                        "text": format!("```{formatted_message}```")
-                    }
+                    "type": "TextBlock",
+                    "text": formatted_message,
+                    "fontType": "Monospace",
+                    "wrap": true
                 },
-                {
-                    "type": "context",
-                    "elements": [
-                        {
-                            "type": "mrkdwn",
-                            "text": "@oncall"
-                        }
-                    ]
-                }
             ]);
             tokio::spawn(async move {
-                let _ = notifier.notify_slack_rich(blocks).await;
+                let _ = notifier.notify_teams_rich(body).await;
+            });
+        }
+    }
+
+    #[cfg(feature = "notify-error-matrix")]
+    fn send_matrix_notification(&self) {
+        use crate::notifier::escape_html;
+
+        if let Some(notifier) = matrix_notifier() {
+            let app_name = app_name();
+            let formatted_message = self.formatted_message();
+
+            let plain = format!("\u{1F534} Exception — {app_name}\n{formatted_message}");
+            let html = format!(
+                "<strong>\u{1F534} Exception — {}</strong><pre><code>{}</code></pre>",
+                escape_html(&app_name),
+                escape_html(&formatted_message),
+            );
+            tokio::spawn(async move {
+                let _ = notifier.notify_matrix_rich(plain, html).await;
+            });
+        }
+    }
+
+    #[cfg(feature = "notify-error-ntfy")]
+    fn send_ntfy_notification(&self) {
+        if let Some(notifier) = ntfy_notifier() {
+            let app_name = app_name();
+            let title = format!("Exception — {app_name}");
+            let message = self.formatted_message();
+            tokio::spawn(async move {
+                let _ = notifier
+                    .notify_ntfy_rich(Some(&title), message, Some(5), &["rotating_light"])
+                    .await;
+            });
+        }
+    }
+
+    #[cfg(feature = "notify-error-pushover")]
+    fn send_pushover_notification(&self) {
+        if let Some(notifier) = pushover_notifier() {
+            let app_name = app_name();
+            let title = format!("Exception — {app_name}");
+            let message = self.formatted_message();
+            tokio::spawn(async move {
+                let _ = notifier
+                    .notify_pushover_rich(Some(&title), message, Some(1))
+                    .await;
+            });
+        }
+    }
+
+    #[cfg(feature = "notify-error-email")]
+    fn send_email_notification(&self) {
+        if let Some(notifier) = email_notifier() {
+            let app_name = app_name();
+            let subject = format!(
+                "[{app_name}] {:?} error at {}",
+                self.code(),
+                self.location()
+            );
+            let body = format!("Exception — {app_name}\n\n{}\n", self.formatted_message());
+            tokio::spawn(async move {
+                let _ = notifier.notify_email(subject, body).await;
+            });
+        }
+    }
+
+    #[cfg(feature = "notify-error-pagerduty")]
+    fn send_pagerduty_event(&self) {
+        use crate::notifier::{Event, PagerDuty, Severity};
+
+        if let Some(pagerduty) = pagerduty() {
+            let severity = match self.code() {
+                ErrorCode::Database => Severity::Critical,
+                _ => Severity::Error,
+            };
+            let event = Event::new(self.formatted_message(), severity)
+                .dedup_key(PagerDuty::location_key(self.location()))
+                .class(format!("{:?}", self.code()))
+                .details(serde_json::json!({ "location": self.location() }));
+            tokio::spawn(async move {
+                let _ = pagerduty.trigger(event).await;
+            });
+        }
+    }
+
+    #[cfg(feature = "notify-error-telegram")]
+    fn send_telegram_notification(&self) {
+        use crate::notifier::{escape_markdown_v2, escape_markdown_v2_code};
+
+        if let Some(notifier) = telegram_notifier() {
+            let app_name = app_name();
+            let formatted_message = self.formatted_message();
+
+            let markdown = format!(
+                "\u{1F534} *Exception — {}*\n```\n{}\n```",
+                escape_markdown_v2(&app_name),
+                escape_markdown_v2_code(&formatted_message),
+            );
+            tokio::spawn(async move {
+                let _ = notifier.notify_telegram_rich(markdown).await;
             });
         }
     }
@@ -161,6 +353,11 @@ impl IntoResponse for AppError {
         let error_code = self.code();
         let formatted_message = self.formatted_message();
 
+        crate::metrics::counter("axtra_errors_total", "Errors returned as responses.").inc_with(&[
+            ("code", &format!("{error_code:?}")),
+            ("status", status.as_str()),
+        ]);
+
         // Log the error
         match error_code {
             ErrorCode::Authentication | ErrorCode::Authorization => {
@@ -187,17 +384,14 @@ impl IntoResponse for AppError {
                         _ => None,
                     },
                 };
-                (status, Json(error_response)).into_response()
+                (status, json_response(&error_response)).into_response()
             }
             ErrorFormat::Html => {
-                let file_path = match error_code {
-                    ErrorCode::NotFound => "dist/404.html",
-                    _ => "dist/500.html",
-                };
-
-                let html_content = fs::read_to_string(Path::new(file_path)).unwrap_or_else(|_| {
-                    format!(
-                        r#"
+                let html_content = error_page(status)
+                    .and_then(|bytes| String::from_utf8(bytes.into_owned()).ok())
+                    .unwrap_or_else(|| {
+                        format!(
+                            r#"
                         <!DOCTYPE html>
                         <html lang="en">
                         <head>
@@ -210,9 +404,9 @@ impl IntoResponse for AppError {
                         </body>
                         </html>
                         "#,
-                        self.user_message()
-                    )
-                });
+                            self.user_message()
+                        )
+                    });
 
                 (status, Html(html_content)).into_response()
             }
@@ -9,7 +9,8 @@
 //! - **TypeScript Type Generation**: Rust error types exported via `ts-rs`.
 //! - **Error Notifications**: Sentry, Slack, Discord integration (optional).
 //! - **Wrapped JSON Responses**: `WrappedJson<T>` and `ResponseKey` derive macro.
-//! - **Health Check Endpoint**: Built-in Axum route for Postgres connectivity.
+//! - **Health Check Endpoint**: Pluggable health registry with Postgres and Redis indicators.
+//! - **Metrics**: Prometheus `/metrics` route fed by errors, bouncer and your own counters.
 //! - **Static File Serving**: SPA and static file helpers for Axum.
 //! - **Bouncer** (optional): Reject and ban IP's hitting invalid endpoints.
 //!
@@ -23,6 +24,8 @@ pub use axtra_macros::*;
 #[cfg(feature = "bouncer")]
 pub mod bouncer;
 pub mod errors;
+pub mod metrics;
+mod net;
 #[cfg(feature = "notifier")]
 pub mod notifier;
 pub mod response;
@@ -0,0 +1,189 @@
+//! In-process metrics registry rendered in the Prometheus text format.
+//!
+//! Axtra modules record into it automatically (`axtra_errors_total`,
+//! `axtra_bouncer_rejections_total`, `axtra_deprecated_requests_total`, ...) and
+//! applications can register their own counters and gauges. Serve it with
+//! [`routes::metrics`](crate::routes::metrics).
+//!
+//! ```rust, ignore
+//! let signups = axtra::metrics::counter("signups_total", "Completed signups");
+//! signups.inc_with(&[("plan", "pro")]);
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+static REGISTRY: OnceLock<Mutex<BTreeMap<&'static str, Arc<Family>>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Counter,
+    Gauge,
+}
+
+impl Kind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+        }
+    }
+}
+
+// A metric name with its help text and one value per label set
+#[derive(Debug)]
+struct Family {
+    help: &'static str,
+    kind: Kind,
+    values: Mutex<BTreeMap<String, f64>>,
+}
+
+impl Family {
+    fn update(&self, labels: &[(&str, &str)], f: impl FnOnce(&mut f64)) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        f(values.entry(render_labels(labels)).or_insert(0.0));
+    }
+}
+
+// Panics if `name` is already registered as another kind
+fn family(name: &'static str, help: &'static str, kind: Kind) -> Arc<Family> {
+    let registry = REGISTRY.get_or_init(Default::default);
+    let mut families = registry.lock().unwrap_or_else(|e| e.into_inner());
+    let family = families
+        .entry(name)
+        .or_insert_with(|| {
+            Arc::new(Family {
+                help,
+                kind,
+                values: Mutex::new(BTreeMap::new()),
+            })
+        })
+        .clone();
+    // Unlock first, so the panic doesn't poison the registry
+    drop(families);
+    assert!(
+        family.kind == kind,
+        "metric {name:?} is registered as a {}, not a {}",
+        family.kind.as_str(),
+        kind.as_str()
+    );
+    family
+}
+
+/// A monotonically increasing value.
+#[derive(Debug, Clone)]
+pub struct Counter {
+    family: Arc<Family>,
+}
+
+impl Counter {
+    pub fn inc(&self) {
+        self.inc_by_with(1.0, &[]);
+    }
+
+    pub fn inc_by(&self, value: f64) {
+        self.inc_by_with(value, &[]);
+    }
+
+    pub fn inc_with(&self, labels: &[(&str, &str)]) {
+        self.inc_by_with(1.0, labels);
+    }
+
+    pub fn inc_by_with(&self, value: f64, labels: &[(&str, &str)]) {
+        self.family.update(labels, |current| *current += value);
+    }
+}
+
+/// A value that can go up and down.
+#[derive(Debug, Clone)]
+pub struct Gauge {
+    family: Arc<Family>,
+}
+
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.set_with(value, &[]);
+    }
+
+    pub fn set_with(&self, value: f64, labels: &[(&str, &str)]) {
+        self.family.update(labels, |current| *current = value);
+    }
+
+    pub fn add_with(&self, value: f64, labels: &[(&str, &str)]) {
+        self.family.update(labels, |current| *current += value);
+    }
+}
+
+/// Register a counter, or get the existing one with this name.
+///
+/// Panics if `name` is already registered as a gauge.
+pub fn counter(name: &'static str, help: &'static str) -> Counter {
+    Counter {
+        family: family(name, help, Kind::Counter),
+    }
+}
+
+/// Register a gauge, or get the existing one with this name.
+///
+/// Panics if `name` is already registered as a counter.
+pub fn gauge(name: &'static str, help: &'static str) -> Gauge {
+    Gauge {
+        family: family(name, help, Kind::Gauge),
+    }
+}
+
+/// Render every registered metric in the Prometheus text exposition format.
+pub fn render() -> String {
+    let mut out = String::new();
+    let Some(registry) = REGISTRY.get() else {
+        return out;
+    };
+
+    let families = registry.lock().unwrap_or_else(|e| e.into_inner());
+    for (name, family) in families.iter() {
+        let values = family.values.lock().unwrap_or_else(|e| e.into_inner());
+        if values.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "# HELP {name} {}", family.help);
+        let _ = writeln!(out, "# TYPE {name} {}", family.kind.as_str());
+        for (labels, value) in values.iter() {
+            let _ = writeln!(out, "{name}{labels} {value}");
+        }
+    }
+    out
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{labels}}}")
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "registered as a counter, not a gauge")]
+    fn rejects_a_name_registered_as_another_kind() {
+        counter("test_kind_mismatch_total", "A counter.").inc();
+        gauge("test_kind_mismatch_total", "A gauge.");
+    }
+}
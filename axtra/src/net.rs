@@ -0,0 +1,180 @@
+//! Shared request helpers.
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::http::{Extensions, HeaderMap};
+
+// Extract the real client IP
+// Trust proxy must be set to use proxy headers as they can be spoofed
+pub(crate) fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trust_proxy: bool,
+) -> Option<IpAddr> {
+    // Check proxy headers if configured to trust them, otherwise skip to direct connection IP
+    if trust_proxy {
+        // 1. Cloudflare
+        if let Some(ip) = headers
+            .get("cf-connecting-ip")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|s| s.parse().ok())
+        {
+            return Some(ip);
+        }
+        // 2. X-Forwarded-For (first in list)
+        if let Some(ip) = headers
+            .get("x-forwarded-for")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|s| s.split(',').next())
+            .and_then(|s| s.trim().parse().ok())
+        {
+            return Some(ip);
+        }
+        // 3. X-Real-IP
+        if let Some(ip) = headers
+            .get("x-real-ip")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|s| s.parse().ok())
+        {
+            return Some(ip);
+        }
+    }
+
+    // Fallback to direct connection IP if available
+    extensions
+        .get::<axum::extract::ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip())
+}
+
+// Compare secrets without short-circuiting on the first differing byte
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// An IP network in CIDR notation (`10.0.0.0/8`), or a single address.
+///
+/// The address is stored masked to the prefix, so `10.1.2.3/8` and `10.0.0.0/8` are equal.
+#[cfg(feature = "bouncer")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+#[cfg(feature = "bouncer")]
+impl IpNet {
+    /// The network of `prefix` bits containing `ip`, or `None` if `prefix` is too long.
+    pub(crate) fn new(ip: IpAddr, prefix: u8) -> Option<Self> {
+        // IPv4-mapped IPv6 clients (`::ffff:10.0.0.1`) belong to IPv4 networks
+        let addr = match ip.to_canonical() {
+            IpAddr::V4(ip) if prefix <= 32 => IpAddr::from(mask(ip.octets(), prefix)),
+            IpAddr::V6(ip) if prefix <= 128 => IpAddr::from(mask(ip.octets(), prefix)),
+            _ => return None,
+        };
+        Some(Self { addr, prefix })
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value.split_once('/') {
+            Some((addr, prefix)) => Self::new(addr.parse().ok()?, prefix.parse().ok()?),
+            None => {
+                let addr: IpAddr = value.parse().ok()?;
+                Self::new(
+                    addr,
+                    if addr.to_canonical().is_ipv4() {
+                        32
+                    } else {
+                        128
+                    },
+                )
+            }
+        }
+    }
+
+    /// The network's first address.
+    pub(crate) fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub(crate) fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    pub(crate) fn is_ipv4(&self) -> bool {
+        self.addr.is_ipv4()
+    }
+
+    pub(crate) fn contains(&self, ip: IpAddr) -> bool {
+        Self::new(ip, self.prefix).is_some_and(|network| network == *self)
+    }
+}
+
+#[cfg(feature = "bouncer")]
+impl std::fmt::Display for IpNet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+// Clear every bit after the first `bits`
+#[cfg(feature = "bouncer")]
+fn mask<const N: usize>(mut octets: [u8; N], bits: u8) -> [u8; N] {
+    for (i, octet) in octets.iter_mut().enumerate() {
+        let keep = usize::from(bits).saturating_sub(i * 8).min(8);
+        *octet &= !(0xffu16 >> keep) as u8;
+    }
+    octets
+}
+
+#[cfg(all(test, feature = "bouncer"))]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+    #[test]
+    fn parses_and_masks_cidr_ranges() {
+        let network = IpNet::parse("10.1.2.3/8").unwrap();
+        assert_eq!(network, IpNet::parse("10.0.0.0/8").unwrap());
+        assert_eq!(network.to_string(), "10.0.0.0/8");
+        assert_eq!(IpNet::parse(" 203.0.113.7 ").unwrap().prefix(), 32);
+        assert_eq!(IpNet::parse("2001:db8::1").unwrap().prefix(), 128);
+        assert_eq!(
+            IpNet::parse("2001:db8:abcd:12::1/33").unwrap().to_string(),
+            "2001:db8:8000::/33"
+        );
+        assert!(IpNet::parse("10.0.0.0/33").is_none());
+        assert!(IpNet::parse("2001:db8::/129").is_none());
+        assert!(IpNet::parse("example.com").is_none());
+    }
+
+    #[test]
+    fn contains_addresses_within_the_prefix() {
+        let network = IpNet::parse("192.168.4.0/22").unwrap();
+        assert!(network.contains(ip("192.168.4.1")));
+        assert!(network.contains(ip("192.168.7.255")));
+        assert!(!network.contains(ip("192.168.8.0")));
+        assert!(!network.contains(ip("2001:db8::1")));
+
+        let network = IpNet::parse("2001:db8::/32").unwrap();
+        assert!(network.contains(ip("2001:db8:ffff::1")));
+        assert!(!network.contains(ip("2001:db9::1")));
+        assert!(!network.contains(ip("10.0.0.1")));
+    }
+
+    #[test]
+    fn ipv4_mapped_clients_belong_to_ipv4_ranges() {
+        let network = IpNet::parse("10.0.0.0/8").unwrap();
+        assert!(network.contains(ip("::ffff:10.1.2.3")));
+        assert!(
+            IpNet::parse("0.0.0.0/0")
+                .unwrap()
+                .contains(ip("::ffff:1.2.3.4"))
+        );
+    }
+}
@@ -0,0 +1,475 @@
+//! Service-agnostic messages and the backends that deliver them.
+
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde_json::Value;
+
+use crate::notifier::{
+    DiscordEmbed, Mentions, escape_html, escape_markdown_v2, escape_markdown_v2_code,
+};
+
+/// Error returned by a [`NotificationBackend`].
+pub type NotifyError = Box<dyn Error + Send + Sync>;
+
+/// Boxed future returned by [`NotificationBackend::send`].
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = Result<(), NotifyError>> + Send + 'a>>;
+
+// Backends the error notifications go to, on top of the env-configured ones
+static BACKENDS: RwLock<Vec<(BackendId, Arc<dyn NotificationBackend>)>> = RwLock::new(Vec::new());
+
+static NEXT_BACKEND_ID: AtomicU64 = AtomicU64::new(0);
+
+tokio::task_local! {
+    // Backends standing in for the registered ones within `with_backends`
+    static SCOPED: Vec<Arc<dyn NotificationBackend>>;
+}
+
+/// A backend added with [`register_backend`], for removing it with
+/// [`unregister_backend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BackendId(u64);
+
+/// How urgent a [`Message`] is. Services with colors show it as one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Level {
+    #[default]
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+/// A notification, formatted by each backend for its own service.
+///
+/// ```rust, ignore
+/// let message = Message::new("Nightly export failed")
+///     .title("Export — billing")
+///     .level(Level::Error)
+///     .field("Job", "export-42")
+///     .field("Rows", rows.to_string())
+///     .code(error.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct Message {
+    /// A short headline, shown in bold where the service supports it.
+    pub title: Option<String>,
+    /// The message itself, as plain text.
+    pub text: String,
+    /// Named values like an error code or request ID, shown as Slack fields, Discord
+    /// embed fields or a Teams fact set.
+    pub fields: Vec<(String, String)>,
+    /// Preformatted text such as error details, shown in a code block.
+    pub code: Option<String>,
+    /// Small print under the message.
+    pub footer: Option<String>,
+    /// Who to ping on Slack and Discord.
+    pub mentions: Mentions,
+    pub level: Level,
+}
+
+impl Message {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Add a named value. Call again for more.
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    pub fn mentions(mut self, mentions: Mentions) -> Self {
+        self.mentions = mentions;
+        self
+    }
+
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Whether the message is only text, so services get their plain form of it.
+    pub(crate) fn is_plain(&self) -> bool {
+        self.title.is_none()
+            && self.fields.is_empty()
+            && self.code.is_none()
+            && self.footer.is_none()
+            && self.mentions.is_empty()
+    }
+
+    /// Everything but the title, one part per line.
+    pub(crate) fn body(&self) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}"));
+        let parts = std::iter::once(self.text.clone())
+            .chain(fields)
+            .chain(self.code.clone())
+            .chain(self.footer.clone());
+        parts
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The whole message, title first, for services without formatting.
+    pub(crate) fn plain(&self) -> String {
+        let body = self.body();
+        match &self.title {
+            Some(title) if body.is_empty() => title.clone(),
+            Some(title) => format!("{title}\n{body}"),
+            None => body,
+        }
+    }
+
+    pub(crate) fn slack(&self) -> Value {
+        let mut blocks = Vec::new();
+        if let Some(title) = &self.title {
+            blocks.push(slack_section(format!("*{}*", escape_slack(title))));
+        }
+        if !self.text.is_empty() {
+            blocks.push(slack_section(escape_slack(&self.text)));
+        }
+        // Sections hold at most 10 fields
+        for fields in self.fields.chunks(10) {
+            let fields = fields.iter().map(|(name, value)| {
+                let text = format!("*{}*\n{}", escape_slack(name), escape_slack(value));
+                serde_json::json!({ "type": "mrkdwn", "text": text })
+            });
+            blocks.push(
+                serde_json::json!({ "type": "section", "fields": fields.collect::<Vec<_>>() }),
+            );
+        }
+        if let Some(code) = &self.code {
+            blocks.push(slack_section(format!("```{}```", escape_slack(code))));
+        }
+        if let Some(footer) = &self.footer {
+            blocks.push(serde_json::json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": escape_slack(footer) }],
+            }));
+        }
+        // The text is what notifications and screen readers show
+        let mut text = self.plain();
+        let mentions = self.mentions.slack();
+        if !mentions.is_empty() {
+            blocks.push(serde_json::json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": mentions }],
+            }));
+            text = format!("{text}\n{mentions}");
+        }
+        serde_json::json!({ "text": text, "blocks": blocks })
+    }
+
+    /// A webhook payload with the embed, and the mentions as content since Discord only
+    /// pings for those.
+    pub(crate) fn discord(&self) -> Value {
+        let mut payload = serde_json::json!({ "embeds": [Value::from(self.discord_embed())] });
+        let mentions = self.mentions.discord();
+        if !mentions.is_empty() {
+            payload["content"] = mentions.into();
+            payload["allowed_mentions"] = self.mentions.discord_allowed();
+        }
+        payload
+    }
+
+    /// An embed shortened to fit Discord's limits, since a cut-off alert beats a rejected one.
+    fn discord_embed(&self) -> DiscordEmbed {
+        let mut embed = DiscordEmbed::new().color(self.level.color());
+        if let Some(title) = &self.title {
+            embed = embed.title(title);
+        }
+        if !self.text.is_empty() {
+            embed = embed.description(&self.text);
+        }
+        for (name, value) in &self.fields {
+            embed = embed.inline_field(name, value);
+        }
+        if let Some(code) = &self.code {
+            // Leave room for the fences around the truncated code
+            let mut code = code.clone();
+            if let Some((end, _)) = code.char_indices().nth(1024 - 7) {
+                code.truncate(end);
+                code.push('…');
+            }
+            embed = embed.field("Details", format!("```{code}```"));
+        }
+        if let Some(footer) = &self.footer {
+            embed = embed.footer(footer);
+        }
+        embed.fit()
+    }
+
+    /// Adaptive Card `body` elements.
+    pub(crate) fn teams_body(&self) -> Value {
+        let mut body = Vec::new();
+        if let Some(title) = &self.title {
+            let color = match self.level >= Level::Error {
+                true => "Attention",
+                false => "Default",
+            };
+            body.push(serde_json::json!({
+                "type": "TextBlock",
+                "text": title,
+                "size": "Medium",
+                "weight": "Bolder",
+                "color": color,
+                "wrap": true
+            }));
+        }
+        if !self.text.is_empty() {
+            body.push(serde_json::json!({ "type": "TextBlock", "text": self.text, "wrap": true }));
+        }
+        if !self.fields.is_empty() {
+            let facts = self
+                .fields
+                .iter()
+                .map(|(name, value)| serde_json::json!({ "title": name, "value": value }));
+            body.push(serde_json::json!({ "type": "FactSet", "facts": facts.collect::<Vec<_>>() }));
+        }
+        if let Some(code) = &self.code {
+            body.push(serde_json::json!({
+                "type": "TextBlock",
+                "text": code,
+                "fontType": "Monospace",
+                "wrap": true
+            }));
+        }
+        if let Some(footer) = &self.footer {
+            body.push(serde_json::json!({ "type": "TextBlock", "text": footer, "isSubtle": true }));
+        }
+        body.into()
+    }
+
+    pub(crate) fn telegram_markdown(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(title) = &self.title {
+            parts.push(format!("*{}*", escape_markdown_v2(title)));
+        }
+        if !self.text.is_empty() {
+            parts.push(escape_markdown_v2(&self.text));
+        }
+        for (name, value) in &self.fields {
+            parts.push(format!(
+                "*{}:* {}",
+                escape_markdown_v2(name),
+                escape_markdown_v2(value)
+            ));
+        }
+        if let Some(code) = &self.code {
+            parts.push(format!("```\n{}\n```", escape_markdown_v2_code(code)));
+        }
+        if let Some(footer) = &self.footer {
+            parts.push(format!("_{}_", escape_markdown_v2(footer)));
+        }
+        parts.join("\n")
+    }
+
+    pub(crate) fn html(&self) -> String {
+        let mut html = String::new();
+        if let Some(title) = &self.title {
+            html.push_str(&format!("<strong>{}</strong>", escape_html(title)));
+        }
+        if !self.text.is_empty() {
+            html.push_str(&format!("<p>{}</p>", escape_html(&self.text)));
+        }
+        if !self.fields.is_empty() {
+            let fields = self.fields.iter().map(|(name, value)| {
+                format!(
+                    "<strong>{}:</strong> {}",
+                    escape_html(name),
+                    escape_html(value)
+                )
+            });
+            html.push_str(&format!(
+                "<p>{}</p>",
+                fields.collect::<Vec<_>>().join("<br>")
+            ));
+        }
+        if let Some(code) = &self.code {
+            html.push_str(&format!("<pre><code>{}</code></pre>", escape_html(code)));
+        }
+        if let Some(footer) = &self.footer {
+            html.push_str(&format!("<p><em>{}</em></p>", escape_html(footer)));
+        }
+        html
+    }
+}
+
+impl Level {
+    /// Discord embed color.
+    fn color(&self) -> u32 {
+        match self {
+            Level::Info => 0x3498DB,
+            Level::Warning => 0xFFA500,
+            Level::Error | Level::Critical => 0xFF0000,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Warning => "warning",
+            Level::Error => "error",
+            Level::Critical => "critical",
+        }
+    }
+}
+
+/// A service that can deliver a [`Message`].
+///
+/// [`Notifier`](crate::notifier::Notifier) implements it for Slack, Discord and the other
+/// built-in services, sending to every destination it's configured with. Implement it,
+/// or pass an async closure, to send somewhere else:
+///
+/// ```rust, ignore
+/// struct Opsgenie { client: reqwest::Client, key: String }
+///
+/// impl NotificationBackend for Opsgenie {
+///     fn send(&self, message: Message) -> NotifyFuture<'_> {
+///         Box::pin(async move {
+///             self.client
+///                 .post("https://api.opsgenie.com/v2/alerts")
+///                 .header("Authorization", format!("GenieKey {}", self.key))
+///                 .json(&json!({ "message": message.title.unwrap_or(message.text) }))
+///                 .send()
+///                 .await?
+///                 .error_for_status()?;
+///             Ok(())
+///         })
+///     }
+/// }
+///
+/// notifier::register_backend(Opsgenie { client, key });
+/// ```
+pub trait NotificationBackend: Send + Sync + 'static {
+    /// Deliver `message`, formatted for the service.
+    fn send(&self, message: Message) -> NotifyFuture<'_>;
+}
+
+impl<F, Fut> NotificationBackend for F
+where
+    F: Fn(Message) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), NotifyError>> + Send + 'static,
+{
+    fn send(&self, message: Message) -> NotifyFuture<'_> {
+        Box::pin(self(message))
+    }
+}
+
+impl fmt::Debug for dyn NotificationBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NotificationBackend")
+    }
+}
+
+/// Send critical error notifications to `backend` too, alongside the services configured
+/// by `notify-error-*` features, until it's removed with [`unregister_backend`]. Register
+/// backends at startup.
+pub fn register_backend(backend: impl NotificationBackend) -> BackendId {
+    let backend: Arc<dyn NotificationBackend> = Arc::new(backend);
+    let id = BackendId(NEXT_BACKEND_ID.fetch_add(1, Ordering::Relaxed));
+    BACKENDS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .push((id, backend));
+    id
+}
+
+/// Stop sending notifications to a backend added with [`register_backend`], returning
+/// whether it was registered. Notifications already on their way still reach it, and it's
+/// dropped once they're sent.
+pub fn unregister_backend(id: BackendId) -> bool {
+    let mut backends = BACKENDS.write().unwrap_or_else(|e| e.into_inner());
+    let before = backends.len();
+    backends.retain(|(registered, _)| *registered != id);
+    backends.len() < before
+}
+
+/// Backends added with [`register_backend`], or the ones set by [`with_backends`] inside
+/// it.
+pub(crate) fn registered_backends() -> Vec<Arc<dyn NotificationBackend>> {
+    if let Ok(scoped) = SCOPED.try_with(Clone::clone) {
+        return scoped;
+    }
+    BACKENDS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(_, backend)| backend.clone())
+        .collect()
+}
+
+fn slack_section(text: String) -> Value {
+    serde_json::json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } })
+}
+
+// Slack reads &, < and > as control characters in message text
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Run `future` with notifications it sends going to `backends` instead of the registered
+/// ones. Tasks it spawns aren't covered.
+pub(crate) async fn with_backends<F: Future>(
+    backends: Vec<Arc<dyn NotificationBackend>>,
+    future: F,
+) -> F::Output {
+    SCOPED.scope(backends, future).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unregistering_drops_the_backend() {
+        let alive = Arc::new(());
+        let held = alive.clone();
+        let id = register_backend(move |_: Message| {
+            let _ = &held;
+            async { Ok(()) }
+        });
+        let in_flight = registered_backends();
+        assert_eq!(Arc::strong_count(&alive), 2);
+
+        assert!(unregister_backend(id));
+        assert!(!unregister_backend(id));
+        // Sends already under way keep it until they finish
+        assert_eq!(Arc::strong_count(&alive), 2);
+        drop(in_flight);
+        assert_eq!(Arc::strong_count(&alive), 1);
+    }
+}
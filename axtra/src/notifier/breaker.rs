@@ -0,0 +1,141 @@
+//! Per-destination circuit breaking for notifications.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// When a [`Notifier`](crate::notifier::Notifier) stops posting to a failing destination.
+///
+/// After `failures` sends in a row fail, retries included, the destination's circuit opens:
+/// for `cooldown`, its notifications are logged with `tracing` instead of being posted, so a
+/// dead webhook doesn't cost every error a failed round trip. The first notification after
+/// the cooldown is sent as a probe; if it goes through the circuit closes, otherwise it opens
+/// for another cooldown.
+///
+/// ```rust, ignore
+/// let notifier = Notifier::with_slack(url)
+///     .circuit_breaker(CircuitBreaker::new(3).cooldown(Duration::from_secs(300)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    pub failures: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreaker {
+    /// Open after 5 failures in a row, for a minute.
+    fn default() -> Self {
+        Self {
+            failures: 5,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Open after `failures` failures in a row, with the default cooldown.
+    pub fn new(failures: u32) -> Self {
+        Self {
+            failures: failures.max(1),
+            ..Self::default()
+        }
+    }
+
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+}
+
+// A destination's failures in a row, and until when it's skipped
+#[derive(Default)]
+struct Circuit {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// A notifier's destinations and their circuits.
+#[derive(Default)]
+pub(crate) struct Circuits {
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl Circuits {
+    /// Whether to send to `destination`. Once an open circuit's cooldown is over, one send
+    /// is let through as a probe, and the rest wait on its result for another cooldown.
+    pub(crate) fn allow(&self, destination: &str, breaker: &CircuitBreaker) -> bool {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(circuit) = circuits.get_mut(destination) else {
+            return true;
+        };
+        let now = Instant::now();
+        match circuit.open_until {
+            Some(until) if now < until => false,
+            Some(_) => {
+                circuit.open_until = Some(now + breaker.cooldown);
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Record how a send to `destination` went, opening or closing its circuit.
+    pub(crate) fn record(&self, destination: &str, breaker: &CircuitBreaker, ok: bool) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        if ok {
+            if circuits
+                .remove(destination)
+                .is_some_and(|circuit| circuit.open_until.is_some())
+            {
+                tracing::info!("Notification destination recovered, sending to it again");
+            }
+            return;
+        }
+
+        let circuit = circuits.entry(destination.to_string()).or_default();
+        circuit.failures += 1;
+        let probe_failed = circuit.open_until.is_some();
+        if probe_failed || circuit.failures >= breaker.failures {
+            circuit.open_until = Some(Instant::now() + breaker.cooldown);
+            // The destination is a webhook URL, which is a secret
+            tracing::warn!(
+                failures = circuit.failures,
+                cooldown_secs = breaker.cooldown.as_secs(),
+                "Notification destination keeps failing, logging its notifications instead"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_failures_in_a_row_and_closes_on_success() {
+        let breaker = CircuitBreaker::new(2).cooldown(Duration::from_secs(60));
+        let circuits = Circuits::default();
+        circuits.record("slack", &breaker, false);
+        assert!(circuits.allow("slack", &breaker));
+        circuits.record("slack", &breaker, false);
+        assert!(!circuits.allow("slack", &breaker));
+        assert!(circuits.allow("discord", &breaker));
+
+        circuits.record("slack", &breaker, true);
+        assert!(circuits.allow("slack", &breaker));
+    }
+
+    #[test]
+    fn lets_one_probe_through_after_the_cooldown() {
+        let breaker = CircuitBreaker::new(1).cooldown(Duration::ZERO);
+        let circuits = Circuits::default();
+        circuits.record("slack", &breaker, false);
+        assert!(circuits.allow("slack", &breaker));
+        // The failed probe opens the circuit again
+        circuits.record("slack", &breaker, false);
+        let open_until = circuits.circuits.lock().unwrap()["slack"].open_until;
+        assert!(open_until.is_some());
+    }
+}
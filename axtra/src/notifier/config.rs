@@ -0,0 +1,107 @@
+//! Notification settings for the whole app, set once at startup.
+
+use std::{sync::OnceLock, time::Duration};
+
+use crate::notifier::{ErrorTemplate, Level, Mentions, set_error_template};
+
+static CONFIG: OnceLock<NotifierConfig> = OnceLock::new();
+
+/// Settings for error notifications and the app's name in every notification, from the
+/// application's own config instead of environment variables.
+///
+/// Unset fields fall back to the environment: `SLACK_ERROR_WEBHOOK_URL`,
+/// `DISCORD_ERROR_WEBHOOK_URL`, `APP_NAME` and `APP_ENV`.
+///
+/// ```rust, ignore
+/// notifier::init(NotifierConfig {
+///     slack: Some(settings.alerts.slack_webhook.clone()),
+///     mentions: Mentions::new().slack_group("S0614TZR7").critical_only(),
+///     app_name: Some("billing".into()),
+///     environment: Some(settings.env.clone()),
+///     digest: Some(Duration::from_secs(300)),
+///     timeout: Some(Duration::from_secs(10)),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct NotifierConfig {
+    /// Slack webhook URL for error notifications (`notify-error-slack`).
+    pub slack: Option<String>,
+    /// Discord webhook URL for error notifications (`notify-error-discord`).
+    pub discord: Option<String>,
+    /// Who error alerts ping on Slack and Discord; see [`Mentions`].
+    pub mentions: Mentions,
+    /// The app's name in notifications, instead of `APP_NAME`.
+    pub app_name: Option<String>,
+    /// The deployment, like `production`, instead of `APP_ENV`.
+    pub environment: Option<String>,
+    /// Batch non-critical error alerts to Slack, Discord and registered backends into one
+    /// summary per interval, like `Duration::from_secs(300)`. Critical errors are still
+    /// sent straight away.
+    pub digest: Option<Duration>,
+    /// How long error notifiers wait for each webhook response; 10 seconds if unset.
+    pub timeout: Option<Duration>,
+    /// The HTTP client error notifiers post with, for a proxy or connection pool
+    /// settings. Its own timeout applies instead of `timeout`.
+    pub client: Option<reqwest::Client>,
+    /// The format of error alerts; see [`ErrorTemplate`].
+    pub template: Option<ErrorTemplate>,
+}
+
+// Webhook URLs are secrets, so only whether they're set is shown
+impl std::fmt::Debug for NotifierConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotifierConfig")
+            .field("slack", &self.slack.is_some())
+            .field("discord", &self.discord.is_some())
+            .field("mentions", &self.mentions)
+            .field("app_name", &self.app_name)
+            .field("environment", &self.environment)
+            .field("digest", &self.digest)
+            .field("timeout", &self.timeout)
+            .field("client", &self.client.is_some())
+            .field("template", &self.template)
+            .finish()
+    }
+}
+
+/// Configure notifications for the whole app. Call it once, at startup, before any
+/// error can be reported; error notifiers are set up on first use.
+///
+/// Panics if called twice.
+pub fn init(config: NotifierConfig) {
+    if let Some(template) = config.template.clone() {
+        set_error_template(template);
+    }
+    if CONFIG.set(config).is_err() {
+        panic!("notifier::init was already called");
+    }
+}
+
+/// The config passed to [`init`], if it was called.
+pub fn config() -> Option<&'static NotifierConfig> {
+    CONFIG.get()
+}
+
+/// Who an error alert at `level` pings, from [`init`].
+pub(crate) fn error_mentions(level: Level) -> Mentions {
+    config()
+        .map(|config| config.mentions.for_level(level))
+        .unwrap_or_default()
+}
+
+/// The app's name in notifications: from [`init`], or `APP_NAME`, or `Rust`.
+pub fn app_name() -> String {
+    config()
+        .and_then(|config| config.app_name.clone())
+        .or_else(|| std::env::var("APP_NAME").ok())
+        .unwrap_or_else(|| "Rust".to_string())
+}
+
+/// The deployment in notifications: from [`init`], or `APP_ENV`, or empty.
+pub fn environment() -> String {
+    config()
+        .and_then(|config| config.environment.clone())
+        .or_else(|| std::env::var("APP_ENV").ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,140 @@
+//! Batching of non-critical error alerts into periodic summaries.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::notifier::{
+    Level, Message, NotificationBackend, app_name, discord::truncate, environment, error_mentions,
+    skipped,
+};
+
+// Locations listed in a summary; the rest are counted
+const TOP_LOCATIONS: usize = 10;
+// Characters of each location's first error shown
+const SAMPLE_MAX: usize = 200;
+
+static DIGEST: Mutex<Digest> = Mutex::new(Digest {
+    errors: BTreeMap::new(),
+    backends: Vec::new(),
+    interval: None,
+});
+
+// Errors since the last summary, by location and code, and where the summary goes
+struct Digest {
+    errors: BTreeMap<(String, String), Batched>,
+    backends: Vec<Arc<dyn NotificationBackend>>,
+    // Set once the flush task is running
+    interval: Option<Duration>,
+}
+
+struct Batched {
+    count: u32,
+    sample: String,
+}
+
+/// Hold an error for the next summary to `backends`, sent every `interval`.
+pub(crate) fn add_to_digest(
+    interval: Duration,
+    backends: Vec<Arc<dyn NotificationBackend>>,
+    code: String,
+    location: &str,
+    message: String,
+) {
+    let mut digest = DIGEST.lock().unwrap_or_else(|e| e.into_inner());
+    digest
+        .errors
+        .entry((location.to_string(), code))
+        .and_modify(|batched| batched.count += 1)
+        .or_insert(Batched {
+            count: 1,
+            sample: message,
+        });
+    digest.backends = backends;
+
+    if digest.interval.is_none() {
+        digest.interval = Some(interval);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                flush_digest().await;
+            }
+        });
+    }
+}
+
+/// Send the errors batched since the last summary now, rather than at the end of the
+/// interval. Call it during graceful shutdown so they aren't lost.
+pub async fn flush_digest() {
+    let (errors, backends, interval) = {
+        let mut digest = DIGEST.lock().unwrap_or_else(|e| e.into_inner());
+        (
+            std::mem::take(&mut digest.errors),
+            digest.backends.clone(),
+            digest.interval,
+        )
+    };
+    let Some(interval) = interval.filter(|_| !errors.is_empty()) else {
+        return;
+    };
+
+    let message = summary(errors, interval);
+    for backend in backends {
+        match backend.send(message.clone()).await {
+            Err(e) if skipped(&*e) => tracing::debug!(error = %e, "Error digest not sent"),
+            Err(e) => tracing::warn!(error = %e, "Failed to send error digest"),
+            Ok(()) => {}
+        }
+    }
+}
+
+// One message counting the errors, most frequent locations first
+fn summary(errors: BTreeMap<(String, String), Batched>, interval: Duration) -> Message {
+    let total: u32 = errors.values().map(|batched| batched.count).sum();
+    let mut errors: Vec<_> = errors.into_iter().collect();
+    errors.sort_by_key(|(_, batched)| std::cmp::Reverse(batched.count));
+
+    let app_name = app_name();
+    let environment = environment();
+    let noun = match total {
+        1 => "error",
+        _ => "errors",
+    };
+    let title = match environment.is_empty() {
+        true => format!("\u{1F9FE} {total} {noun} in {app_name}"),
+        false => format!("\u{1F9FE} {total} {noun} in {app_name} ({environment})"),
+    };
+    let text = format!("In the last {}", describe(interval));
+
+    let mut message = Message::new(text).title(title).level(Level::Error);
+    let others = errors.len().saturating_sub(TOP_LOCATIONS);
+    for ((location, code), mut batched) in errors.into_iter().take(TOP_LOCATIONS) {
+        truncate(&mut batched.sample, SAMPLE_MAX);
+        message = message.field(
+            location,
+            format!("{}× {code}: {}", batched.count, batched.sample),
+        );
+    }
+    match others {
+        0 => {}
+        1 => message = message.footer("…and 1 more location"),
+        n => message = message.footer(format!("…and {n} more locations")),
+    }
+    message.mentions(error_mentions(Level::Error))
+}
+
+// `5 minutes`, `1 hour`, `30 seconds`
+fn describe(interval: Duration) -> String {
+    let secs = interval.as_secs();
+    let (count, unit) = match secs {
+        s if s >= 3600 && s % 3600 == 0 => (s / 3600, "hour"),
+        s if s >= 60 && s % 60 == 0 => (s / 60, "minute"),
+        s => (s, "second"),
+    };
+    match count {
+        1 => format!("1 {unit}"),
+        n => format!("{n} {unit}s"),
+    }
+}
@@ -0,0 +1,355 @@
+//! Discord embeds, built and checked against Discord's limits.
+
+use serde_json::Value;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+// Discord's limits, in characters: https://discord.com/developers/docs/resources/message#embed-object-embed-limits
+const TITLE_MAX: usize = 256;
+const DESCRIPTION_MAX: usize = 4096;
+const FIELDS_MAX: usize = 25;
+const FIELD_NAME_MAX: usize = 256;
+const FIELD_VALUE_MAX: usize = 1024;
+const FOOTER_MAX: usize = 2048;
+const AUTHOR_MAX: usize = 256;
+// Across every embed in a message
+const TOTAL_MAX: usize = 6000;
+const EMBEDS_MAX: usize = 10;
+const COLOR_MAX: u32 = 0xFFFFFF;
+
+/// Why Discord would reject an embed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EmbedError {
+    #[error("Discord embed {part} is {len} characters, over the limit of {max}")]
+    TooLong {
+        part: &'static str,
+        len: usize,
+        max: usize,
+    },
+    #[error("Discord embeds in a message can total at most {TOTAL_MAX} characters, got {0}")]
+    TotalTooLong(usize),
+    #[error("Discord embeds can have at most {FIELDS_MAX} fields, got {0}")]
+    TooManyFields(usize),
+    #[error("Discord messages can have at most {EMBEDS_MAX} embeds, got {0}")]
+    TooManyEmbeds(usize),
+    #[error("Discord embed fields need a name and a value")]
+    EmptyField,
+    #[error("Discord embed color {0:#x} isn't an RGB color")]
+    Color(u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    name: String,
+    value: String,
+    inline: bool,
+}
+
+/// A Discord embed. Check it with [`validate`](Self::validate), or let
+/// [`Notifier::notify_discord_rich`](crate::notifier::Notifier::notify_discord_rich) check
+/// it before sending.
+///
+/// ```rust, ignore
+/// let embed = DiscordEmbed::new()
+///     .title("Deploy finished")
+///     .description(format!("{version} is live"))
+///     .color(0x2ECC71)
+///     .inline_field("Region", "eu-west-1")
+///     .inline_field("Duration", "4m 12s")
+///     .footer("deploy-bot")
+///     .timestamp(OffsetDateTime::now_utc());
+/// discord.notify_discord_rich(embed).await?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiscordEmbed {
+    title: Option<String>,
+    description: Option<String>,
+    color: Option<u32>,
+    fields: Vec<Field>,
+    footer: Option<String>,
+    timestamp: Option<OffsetDateTime>,
+}
+
+impl DiscordEmbed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Up to 256 characters.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Up to 4096 characters of Discord markdown.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// The bar down the left side, as `0xRRGGBB`.
+    pub fn color(mut self, color: u32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Add a field on its own line: a name of up to 256 characters and a value of up to
+    /// 1024. Embeds hold up to 25 fields.
+    pub fn field(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.push_field(name.into(), value.into(), false)
+    }
+
+    /// Add a field that sits beside other inline fields.
+    pub fn inline_field(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.push_field(name.into(), value.into(), true)
+    }
+
+    fn push_field(mut self, name: String, value: String, inline: bool) -> Self {
+        self.fields.push(Field {
+            name,
+            value,
+            inline,
+        });
+        self
+    }
+
+    /// Up to 2048 characters.
+    pub fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    /// The time shown by the footer, in the reader's timezone.
+    pub fn timestamp(mut self, timestamp: OffsetDateTime) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Whether Discord would accept the embed, or why not.
+    pub fn validate(&self) -> Result<(), EmbedError> {
+        validate(&Value::Array(vec![self.to_value()]))
+    }
+
+    /// Shorten whatever is over Discord's limits, marking the cut with `…`, so the embed
+    /// is sent rather than rejected.
+    pub(crate) fn fit(mut self) -> Self {
+        if let Some(title) = &mut self.title {
+            truncate(title, TITLE_MAX);
+        }
+        if let Some(description) = &mut self.description {
+            truncate(description, DESCRIPTION_MAX);
+        }
+        self.fields.truncate(FIELDS_MAX);
+        for field in &mut self.fields {
+            truncate(&mut field.name, FIELD_NAME_MAX);
+            truncate(&mut field.value, FIELD_VALUE_MAX);
+        }
+        if let Some(footer) = &mut self.footer {
+            truncate(footer, FOOTER_MAX);
+        }
+
+        // Take anything over the total out of the description, then the field values
+        let mut excess = self.len().saturating_sub(TOTAL_MAX);
+        let parts = self
+            .description
+            .iter_mut()
+            .chain(self.fields.iter_mut().map(|field| &mut field.value));
+        for part in parts {
+            if excess == 0 {
+                break;
+            }
+            let len = part.chars().count();
+            let keep = len.saturating_sub(excess).max(1);
+            truncate(part, keep);
+            excess -= len - part.chars().count();
+        }
+        self
+    }
+
+    // Characters counted toward the total limit
+    fn len(&self) -> usize {
+        let parts = self
+            .title
+            .iter()
+            .chain(&self.description)
+            .chain(
+                self.fields
+                    .iter()
+                    .flat_map(|field| [&field.name, &field.value]),
+            )
+            .chain(&self.footer);
+        parts.map(|part| part.chars().count()).sum()
+    }
+
+    fn to_value(&self) -> Value {
+        let mut embed = serde_json::Map::new();
+        if let Some(title) = &self.title {
+            embed.insert("title".into(), title.as_str().into());
+        }
+        if let Some(description) = &self.description {
+            embed.insert("description".into(), description.as_str().into());
+        }
+        if let Some(color) = self.color {
+            embed.insert("color".into(), color.into());
+        }
+        if !self.fields.is_empty() {
+            let fields = self.fields.iter().map(|field| {
+                serde_json::json!({ "name": field.name, "value": field.value, "inline": field.inline })
+            });
+            embed.insert("fields".into(), fields.collect());
+        }
+        if let Some(footer) = &self.footer {
+            embed.insert("footer".into(), serde_json::json!({ "text": footer }));
+        }
+        if let Some(timestamp) = self.timestamp.and_then(|t| t.format(&Rfc3339).ok()) {
+            embed.insert("timestamp".into(), timestamp.into());
+        }
+        embed.into()
+    }
+}
+
+impl From<DiscordEmbed> for Value {
+    fn from(embed: DiscordEmbed) -> Self {
+        embed.to_value()
+    }
+}
+
+/// Check a message's embeds, built or raw JSON, against Discord's limits.
+pub(crate) fn validate(embeds: &Value) -> Result<(), EmbedError> {
+    let embeds = embeds.as_array().map(Vec::as_slice).unwrap_or_default();
+    if embeds.len() > EMBEDS_MAX {
+        return Err(EmbedError::TooManyEmbeds(embeds.len()));
+    }
+
+    let mut total = 0;
+    for embed in embeds {
+        let mut check = |part: &'static str, value: &Value, max: usize| {
+            let len = value.as_str().map_or(0, |text| text.chars().count());
+            total += len;
+            match len > max {
+                true => Err(EmbedError::TooLong { part, len, max }),
+                false => Ok(()),
+            }
+        };
+        check("title", &embed["title"], TITLE_MAX)?;
+        check("description", &embed["description"], DESCRIPTION_MAX)?;
+        check("footer", &embed["footer"]["text"], FOOTER_MAX)?;
+        check("author name", &embed["author"]["name"], AUTHOR_MAX)?;
+
+        let fields = embed["fields"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if fields.len() > FIELDS_MAX {
+            return Err(EmbedError::TooManyFields(fields.len()));
+        }
+        for field in fields {
+            let empty = |value: &Value| value.as_str().is_none_or(|text| text.trim().is_empty());
+            if empty(&field["name"]) || empty(&field["value"]) {
+                return Err(EmbedError::EmptyField);
+            }
+            check("field name", &field["name"], FIELD_NAME_MAX)?;
+            check("field value", &field["value"], FIELD_VALUE_MAX)?;
+        }
+
+        if let Some(color) = embed["color"].as_u64()
+            && color > u64::from(COLOR_MAX)
+        {
+            return Err(EmbedError::Color(color));
+        }
+    }
+    if total > TOTAL_MAX {
+        return Err(EmbedError::TotalTooLong(total));
+    }
+    Ok(())
+}
+
+// Cut `text` to at most `max` characters, the last being `…`
+pub(crate) fn truncate(text: &mut String, max: usize) {
+    if let Some((end, _)) = text.char_indices().nth(max) {
+        let end = text[..end].char_indices().next_back().map_or(0, |(i, _)| i);
+        text.truncate(end);
+        text.push('…');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    #[test]
+    fn accepts_embeds_within_the_limits() {
+        let embed = DiscordEmbed::new()
+            .title("t".repeat(TITLE_MAX))
+            .description("Deploy finished")
+            .color(COLOR_MAX)
+            .inline_field("Region", "eu-west-1")
+            .footer("deploy-bot");
+        assert_eq!(embed.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_parts_over_their_limits() {
+        assert_eq!(
+            DiscordEmbed::new()
+                .title("é".repeat(TITLE_MAX + 1))
+                .validate(),
+            Err(EmbedError::TooLong {
+                part: "title",
+                len: TITLE_MAX + 1,
+                max: TITLE_MAX
+            })
+        );
+        assert!(matches!(
+            DiscordEmbed::new()
+                .field("Trace", "x".repeat(FIELD_VALUE_MAX + 1))
+                .validate(),
+            Err(EmbedError::TooLong {
+                part: "field value",
+                ..
+            })
+        ));
+        let fields = (0..=FIELDS_MAX).fold(DiscordEmbed::new(), |embed, i| {
+            embed.field(i.to_string(), "v")
+        });
+        assert_eq!(
+            fields.validate(),
+            Err(EmbedError::TooManyFields(FIELDS_MAX + 1))
+        );
+        assert_eq!(
+            DiscordEmbed::new().field("Name", " ").validate(),
+            Err(EmbedError::EmptyField)
+        );
+        assert_eq!(
+            DiscordEmbed::new().color(COLOR_MAX + 1).validate(),
+            Err(EmbedError::Color(u64::from(COLOR_MAX) + 1))
+        );
+    }
+
+    #[test]
+    fn rejects_messages_over_the_totals() {
+        let description = "d".repeat(DESCRIPTION_MAX);
+        let embeds = json!([{ "description": description }, { "description": description }]);
+        assert_eq!(
+            validate(&embeds),
+            Err(EmbedError::TotalTooLong(DESCRIPTION_MAX * 2))
+        );
+        let embeds = Value::Array(vec![json!({}); EMBEDS_MAX + 1]);
+        assert_eq!(
+            validate(&embeds),
+            Err(EmbedError::TooManyEmbeds(EMBEDS_MAX + 1))
+        );
+    }
+
+    #[test]
+    fn fit_shortens_embeds_until_they_validate() {
+        let embed = DiscordEmbed::new()
+            .title("t".repeat(TITLE_MAX * 2))
+            .description("d".repeat(DESCRIPTION_MAX))
+            .field("Trace", "v".repeat(FIELD_VALUE_MAX * 3))
+            .field("Query", "q".repeat(FIELD_VALUE_MAX))
+            .fit();
+        assert_eq!(embed.validate(), Ok(()));
+        assert!(embed.title.as_deref().unwrap().ends_with('…'));
+    }
+}
@@ -0,0 +1,174 @@
+//! Operational notifications from application code, through the [`notify!`](crate::notify)
+//! macro.
+
+use std::sync::Arc;
+
+#[cfg(feature = "notify-error-discord")]
+use crate::errors::notifiers::discord_notifier;
+#[cfg(feature = "notify-error-email")]
+use crate::errors::notifiers::email_notifier;
+#[cfg(feature = "notify-error-matrix")]
+use crate::errors::notifiers::matrix_notifier;
+#[cfg(feature = "notify-error-ntfy")]
+use crate::errors::notifiers::ntfy_notifier;
+#[cfg(feature = "notify-error-pushover")]
+use crate::errors::notifiers::pushover_notifier;
+#[cfg(any(
+    feature = "notify-error-slack",
+    feature = "notify-error-discord",
+    feature = "notify-error-teams",
+    feature = "notify-error-telegram",
+    feature = "notify-error-matrix",
+    feature = "notify-error-ntfy",
+    feature = "notify-error-pushover",
+    feature = "notify-error-email"
+))]
+use crate::errors::notifiers::shared;
+#[cfg(feature = "notify-error-slack")]
+use crate::errors::notifiers::slack_notifier;
+#[cfg(feature = "notify-error-teams")]
+use crate::errors::notifiers::teams_notifier;
+#[cfg(feature = "notify-error-telegram")]
+use crate::errors::notifiers::telegram_notifier;
+use crate::notifier::{
+    Level, Message, NotificationBackend, app_name, environment, registered_backends, skipped,
+};
+
+/// Format a message and send it in the background to the notifiers error alerts use.
+///
+/// Takes a destination, a level and `format!` arguments; the app name, environment and
+/// the calling module and line are added as fields. The destination is `slack`,
+/// `discord`, `teams`, `telegram`, `matrix`, `ntfy`, `pushover`, `email`, `backends`
+/// (registered with [`register_backend`](crate::notifier::register_backend)), or `all`,
+/// which is also what leaving it out means. The level is `info`, `warn`, `error` or
+/// `critical`.
+///
+/// Destinations are set up like error notifications, through [`init`](crate::notifier::init)
+/// or `SLACK_ERROR_WEBHOOK_URL` and friends; unconfigured ones are skipped. Must be called
+/// within a Tokio runtime.
+///
+/// ```rust, ignore
+/// notify!(slack, warn, "deploy {} finished", version);
+/// notify!(info, "nightly export wrote {rows} rows");
+/// ```
+#[macro_export]
+macro_rules! notify {
+    ($destination:ident, $level:ident, $fmt:literal $(, $args:expr)* $(,)?) => {
+        $crate::notifier::dispatch(
+            $crate::notify!(@destination $destination),
+            $crate::notify!(@level $level),
+            format!($fmt $(, $args)*),
+            $crate::error_location!(),
+        )
+    };
+    ($level:ident, $fmt:literal $(, $args:expr)* $(,)?) => {
+        $crate::notify!(all, $level, $fmt $(, $args)*)
+    };
+
+    (@destination all) => { $crate::notifier::Destination::All };
+    (@destination slack) => { $crate::notifier::Destination::Slack };
+    (@destination discord) => { $crate::notifier::Destination::Discord };
+    (@destination teams) => { $crate::notifier::Destination::Teams };
+    (@destination telegram) => { $crate::notifier::Destination::Telegram };
+    (@destination matrix) => { $crate::notifier::Destination::Matrix };
+    (@destination ntfy) => { $crate::notifier::Destination::Ntfy };
+    (@destination pushover) => { $crate::notifier::Destination::Pushover };
+    (@destination email) => { $crate::notifier::Destination::Email };
+    (@destination backends) => { $crate::notifier::Destination::Backends };
+
+    (@level info) => { $crate::notifier::Level::Info };
+    (@level warn) => { $crate::notifier::Level::Warning };
+    (@level error) => { $crate::notifier::Level::Error };
+    (@level critical) => { $crate::notifier::Level::Critical };
+}
+
+/// Where a [`notify!`](crate::notify) message goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
+    /// Every configured notifier and registered backend.
+    All,
+    Slack,
+    Discord,
+    Teams,
+    Telegram,
+    Matrix,
+    Ntfy,
+    Pushover,
+    Email,
+    /// Backends added with [`register_backend`](crate::notifier::register_backend).
+    Backends,
+}
+
+impl Destination {
+    // Whether messages to this destination go to `other`
+    fn includes(self, other: Destination) -> bool {
+        self == Destination::All || self == other
+    }
+}
+
+/// Send `text` to `destination` in the background, with the app, environment and
+/// `location`. Used by [`notify!`](crate::notify).
+#[doc(hidden)]
+pub fn dispatch(destination: Destination, level: Level, text: String, location: String) {
+    let mut backends: Vec<Arc<dyn NotificationBackend>> = Vec::new();
+    #[cfg(feature = "notify-error-slack")]
+    if destination.includes(Destination::Slack) {
+        backends.extend(slack_notifier().map(shared));
+    }
+    #[cfg(feature = "notify-error-discord")]
+    if destination.includes(Destination::Discord) {
+        backends.extend(discord_notifier().map(shared));
+    }
+    #[cfg(feature = "notify-error-teams")]
+    if destination.includes(Destination::Teams) {
+        backends.extend(teams_notifier().map(shared));
+    }
+    #[cfg(feature = "notify-error-telegram")]
+    if destination.includes(Destination::Telegram) {
+        backends.extend(telegram_notifier().map(shared));
+    }
+    #[cfg(feature = "notify-error-matrix")]
+    if destination.includes(Destination::Matrix) {
+        backends.extend(matrix_notifier().map(shared));
+    }
+    #[cfg(feature = "notify-error-ntfy")]
+    if destination.includes(Destination::Ntfy) {
+        backends.extend(ntfy_notifier().map(shared));
+    }
+    #[cfg(feature = "notify-error-pushover")]
+    if destination.includes(Destination::Pushover) {
+        backends.extend(pushover_notifier().map(shared));
+    }
+    #[cfg(feature = "notify-error-email")]
+    if destination.includes(Destination::Email) {
+        backends.extend(email_notifier().map(shared));
+    }
+    if destination.includes(Destination::Backends) {
+        backends.extend(registered_backends());
+    }
+    if backends.is_empty() {
+        tracing::debug!(
+            ?destination,
+            "No notifier configured, dropping notification"
+        );
+        return;
+    }
+
+    let environment = environment();
+    let mut message = Message::new(text).level(level).field("App", app_name());
+    if !environment.is_empty() {
+        message = message.field("Environment", environment);
+    }
+    let message = message.field("Location", location);
+
+    for backend in backends {
+        let message = message.clone();
+        tokio::spawn(async move {
+            match backend.send(message).await {
+                Err(e) if skipped(&*e) => tracing::debug!(error = %e, "Notification not sent"),
+                Err(e) => tracing::warn!(error = %e, "Failed to send notification"),
+                Ok(()) => {}
+            }
+        });
+    }
+}
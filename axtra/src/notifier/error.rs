@@ -0,0 +1,147 @@
+//! Why a notifier didn't deliver a notification.
+
+use std::error::Error;
+
+use crate::notifier::NotifyError;
+
+/// Why a [`Notifier`](crate::notifier::Notifier) didn't deliver a notification.
+///
+/// Returned boxed from the `notify_*` methods; downcast to tell a message held back on
+/// purpose from a failed post.
+///
+/// ```rust, ignore
+/// if let Err(e) = slack.notify_slack("Deploy finished").await {
+///     match e.downcast_ref::<NotifierError>() {
+///         Some(NotifierError::Suppressed) => {} // counted in the next digest
+///         _ => tracing::warn!(error = %e, "Deploy notification failed"),
+///     }
+/// }
+/// ```
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum NotifierError {
+    /// The destination's [circuit breaker](crate::notifier::CircuitBreaker) is open, so
+    /// the notification was logged instead of posted.
+    #[error("notification destination is failing, circuit open")]
+    CircuitOpen,
+    /// Dropped by the rate limit or as a duplicate, and counted in the next suppressed
+    /// digest.
+    #[error("notification suppressed (rate limit or duplicate)")]
+    Suppressed,
+    /// The post failed, after any retries.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// Some of the destinations a message went to didn't get it, each named with its
+    /// error. The others did.
+    #[error(
+        "{} of {attempted} notification destinations failed: {}",
+        .failures.len(),
+        describe(.failures)
+    )]
+    Destinations {
+        attempted: usize,
+        failures: Vec<(&'static str, NotifyError)>,
+    },
+}
+
+impl NotifierError {
+    /// Whether the notification was held back on purpose rather than failing.
+    pub fn is_skipped(&self) -> bool {
+        match self {
+            Self::CircuitOpen | Self::Suppressed => true,
+            Self::Http(_) => false,
+            Self::Destinations { failures, .. } => {
+                failures.iter().all(|(_, error)| skipped(&**error))
+            }
+        }
+    }
+}
+
+/// The result of sending to each named destination: the only error if there was one
+/// destination, every failure if there were more.
+pub(crate) fn aggregate(
+    outcomes: Vec<(&'static str, Result<(), NotifyError>)>,
+) -> Result<(), NotifyError> {
+    if outcomes.is_empty() {
+        return Err("No webhook configured".into());
+    }
+    let attempted = outcomes.len();
+    let mut failures: Vec<_> = outcomes
+        .into_iter()
+        .filter_map(|(name, result)| result.err().map(|error| (name, error)))
+        .collect();
+    match failures.len() {
+        0 => Ok(()),
+        1 if attempted == 1 => Err(failures.remove(0).1),
+        _ => Err(NotifierError::Destinations {
+            attempted,
+            failures,
+        }
+        .into()),
+    }
+}
+
+// `Slack: <error>; email: <error>`
+fn describe(failures: &[(&'static str, NotifyError)]) -> String {
+    failures
+        .iter()
+        .map(|(name, error)| format!("{name}: {error}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Whether `error` is a [`NotifierError`] for a notification held back on purpose, so
+/// callers log it quietly.
+pub(crate) fn skipped(error: &(dyn Error + 'static)) -> bool {
+    error
+        .downcast_ref::<NotifierError>()
+        .is_some_and(NotifierError::is_skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_every_failed_destination() {
+        let error = aggregate(vec![
+            ("Slack", Err("webhook deleted".into())),
+            ("Discord", Ok(())),
+            ("email", Err("connection refused".into())),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "2 of 3 notification destinations failed: Slack: webhook deleted; email: connection refused"
+        );
+        assert!(!skipped(&*error));
+    }
+
+    #[test]
+    fn returns_a_single_destination_error_as_is() {
+        let error = aggregate(vec![("Slack", Err(NotifierError::Suppressed.into()))]).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<NotifierError>(),
+            Some(NotifierError::Suppressed)
+        ));
+        assert!(aggregate(vec![("Slack", Ok(())), ("Discord", Ok(()))]).is_ok());
+        assert!(aggregate(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn skipped_only_when_every_failure_was_held_back() {
+        let held_back = aggregate(vec![
+            ("Slack", Err(NotifierError::Suppressed.into())),
+            ("Discord", Err(NotifierError::CircuitOpen.into())),
+        ])
+        .unwrap_err();
+        assert!(skipped(&*held_back));
+
+        let mixed = aggregate(vec![
+            ("Slack", Err(NotifierError::Suppressed.into())),
+            ("Discord", Err("bad request".into())),
+        ])
+        .unwrap_err();
+        assert!(!skipped(&*mixed));
+    }
+}
@@ -0,0 +1,62 @@
+//! The HTTP client notifications are posted with.
+
+use std::{sync::OnceLock, time::Duration};
+
+use reqwest::{Client, Proxy};
+
+/// How long a notification waits for a response unless told otherwise, so a hanging
+/// endpoint can't hold a notification task forever.
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Shared by notifiers with default options, so they share a connection pool too
+static DEFAULT_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// A client with the default options.
+pub(crate) fn default_client() -> Client {
+    DEFAULT_CLIENT
+        .get_or_init(|| ClientOptions::default().build())
+        .clone()
+}
+
+/// Settings a notifier's client is built from.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientOptions {
+    pub(crate) timeout: Duration,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) proxy: Option<Proxy>,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+            proxy: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+        }
+    }
+}
+
+impl ClientOptions {
+    pub(crate) fn build(&self) -> Client {
+        let mut builder = Client::builder().timeout(self.timeout);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy.clone() {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        builder
+            .build()
+            .expect("Failed to build the notifier's HTTP client")
+    }
+}
@@ -0,0 +1,97 @@
+//! Matrix room messages through the client-server API.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{Client, RequestBuilder, Url};
+use serde_json::Value;
+
+// Makes transaction IDs unique within a process
+static TXN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A room and the account posting to it.
+#[derive(Clone)]
+pub(crate) struct Matrix {
+    /// The room's send endpoint, without a transaction ID
+    pub(crate) url: Url,
+    access_token: String,
+}
+
+impl Matrix {
+    /// Panics if `homeserver` isn't a valid base URL.
+    pub(crate) fn new(homeserver: &str, access_token: String, room_id: &str) -> Self {
+        let mut url = Url::parse(homeserver)
+            .ok()
+            .filter(|url| !url.cannot_be_a_base())
+            .unwrap_or_else(|| panic!("Invalid Matrix homeserver URL {homeserver:?}"));
+        url.path_segments_mut()
+            .expect("base URLs have path segments")
+            .pop_if_empty()
+            .extend([
+                "_matrix",
+                "client",
+                "v3",
+                "rooms",
+                room_id,
+                "send",
+                "m.room.message",
+            ]);
+        Self { url, access_token }
+    }
+
+    pub(crate) fn text(message: &str) -> Value {
+        serde_json::json!({ "msgtype": "m.text", "body": message })
+    }
+
+    pub(crate) fn html(plain: &str, html: &str) -> Value {
+        serde_json::json!({
+            "msgtype": "m.text",
+            "body": plain,
+            "format": "org.matrix.custom.html",
+            "formatted_body": html,
+        })
+    }
+
+    /// A PUT of `payload` under a fresh transaction ID. Retries clone the request, so they
+    /// reuse the ID and the homeserver drops duplicates.
+    pub(crate) fn request(&self, client: &Client, payload: &Value) -> RequestBuilder {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let txn_id = format!(
+            "axtra-{nanos}-{}",
+            TXN_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .expect("base URLs have path segments")
+            .push(&txn_id);
+        client
+            .put(url)
+            .bearer_auth(&self.access_token)
+            .json(payload)
+    }
+}
+
+/// Escape text for HTML, such as a Matrix formatted body.
+///
+/// ```rust, ignore
+/// let html = format!("<b>Failed:</b> <code>{}</code>", escape_html(&job));
+/// ```
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
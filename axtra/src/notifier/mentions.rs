@@ -0,0 +1,140 @@
+//! Who to ping with a notification, in each service's own syntax.
+
+use serde_json::Value;
+
+use crate::notifier::Level;
+
+/// People and groups to ping with a notification, by their Slack and Discord IDs.
+///
+/// Plain `@name` text doesn't ping anyone, so mentions are written the way each service
+/// expects: `<@U…>` and `<!subteam^S…>` on Slack, `<@…>` and `<@&…>` on Discord, which
+/// is also told to ping exactly these and nobody named in the alert itself. Other
+/// services don't show mentions.
+///
+/// ```rust, ignore
+/// notifier::init(NotifierConfig {
+///     mentions: Mentions::new()
+///         .slack_group("S0614TZR7")
+///         .discord_role("165511591545143296")
+///         .here()
+///         .critical_only(),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mentions {
+    slack_users: Vec<String>,
+    slack_groups: Vec<String>,
+    discord_users: Vec<String>,
+    discord_roles: Vec<String>,
+    here: bool,
+    everyone: bool,
+    critical_only: bool,
+}
+
+impl Mentions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A Slack member ID, like `U024BE7LH`, from their profile's "Copy member ID".
+    pub fn slack_user(mut self, id: impl Into<String>) -> Self {
+        self.slack_users.push(id.into());
+        self
+    }
+
+    /// A Slack user group ID, like `S0614TZR7`.
+    pub fn slack_group(mut self, id: impl Into<String>) -> Self {
+        self.slack_groups.push(id.into());
+        self
+    }
+
+    /// A Discord user ID, from "Copy User ID" in developer mode.
+    pub fn discord_user(mut self, id: impl Into<String>) -> Self {
+        self.discord_users.push(id.into());
+        self
+    }
+
+    /// A Discord role ID. The role must be mentionable, or the webhook allowed to
+    /// mention every role.
+    pub fn discord_role(mut self, id: impl Into<String>) -> Self {
+        self.discord_roles.push(id.into());
+        self
+    }
+
+    /// Ping whoever is online in the channel: `@here` on both services.
+    pub fn here(mut self) -> Self {
+        self.here = true;
+        self
+    }
+
+    /// Ping everyone in the channel: `@channel` on Slack, `@everyone` on Discord.
+    pub fn everyone(mut self) -> Self {
+        self.everyone = true;
+        self
+    }
+
+    /// Only ping for [`Level::Critical`] messages, such as database errors.
+    pub fn critical_only(mut self) -> Self {
+        self.critical_only = true;
+        self
+    }
+
+    /// Whether there's nobody to ping.
+    pub fn is_empty(&self) -> bool {
+        self.slack_users.is_empty()
+            && self.slack_groups.is_empty()
+            && self.discord_users.is_empty()
+            && self.discord_roles.is_empty()
+            && !self.here
+            && !self.everyone
+    }
+
+    /// These mentions if they apply to `level`, else none.
+    pub(crate) fn for_level(&self, level: Level) -> Self {
+        match self.critical_only && level != Level::Critical {
+            true => Self::default(),
+            false => self.clone(),
+        }
+    }
+
+    /// Slack mrkdwn, like `<!subteam^S0614TZR7> <!here>`.
+    pub(crate) fn slack(&self) -> String {
+        let users = self.slack_users.iter().map(|id| format!("<@{id}>"));
+        let groups = self
+            .slack_groups
+            .iter()
+            .map(|id| format!("<!subteam^{id}>"));
+        let broadcasts = [
+            self.here.then(|| "<!here>".to_string()),
+            self.everyone.then(|| "<!channel>".to_string()),
+        ];
+        let mentions = users.chain(groups).chain(broadcasts.into_iter().flatten());
+        mentions.collect::<Vec<_>>().join(" ")
+    }
+
+    /// Discord message content, like `<@&165511591545143296> @here`.
+    pub(crate) fn discord(&self) -> String {
+        let users = self.discord_users.iter().map(|id| format!("<@{id}>"));
+        let roles = self.discord_roles.iter().map(|id| format!("<@&{id}>"));
+        let broadcasts = [
+            self.here.then(|| "@here".to_string()),
+            self.everyone.then(|| "@everyone".to_string()),
+        ];
+        let mentions = users.chain(roles).chain(broadcasts.into_iter().flatten());
+        mentions.collect::<Vec<_>>().join(" ")
+    }
+
+    /// Discord's `allowed_mentions`, so only these mentions ping.
+    pub(crate) fn discord_allowed(&self) -> Value {
+        let parse: &[&str] = match self.here || self.everyone {
+            true => &["everyone"],
+            false => &[],
+        };
+        serde_json::json!({
+            "parse": parse,
+            "users": self.discord_users,
+            "roles": self.discord_roles,
+        })
+    }
+}
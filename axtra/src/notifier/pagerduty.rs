@@ -0,0 +1,265 @@
+//! PagerDuty Events API v2 incidents.
+
+use std::error::Error;
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::notifier::{Retry, default_client, retry};
+
+const EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+// PagerDuty rejects longer summaries and dedup keys
+const MAX_SUMMARY: usize = 1024;
+const MAX_DEDUP_KEY: usize = 255;
+
+/// How urgent an incident is; PagerDuty maps this to urgency through the service's rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Severity {
+    Critical,
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Critical => "critical",
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+/// An incident to trigger. Events sharing a dedup key are grouped into one open incident.
+///
+/// ```rust, ignore
+/// let event = Event::new("Payment webhook failing", Severity::Critical)
+///     .dedup_key("payments:webhook")
+///     .component("payments")
+///     .details(json!({ "failures": 12 }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Event {
+    summary: String,
+    severity: Severity,
+    source: Option<String>,
+    dedup_key: Option<String>,
+    component: Option<String>,
+    group: Option<String>,
+    class: Option<String>,
+    details: Option<Value>,
+}
+
+impl Event {
+    /// An event with this summary, the incident title shown in PagerDuty.
+    pub fn new(summary: impl Into<String>, severity: Severity) -> Self {
+        Self {
+            summary: summary.into(),
+            severity,
+            source: None,
+            dedup_key: None,
+            component: None,
+            group: None,
+            class: None,
+            details: None,
+        }
+    }
+
+    /// The affected system. Defaults to `APP_NAME`, or `"Rust"`.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Group this event with others sharing `key`. Without one PagerDuty generates a key,
+    /// returned by [`PagerDuty::trigger`].
+    pub fn dedup_key(mut self, key: impl Into<String>) -> Self {
+        self.dedup_key = Some(key.into());
+        self
+    }
+
+    pub fn component(mut self, component: impl Into<String>) -> Self {
+        self.component = Some(component.into());
+        self
+    }
+
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    pub fn class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// Free-form details shown on the incident.
+    pub fn details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+/// Triggers, acknowledges and resolves incidents on a PagerDuty service through its
+/// Events API v2 integration.
+///
+/// ```rust, ignore
+/// let pagerduty = PagerDuty::new(env::var("PAGERDUTY_ROUTING_KEY")?);
+///
+/// let key = pagerduty.trigger(Event::new("Queue backed up", Severity::Error)).await?;
+/// pagerduty.acknowledge(&key).await?;
+/// pagerduty.resolve(&key).await?;
+/// ```
+#[derive(Clone)]
+pub struct PagerDuty {
+    client: Client,
+    routing_key: String,
+    url: String,
+    retry: Retry,
+}
+
+impl PagerDuty {
+    /// A client for the service with this integration (routing) key.
+    pub fn new(routing_key: impl Into<String>) -> Self {
+        Self {
+            client: default_client(),
+            routing_key: routing_key.into(),
+            url: EVENTS_URL.to_string(),
+            retry: Retry::default(),
+        }
+    }
+
+    /// Send events somewhere other than `events.pagerduty.com`, e.g.
+    /// `https://events.eu.pagerduty.com/v2/enqueue` for EU accounts.
+    pub fn endpoint(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    /// Send events with `client` instead of one with a 10 second timeout.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Retry failed events with this policy instead of the default 3 attempts.
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// The dedup key error notifications use for errors raised at `location` (an
+    /// [`error_location!`](crate::error_location)), so their incident can be resolved later.
+    pub fn location_key(location: &str) -> String {
+        let app_name = crate::notifier::app_name();
+        truncate(format!("{app_name}:{location}"), MAX_DEDUP_KEY)
+    }
+
+    /// Open an incident, or add to the open one with the same dedup key. Returns the dedup
+    /// key for acknowledging or resolving it.
+    pub async fn trigger(&self, event: Event) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let source = event.source.unwrap_or_else(crate::notifier::app_name);
+        let mut payload = serde_json::json!({
+            "summary": truncate(event.summary, MAX_SUMMARY),
+            "source": source,
+            "severity": event.severity.as_str(),
+        });
+        for (field, value) in [
+            ("component", event.component),
+            ("group", event.group),
+            ("class", event.class),
+        ] {
+            if let Some(value) = value {
+                payload[field] = value.into();
+            }
+        }
+        if let Some(details) = event.details {
+            payload["custom_details"] = details;
+        }
+
+        let mut body = serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": "trigger",
+            "payload": payload,
+        });
+        if let Some(key) = &event.dedup_key {
+            body["dedup_key"] = truncate(key.clone(), MAX_DEDUP_KEY).into();
+        }
+
+        let accepted = self.enqueue(&body).await?;
+        accepted
+            .or(event.dedup_key)
+            .ok_or_else(|| "PagerDuty returned no dedup key".into())
+    }
+
+    /// Mark the incident with this dedup key as being worked on, stopping escalation.
+    pub async fn acknowledge(
+        &self,
+        dedup_key: impl AsRef<str>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.update("acknowledge", dedup_key.as_ref()).await
+    }
+
+    /// Resolve the incident with this dedup key.
+    pub async fn resolve(
+        &self,
+        dedup_key: impl AsRef<str>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.update("resolve", dedup_key.as_ref()).await
+    }
+
+    async fn update(
+        &self,
+        action: &str,
+        dedup_key: &str,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let body = serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": action,
+            "dedup_key": dedup_key,
+        });
+        self.enqueue(&body).await.map(|_| ())
+    }
+
+    // Post an event, returning the dedup key PagerDuty accepted it under
+    async fn enqueue(&self, body: &Value) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+        #[derive(Deserialize)]
+        struct Accepted {
+            dedup_key: Option<String>,
+        }
+
+        let res = retry::post(&self.client, &self.retry, &self.url, body).await?;
+        Ok(res
+            .json::<Accepted>()
+            .await
+            .ok()
+            .and_then(|accepted| accepted.dedup_key))
+    }
+}
+
+// The routing key is a secret, so only the endpoint is shown
+impl std::fmt::Debug for PagerDuty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PagerDuty")
+            .field("url", &self.url)
+            .field("retry", &self.retry)
+            .finish_non_exhaustive()
+    }
+}
+
+// Cut `text` to at most `max` bytes on a character boundary
+fn truncate(mut text: String, max: usize) -> String {
+    if text.len() > max {
+        let mut end = max;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
@@ -0,0 +1,119 @@
+//! Phone push notifications through ntfy and Pushover.
+
+use reqwest::{Client, RequestBuilder};
+use serde_json::Value;
+
+const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+
+// How often Pushover repeats an emergency alert until acknowledged, and for how long
+const PUSHOVER_RETRY_SECS: u32 = 60;
+const PUSHOVER_EXPIRE_SECS: u32 = 3600;
+
+/// An ntfy topic, with the priority and tags its messages get by default.
+#[derive(Clone)]
+pub(crate) struct Ntfy {
+    /// The server's root, which takes JSON messages naming their topic
+    pub(crate) server: String,
+    topic: String,
+    pub(crate) priority: Option<u8>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) token: Option<String>,
+}
+
+impl Ntfy {
+    /// Panics if `topic_url` has no topic after the server, like `https://ntfy.sh/alerts`.
+    pub(crate) fn new(topic_url: &str) -> Self {
+        let (server, topic) = topic_url
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .filter(|(server, topic)| {
+                server.contains("://") && !server.ends_with('/') && !topic.is_empty()
+            })
+            .unwrap_or_else(|| {
+                panic!("ntfy URL {topic_url:?} needs a topic, like https://ntfy.sh/alerts")
+            });
+        Self {
+            server: format!("{server}/"),
+            topic: topic.to_string(),
+            priority: None,
+            tags: Vec::new(),
+            token: None,
+        }
+    }
+
+    pub(crate) fn message(
+        &self,
+        title: Option<&str>,
+        message: &str,
+        priority: Option<u8>,
+        tags: &[&str],
+    ) -> Value {
+        let mut payload = serde_json::json!({ "topic": self.topic, "message": message });
+        if let Some(title) = title {
+            payload["title"] = title.into();
+        }
+        if let Some(priority) = priority.or(self.priority) {
+            payload["priority"] = priority.into();
+        }
+        let tags = match tags.is_empty() {
+            true => self.tags.iter().map(String::as_str).collect(),
+            false => tags.to_vec(),
+        };
+        if !tags.is_empty() {
+            payload["tags"] = tags.into();
+        }
+        payload
+    }
+
+    pub(crate) fn request(&self, client: &Client, payload: &Value) -> RequestBuilder {
+        let request = client.post(&self.server).json(payload);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// A Pushover application and the user or group it alerts.
+#[derive(Clone)]
+pub(crate) struct Pushover {
+    pub(crate) url: String,
+    token: String,
+    user: String,
+    pub(crate) priority: i8,
+}
+
+impl Pushover {
+    pub(crate) fn new(app_token: String, user_key: String) -> Self {
+        Self {
+            url: PUSHOVER_URL.to_string(),
+            token: app_token,
+            user: user_key,
+            priority: 0,
+        }
+    }
+
+    pub(crate) fn message(
+        &self,
+        title: Option<&str>,
+        message: &str,
+        priority: Option<i8>,
+    ) -> Value {
+        let priority = priority.unwrap_or(self.priority);
+        let mut payload = serde_json::json!({
+            "token": self.token,
+            "user": self.user,
+            "message": message,
+            "priority": priority,
+        });
+        if let Some(title) = title {
+            payload["title"] = title.into();
+        }
+        // Emergency alerts repeat until acknowledged, and Pushover requires saying how
+        if priority == 2 {
+            payload["retry"] = PUSHOVER_RETRY_SECS.into();
+            payload["expire"] = PUSHOVER_EXPIRE_SECS.into();
+        }
+        payload
+    }
+}
@@ -0,0 +1,195 @@
+//! Retries with exponential backoff for webhook posts.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use reqwest::{Client, RequestBuilder, Response, StatusCode, header::RETRY_AFTER};
+use serde_json::Value;
+
+/// How a [`Notifier`](crate::notifier::Notifier) retries failed webhook posts.
+///
+/// Rate limits (429), server errors (5xx), timeouts and connection errors are retried,
+/// waiting `base_delay`, then twice that, and so on up to `max_delay`, with random jitter
+/// so replicas don't retry in lockstep. A `Retry-After` from Slack or Discord is waited out
+/// instead, unless it's longer than `max_delay`. Other errors fail straight away.
+///
+/// ```rust, ignore
+/// let notifier = Notifier::with_slack(url).retry(Retry::new(5).max_delay(Duration::from_secs(60)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for Retry {
+    /// 3 attempts, starting at 500ms and waiting at most 30 seconds.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Retry {
+    /// Up to `max_attempts` attempts in all, with the default delays.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// A single attempt.
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// How long to wait before retrying after `attempt` (1-based) failed, or `None` to
+    /// give up.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        // Somewhere between half and all of the backoff
+        let jitter = RandomState::new().build_hasher().finish() % 1000;
+        Some(backoff / 2 + backoff / 2 * jitter as u32 / 1000)
+    }
+}
+
+/// Whether a failed request is worth retrying.
+pub(crate) fn retryable(e: &reqwest::Error) -> bool {
+    match e.status() {
+        Some(status) => status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+        None => e.is_timeout() || e.is_connect() || e.is_request(),
+    }
+}
+
+/// The `Retry-After` of a response, in seconds; Discord sends fractions.
+pub(crate) fn retry_after(res: &Response) -> Option<Duration> {
+    let secs: f64 = res
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+/// Post `payload` to `url`, retrying transient failures under `retry`. Returns the first
+/// successful response.
+pub(crate) async fn post(
+    client: &Client,
+    retry: &Retry,
+    url: &str,
+    payload: &Value,
+) -> Result<Response, reqwest::Error> {
+    send(retry, client.post(url).json(payload)).await
+}
+
+/// Send `request`, retrying transient failures under `retry`. The request's body must be
+/// clonable, as JSON bodies are.
+pub(crate) async fn send(
+    retry: &Retry,
+    request: RequestBuilder,
+) -> Result<Response, reqwest::Error> {
+    let mut attempt = 1;
+    loop {
+        let attempt_request = request
+            .try_clone()
+            .expect("notification requests have clonable bodies");
+        let (e, retry_after) = match attempt_request.send().await {
+            Ok(res) => {
+                let retry_after = retry_after(&res);
+                match res.error_for_status() {
+                    Ok(res) => return Ok(res),
+                    Err(e) => (e, retry_after),
+                }
+            }
+            Err(e) => (e, None),
+        };
+        let delay = match retryable(&e) {
+            true => retry.delay(attempt, retry_after),
+            false => None,
+        };
+        let Some(delay) = delay else {
+            return Err(e);
+        };
+        // The error's URL is the webhook, which is a secret
+        tracing::debug!(
+            attempt,
+            status = ?e.status(),
+            delay_ms = delay.as_millis() as u64,
+            "Webhook post failed, retrying"
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn backs_off_exponentially_with_jitter_up_to_the_max() {
+        let retry = Retry::new(10)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(1000));
+        for (attempt, backoff) in [(1, 100), (2, 200), (3, 400), (4, 800), (5, 1000), (9, 1000)] {
+            let delay = retry.delay(attempt, None).unwrap();
+            let backoff = Duration::from_millis(backoff);
+            assert!(
+                delay >= backoff / 2 && delay <= backoff,
+                "attempt {attempt}: {delay:?} outside {backoff:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let retry = Retry::new(3);
+        assert!(retry.delay(2, None).is_some());
+        assert_eq!(retry.delay(3, None), None);
+        assert_eq!(Retry::none().delay(1, None), None);
+        assert_eq!(Retry::new(0).max_attempts, 1);
+    }
+
+    #[test]
+    fn waits_out_retry_after_unless_over_the_max() {
+        let retry = Retry::new(3).max_delay(Duration::from_secs(30));
+        let wait = Duration::from_secs(5);
+        assert_eq!(retry.delay(1, Some(wait)), Some(wait));
+        assert_eq!(retry.delay(1, Some(Duration::from_secs(31))), None);
+    }
+
+    #[test]
+    fn huge_attempt_counts_do_not_overflow() {
+        let retry = Retry::new(u32::MAX);
+        assert!(retry.delay(u32::MAX - 1, None).unwrap() <= retry.max_delay);
+    }
+}
@@ -0,0 +1,57 @@
+//! Telegram Bot API messages.
+
+use serde_json::Value;
+
+/// A bot and the chat it posts to.
+#[derive(Debug, Clone)]
+pub(crate) struct Telegram {
+    /// The bot's `sendMessage` endpoint, which embeds its token.
+    pub(crate) url: String,
+    pub(crate) chat_id: String,
+}
+
+impl Telegram {
+    pub(crate) fn new(bot_token: &str, chat_id: impl Into<String>) -> Self {
+        Self {
+            url: format!("https://api.telegram.org/bot{bot_token}/sendMessage"),
+            chat_id: chat_id.into(),
+        }
+    }
+
+    pub(crate) fn text(&self, message: &str) -> Value {
+        serde_json::json!({ "chat_id": self.chat_id, "text": message })
+    }
+
+    pub(crate) fn markdown(&self, message: &str) -> Value {
+        serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": message,
+            "parse_mode": "MarkdownV2",
+        })
+    }
+}
+
+/// Escape text for a Telegram MarkdownV2 message, outside code blocks.
+///
+/// ```rust, ignore
+/// let message = format!("*Deploy finished* {}", escape_markdown_v2("v1.2.0 (build #42)"));
+/// ```
+pub fn escape_markdown_v2(text: &str) -> String {
+    escape(text, "_*[]()~`>#+-=|{}.!\\")
+}
+
+/// Escape text for a MarkdownV2 code block, where only `` ` `` and `\` are special.
+pub fn escape_markdown_v2_code(text: &str) -> String {
+    escape(text, "`\\")
+}
+
+fn escape(text: &str, special: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,85 @@
+//! The format of error notifications.
+
+use std::sync::OnceLock;
+
+use crate::notifier::webhook::substitute;
+
+static ERROR_TEMPLATE: OnceLock<ErrorTemplate> = OnceLock::new();
+
+/// How error notifications to Slack, Discord and registered backends read.
+///
+/// The title is the alert's headline and the body goes in a code block beneath it. Both
+/// can use these placeholders:
+///
+/// - `{{app}}`: the [`app_name`](crate::notifier::app_name)
+/// - `{{environment}}`: the [`environment`](crate::notifier::environment), like `production`
+/// - `{{code}}`: the error code, like `Database`
+/// - `{{status}}`: the response status, like `500`
+/// - `{{location}}`: where the error was created
+/// - `{{message}}`: the error and its causes
+/// - `{{request_id}}`: the request's ID, read by [`RequestIdLayer`](crate::errors::RequestIdLayer), or `-`
+/// - `{{release}}`: the version and git SHA from [`build_info`](crate::routes::health::build_info), or `-`
+///
+/// The code, status, location, request ID, environment and release are also sent as
+/// separate fields, so the default body is just the message.
+///
+/// ```rust, ignore
+/// notifier::set_error_template(
+///     ErrorTemplate::new()
+///         .title("{{code}} error in {{app}} ({{environment}})")
+///         .body("{{message}}\nat {{location}}, request {{request_id}}"),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorTemplate {
+    title: String,
+    body: String,
+}
+
+impl Default for ErrorTemplate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErrorTemplate {
+    /// The default format: `🔴 Exception — {{app}}` over `{{message}}`.
+    pub fn new() -> Self {
+        Self {
+            title: "\u{1F534} Exception — {{app}}".to_string(),
+            body: "{{message}}".to_string(),
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// The title and body with the placeholders in `values` filled in.
+    pub(crate) fn render(&self, values: &[(&str, &str)]) -> (String, String) {
+        (
+            substitute(&self.title, values),
+            substitute(&self.body, values),
+        )
+    }
+}
+
+/// Use `template` for error notifications from now on. Call it once, at startup.
+///
+/// Panics if a template is already set.
+pub fn set_error_template(template: ErrorTemplate) {
+    if ERROR_TEMPLATE.set(template).is_err() {
+        panic!("notifier::set_error_template was already called");
+    }
+}
+
+/// The template set with [`set_error_template`], or the default.
+pub(crate) fn error_template() -> &'static ErrorTemplate {
+    ERROR_TEMPLATE.get_or_init(ErrorTemplate::new)
+}
@@ -0,0 +1,176 @@
+//! Capturing notifications in tests instead of sending them.
+//!
+//! ```rust, ignore
+//! use axtra::notifier::{Level, testing::MockNotifier};
+//!
+//! #[tokio::test]
+//! async fn failed_query_alerts() {
+//!     let notifier = MockNotifier::new();
+//!
+//!     let request = Request::get("/orders").body(Body::empty()).unwrap();
+//!     let res = notifier.capture(app().oneshot(request)).await.unwrap();
+//!     assert_eq!(res.status(), 500);
+//!
+//!     let messages = notifier.wait_for(1).await;
+//!     assert_eq!(messages[0].level, Level::Critical);
+//!     assert!(messages[0].fields.contains(&("Code".into(), "Database".into())));
+//! }
+//! ```
+
+use std::{
+    future::Future,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+use crate::notifier::{
+    BackendId, Message, NotificationBackend, NotifyFuture, register_backend, unregister_backend,
+    with_backends,
+};
+
+// How long `wait_for` waits for notifications sent in the background
+const WAIT: Duration = Duration::from_secs(1);
+
+/// A [`NotificationBackend`] that keeps every message it's sent, for assertions.
+///
+/// Clones share their messages, so pass one to the code under test and keep another.
+/// [`capture`](Self::capture) collects the error alerts of one test; a mock from
+/// [`register`](Self::register) gets every test's, including ones raised in spawned tasks.
+#[derive(Debug, Clone, Default)]
+pub struct MockNotifier {
+    messages: Arc<Mutex<Vec<Message>>>,
+    sent: Arc<Notify>,
+}
+
+impl MockNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A new mock that receives error alerts, like a backend passed to
+    /// [`register_backend`], until the returned guard is dropped.
+    pub fn register() -> Registered {
+        let notifier = Self::new();
+        let id = register_backend(notifier.clone());
+        Registered { notifier, id }
+    }
+
+    /// Run `future` with the error alerts and [`notify!`](crate::notify) messages it sends
+    /// coming to this mock alone, instead of to registered backends, so tests running in
+    /// parallel only see their own. Alerts from tasks it spawns aren't captured.
+    pub async fn capture<F: Future>(&self, future: F) -> F::Output {
+        with_backends(vec![Arc::new(self.clone())], future).await
+    }
+
+    /// The messages sent so far, oldest first.
+    pub fn messages(&self) -> Vec<Message> {
+        self.lock().clone()
+    }
+
+    /// The messages sent so far, clearing them.
+    pub fn take(&self) -> Vec<Message> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// The messages sent once there are at least `count`, waiting up to a second for
+    /// notifications sent in the background, like error alerts.
+    ///
+    /// Panics if fewer than `count` arrive in time.
+    pub async fn wait_for(&self, count: usize) -> Vec<Message> {
+        let arrived = async {
+            loop {
+                // Created before checking, so a send in between still wakes it
+                let sent = self.sent.notified();
+                if self.len() >= count {
+                    return;
+                }
+                sent.await;
+            }
+        };
+        if tokio::time::timeout(WAIT, arrived).await.is_err() {
+            panic!(
+                "expected {count} notifications within {WAIT:?}, got {}",
+                self.len()
+            );
+        }
+        self.messages()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Message>> {
+        self.messages.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A [`MockNotifier`] receiving error alerts, from [`MockNotifier::register`].
+/// Unregisters it when dropped, so it doesn't outlive the test.
+#[derive(Debug)]
+pub struct Registered {
+    notifier: MockNotifier,
+    id: BackendId,
+}
+
+impl Deref for Registered {
+    type Target = MockNotifier;
+
+    fn deref(&self) -> &MockNotifier {
+        &self.notifier
+    }
+}
+
+impl Drop for Registered {
+    fn drop(&mut self) {
+        unregister_backend(self.id);
+    }
+}
+
+impl NotificationBackend for MockNotifier {
+    fn send(&self, message: Message) -> NotifyFuture<'_> {
+        self.lock().push(message);
+        self.sent.notify_waiters();
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::notifier::registered_backends;
+
+    #[test]
+    fn unregisters_and_frees_when_dropped() {
+        let registered = MockNotifier::register();
+        let (id, messages) = (registered.id, registered.messages.clone());
+        drop(registered);
+        // Already gone, so there's nothing left to remove
+        assert!(!unregister_backend(id));
+        assert_eq!(Arc::strong_count(&messages), 1);
+    }
+
+    #[tokio::test]
+    async fn captures_only_alerts_sent_inside() {
+        let notifier = MockNotifier::new();
+        let sent = notifier
+            .capture(async {
+                let backends = registered_backends();
+                for backend in &backends {
+                    backend.send(Message::new("Database down")).await.unwrap();
+                }
+                backends.len()
+            })
+            .await;
+        assert_eq!(sent, 1);
+        assert_eq!(notifier.len(), 1);
+    }
+}
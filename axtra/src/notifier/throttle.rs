@@ -0,0 +1,205 @@
+//! Per-destination rate limiting and deduplication for notifications.
+
+use std::{
+    collections::{HashMap, hash_map::DefaultHasher},
+    fmt::Display,
+    future::Future,
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+/// Length of a rate-limit window, and how long suppressed messages wait for their digest.
+pub(crate) const WINDOW: Duration = Duration::from_secs(60);
+
+// Fingerprints of recent messages are swept once there are this many
+const SEEN_SWEEP_THRESHOLD: usize = 256;
+
+// Messages sent and suppressed in a destination's current window
+struct Window {
+    started: Instant,
+    sent: u32,
+    suppressed: u32,
+}
+
+/// What to do with a message.
+pub(crate) enum Verdict {
+    Send,
+    /// Drop the message. Carries when to send a digest of suppressed messages, if this is
+    /// the first one suppressed since the last digest.
+    Suppress(Option<Instant>),
+}
+
+/// Windows and recent message fingerprints, shared with pending digests.
+#[derive(Default)]
+pub(crate) struct Limits {
+    windows: Mutex<HashMap<String, Window>>,
+    seen: Mutex<HashMap<u64, Instant>>,
+}
+
+impl Limits {
+    /// Count a message to `destination`, suppressing it if the destination already had
+    /// `per_minute` messages this window or the same payload went there within `dedup`.
+    pub(crate) fn check(
+        &self,
+        destination: &str,
+        payload: &Value,
+        per_minute: Option<u32>,
+        dedup: Option<Duration>,
+    ) -> Verdict {
+        let now = Instant::now();
+        let duplicate = dedup.is_some_and(|window| self.seen_within(destination, payload, window));
+
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let window = windows.entry(destination.to_string()).or_insert(Window {
+            started: now,
+            sent: 0,
+            suppressed: 0,
+        });
+        // Suppressed counts carry over until their digest takes them
+        if now.duration_since(window.started) >= WINDOW {
+            window.started = now;
+            window.sent = 0;
+        }
+        if !duplicate && per_minute.is_none_or(|max| window.sent < max) {
+            window.sent += 1;
+            return Verdict::Send;
+        }
+        window.suppressed += 1;
+        Verdict::Suppress((window.suppressed == 1).then(|| window.started + WINDOW))
+    }
+
+    /// Take the count of messages suppressed for `destination` since its last digest.
+    pub(crate) fn take_suppressed(&self, destination: &str) -> u32 {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        windows
+            .get_mut(destination)
+            .map_or(0, |window| std::mem::take(&mut window.suppressed))
+    }
+
+    // Whether `payload` went to `destination` within `window`, recording it if not
+    fn seen_within(&self, destination: &str, payload: &Value, window: Duration) -> bool {
+        let mut hasher = DefaultHasher::new();
+        destination.hash(&mut hasher);
+        payload.to_string().hash(&mut hasher);
+        let fingerprint = hasher.finish();
+
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        if seen.len() >= SEEN_SWEEP_THRESHOLD {
+            seen.retain(|_, at| now.duration_since(*at) < window);
+        }
+        match seen.get(&fingerprint) {
+            Some(at) if now.duration_since(*at) < window => true,
+            _ => {
+                seen.insert(fingerprint, now);
+                false
+            }
+        }
+    }
+}
+
+/// At `at`, tell `destination` how many messages were suppressed since its last digest,
+/// through `send`. Nothing is sent if none were.
+pub(crate) fn schedule_digest<F, Fut, E>(
+    limits: Arc<Limits>,
+    destination: String,
+    at: Instant,
+    send: F,
+) where
+    F: FnOnce(String) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), E>> + Send,
+    E: Display,
+{
+    tokio::spawn(async move {
+        tokio::time::sleep_until(at.into()).await;
+        let suppressed = limits.take_suppressed(&destination);
+        if suppressed == 0 {
+            return;
+        }
+        let message = match suppressed {
+            1 => "1 more notification suppressed (rate limit or duplicate)".to_string(),
+            n => format!("{n} more notifications suppressed (rate limit or duplicates)"),
+        };
+        if let Err(e) = send(message).await {
+            tracing::warn!(error = %e, "Failed to send suppressed notification digest");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    #[test]
+    fn suppresses_over_the_rate_limit_and_counts_for_one_digest() {
+        let limits = Limits::default();
+        let check = |n: u32| limits.check("slack", &json!({ "text": n }), Some(2), None);
+
+        assert!(matches!(check(1), Verdict::Send));
+        assert!(matches!(check(2), Verdict::Send));
+        // Only the first suppressed message schedules the digest
+        assert!(matches!(check(3), Verdict::Suppress(Some(_))));
+        assert!(matches!(check(4), Verdict::Suppress(None)));
+        assert_eq!(limits.take_suppressed("slack"), 2);
+        assert_eq!(limits.take_suppressed("slack"), 0);
+
+        assert!(matches!(
+            limits.check("discord", &json!({}), Some(2), None),
+            Verdict::Send
+        ));
+    }
+
+    #[test]
+    fn drops_duplicates_within_the_dedup_window() {
+        let limits = Limits::default();
+        let dedup = Some(Duration::from_millis(50));
+        let payload = json!({ "text": "Database down" });
+
+        assert!(matches!(
+            limits.check("slack", &payload, None, dedup),
+            Verdict::Send
+        ));
+        assert!(matches!(
+            limits.check("slack", &payload, None, dedup),
+            Verdict::Suppress(Some(_))
+        ));
+        // The same message to another destination isn't a duplicate
+        assert!(matches!(
+            limits.check("discord", &payload, None, dedup),
+            Verdict::Send
+        ));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(matches!(
+            limits.check("slack", &payload, None, dedup),
+            Verdict::Send
+        ));
+    }
+
+    #[tokio::test]
+    async fn digest_counts_what_was_suppressed() {
+        let limits = Arc::new(Limits::default());
+        for n in 0..3 {
+            limits.check("email", &json!({ "text": n }), Some(1), None);
+        }
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        schedule_digest(
+            limits.clone(),
+            "email".into(),
+            Instant::now(),
+            |message| async {
+                tx.send(message).unwrap();
+                Ok::<_, String>(())
+            },
+        );
+        assert_eq!(
+            rx.await.unwrap(),
+            "2 more notifications suppressed (rate limit or duplicates)"
+        );
+        assert_eq!(limits.take_suppressed("email"), 0);
+    }
+}
@@ -0,0 +1,117 @@
+//! Generic JSON webhooks with user-defined payloads.
+
+use reqwest::{
+    Client, RequestBuilder,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use serde_json::Value;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+#[derive(Clone)]
+pub(crate) enum Auth {
+    Bearer(String),
+    Basic(String, String),
+}
+
+/// A URL, the payload template rendered for each message, and the headers sent with it.
+#[derive(Clone)]
+pub(crate) struct Webhook {
+    pub(crate) url: String,
+    pub(crate) template: Value,
+    pub(crate) headers: HeaderMap,
+    pub(crate) auth: Option<Auth>,
+}
+
+impl Webhook {
+    pub(crate) fn new(url: String) -> Self {
+        Self {
+            url,
+            template: serde_json::json!({ "message": "{{message}}" }),
+            headers: HeaderMap::new(),
+            auth: None,
+        }
+    }
+
+    /// Panics if `name` or `value` isn't a valid header.
+    pub(crate) fn header(&mut self, name: &str, value: &str) {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .unwrap_or_else(|e| panic!("Invalid webhook header name {name:?}: {e}"));
+        let mut value = HeaderValue::from_str(value)
+            .unwrap_or_else(|e| panic!("Invalid value for webhook header {name}: {e}"));
+        value.set_sensitive(true);
+        self.headers.insert(name, value);
+    }
+
+    pub(crate) fn request(&self, client: &Client, payload: &Value) -> RequestBuilder {
+        let request = client
+            .post(&self.url)
+            .headers(self.headers.clone())
+            .json(payload);
+        match &self.auth {
+            Some(Auth::Bearer(token)) => request.bearer_auth(token),
+            Some(Auth::Basic(username, password)) => request.basic_auth(username, Some(password)),
+            None => request,
+        }
+    }
+
+    /// The template with `{{message}}`, `{{app}}`, `{{timestamp}}` and each field's
+    /// `{{name}}` filled in. Unknown placeholders are left as they are.
+    pub(crate) fn render(&self, message: &str, fields: &[(&str, &str)]) -> Value {
+        let app = crate::notifier::app_name();
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default();
+        let mut values = vec![
+            ("message", message),
+            ("app", app.as_str()),
+            ("timestamp", timestamp.as_str()),
+        ];
+        values.extend_from_slice(fields);
+
+        let mut payload = self.template.clone();
+        fill(&mut payload, &values);
+        payload
+    }
+}
+
+// Replace placeholders in every string in `value`, keys included
+fn fill(value: &mut Value, values: &[(&str, &str)]) {
+    match value {
+        Value::String(text) if text.contains("{{") => *text = substitute(text, values),
+        Value::Array(items) => items.iter_mut().for_each(|item| fill(item, values)),
+        Value::Object(map) => {
+            let entries = std::mem::take(map);
+            for (key, mut item) in entries {
+                fill(&mut item, values);
+                let key = match key.contains("{{") {
+                    true => substitute(&key, values),
+                    false => key,
+                };
+                map.insert(key, item);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn substitute(text: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = after[..end].trim();
+        // Later fields win, so callers can override the built-in placeholders
+        match values.iter().rev().find(|(key, _)| *key == name) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,185 @@
+//! `Cache-Control` policies for JSON responses and routes.
+
+use std::fmt;
+
+use axum::{
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use tower_http::set_header::SetResponseHeaderLayer;
+
+use crate::response::{ETagged, WrappedJson, WrappedKey};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visibility {
+    Public,
+    Private,
+    NoStore,
+}
+
+/// Builder for `Cache-Control` header values.
+///
+/// All durations are in seconds, matching the header itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePolicy {
+    visibility: Visibility,
+    max_age: Option<u64>,
+    s_maxage: Option<u64>,
+    stale_while_revalidate: Option<u64>,
+    stale_if_error: Option<u64>,
+    no_cache: bool,
+    must_revalidate: bool,
+    immutable: bool,
+}
+
+impl CachePolicy {
+    fn with_visibility(visibility: Visibility) -> Self {
+        Self {
+            visibility,
+            max_age: None,
+            s_maxage: None,
+            stale_while_revalidate: None,
+            stale_if_error: None,
+            no_cache: false,
+            must_revalidate: false,
+            immutable: false,
+        }
+    }
+
+    /// Cacheable by browsers and shared caches for `max_age` seconds.
+    pub fn public(max_age: u64) -> Self {
+        Self::with_visibility(Visibility::Public).max_age(max_age)
+    }
+
+    /// Cacheable only by the user's browser; revalidated on every use unless `max_age` is set.
+    pub fn private() -> Self {
+        Self::with_visibility(Visibility::Private).no_cache()
+    }
+
+    /// Never stored by any cache.
+    pub fn no_store() -> Self {
+        Self::with_visibility(Visibility::NoStore)
+    }
+
+    pub fn max_age(mut self, seconds: u64) -> Self {
+        self.max_age = Some(seconds);
+        self.no_cache = false;
+        self
+    }
+
+    /// Lifetime for shared caches (CDNs), overriding `max-age` there.
+    pub fn s_maxage(mut self, seconds: u64) -> Self {
+        self.s_maxage = Some(seconds);
+        self
+    }
+
+    pub fn stale_while_revalidate(mut self, seconds: u64) -> Self {
+        self.stale_while_revalidate = Some(seconds);
+        self
+    }
+
+    pub fn stale_if_error(mut self, seconds: u64) -> Self {
+        self.stale_if_error = Some(seconds);
+        self
+    }
+
+    /// Require revalidation (e.g. via ETag) before every reuse.
+    pub fn no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+
+    pub fn must_revalidate(mut self) -> Self {
+        self.must_revalidate = true;
+        self
+    }
+
+    /// The response will never change (fingerprinted assets).
+    pub fn immutable(mut self) -> Self {
+        self.immutable = true;
+        self
+    }
+
+    pub fn header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.to_string()).expect("cache-control directives are valid")
+    }
+
+    /// Layer setting this policy on every response that doesn't already set `Cache-Control`.
+    pub fn layer(&self) -> SetResponseHeaderLayer<HeaderValue> {
+        SetResponseHeaderLayer::if_not_present(header::CACHE_CONTROL, self.header_value())
+    }
+}
+
+impl fmt::Display for CachePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.visibility == Visibility::NoStore {
+            return f.write_str("no-store");
+        }
+
+        let mut directives = vec![match self.visibility {
+            Visibility::Public => "public".to_string(),
+            _ => "private".to_string(),
+        }];
+        if self.no_cache {
+            directives.push("no-cache".to_string());
+        }
+        if let Some(max_age) = self.max_age {
+            directives.push(format!("max-age={max_age}"));
+        }
+        if let Some(s_maxage) = self.s_maxage {
+            directives.push(format!("s-maxage={s_maxage}"));
+        }
+        if let Some(swr) = self.stale_while_revalidate {
+            directives.push(format!("stale-while-revalidate={swr}"));
+        }
+        if let Some(sie) = self.stale_if_error {
+            directives.push(format!("stale-if-error={sie}"));
+        }
+        if self.must_revalidate {
+            directives.push("must-revalidate".to_string());
+        }
+        if self.immutable {
+            directives.push("immutable".to_string());
+        }
+        f.write_str(&directives.join(", "))
+    }
+}
+
+/// A response with an explicit `Cache-Control` policy.
+pub struct CacheControlled<R> {
+    inner: R,
+    policy: CachePolicy,
+}
+
+impl<R> CacheControlled<R> {
+    pub fn new(inner: R, policy: CachePolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl<R: IntoResponse> IntoResponse for CacheControlled<R> {
+    fn into_response(self) -> Response {
+        let mut res = self.inner.into_response();
+        res.headers_mut()
+            .insert(header::CACHE_CONTROL, self.policy.header_value());
+        res
+    }
+}
+
+impl<T> WrappedJson<T>
+where
+    T: Serialize + WrappedKey,
+{
+    /// Set the `Cache-Control` policy for this response.
+    pub fn cache(self, policy: CachePolicy) -> CacheControlled<Self> {
+        CacheControlled::new(self, policy)
+    }
+}
+
+impl ETagged {
+    /// Set the `Cache-Control` policy for this response.
+    pub fn cache(self, policy: CachePolicy) -> CacheControlled<Self> {
+        CacheControlled::new(self, policy)
+    }
+}
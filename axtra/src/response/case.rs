@@ -0,0 +1,143 @@
+//! camelCase key transformation for outgoing JSON responses.
+
+use std::{future::Future, pin::Pin};
+
+use axum::{
+    BoxError,
+    body::{Body, Bytes, HttpBody},
+    http::{Request, Response, header},
+};
+use inflector::cases::camelcase::to_camel_case;
+use serde_json::{Map, Value};
+use tower::{Layer, Service};
+
+use crate::response::json::to_json_vec;
+
+/// Recursively convert every object key in `value` to camelCase.
+///
+/// Keys without an underscore or hyphen are left untouched so already camelCased
+/// or acronym keys (`id`, `URL`) are preserved. The `field` entries of
+/// `validationErrors` are converted as well so they match the renamed payload keys.
+pub fn camel_case_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut converted = Map::with_capacity(map.len());
+            for (key, value) in map {
+                let key = camel_case_key(key);
+                let mut value = camel_case_keys(value);
+                if key == "validationErrors" {
+                    camel_case_validation_fields(&mut value);
+                }
+                converted.insert(key, value);
+            }
+            Value::Object(converted)
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(camel_case_keys).collect()),
+        other => other,
+    }
+}
+
+fn camel_case_key(key: String) -> String {
+    if key.contains(['_', '-']) {
+        to_camel_case(&key)
+    } else {
+        key
+    }
+}
+
+// Validation errors reference payload fields by name, so rename those too
+fn camel_case_validation_fields(value: &mut Value) {
+    let Some(errors) = value.get_mut("errors").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for error in errors {
+        if let Some(Value::String(field)) = error.get_mut("field") {
+            *field = camel_case_key(std::mem::take(field));
+        }
+    }
+}
+
+// CamelCaseLayer factory
+#[derive(Debug, Clone, Default)]
+pub struct CamelCaseLayer;
+
+impl CamelCaseLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for CamelCaseLayer {
+    type Service = CamelCaseMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CamelCaseMiddleware { inner }
+    }
+}
+
+// A middleware that rewrites JSON response bodies with camelCase keys
+#[derive(Debug, Clone)]
+pub struct CamelCaseMiddleware<S> {
+    inner: S,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for CamelCaseMiddleware<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+    ResBody: HttpBody<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let res = inner.call(req).await?;
+            if !is_json(&res) {
+                return Ok(res.map(Body::new));
+            }
+
+            let (mut parts, body) = res.into_parts();
+            let bytes = match axum::body::to_bytes(Body::new(body), usize::MAX).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    tracing::error!("Failed to buffer JSON response body: {e}");
+                    let mut res = Response::new(Body::empty());
+                    *res.status_mut() = http::StatusCode::INTERNAL_SERVER_ERROR;
+                    return Ok(res);
+                }
+            };
+
+            let body = match serde_json::from_slice::<Value>(&bytes) {
+                Ok(value) => match to_json_vec(&camel_case_keys(value)) {
+                    Ok(converted) => Bytes::from(converted),
+                    Err(_) => bytes,
+                },
+                Err(_) => bytes,
+            };
+
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Ok(Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+fn is_json<B>(res: &Response<B>) -> bool {
+    res.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
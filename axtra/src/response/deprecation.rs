@@ -0,0 +1,170 @@
+//! `Deprecation`, `Sunset`, and successor `Link` headers for deprecated endpoints.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+use axum::{
+    http::{HeaderMap, HeaderName, HeaderValue, Request, Response, header},
+    response::IntoResponse,
+};
+use time::OffsetDateTime;
+use tower::{Layer, Service};
+
+static DEPRECATED_HITS: AtomicU64 = AtomicU64::new(0);
+
+/// Total number of responses served from deprecated endpoints since startup.
+pub fn deprecated_hits() -> u64 {
+    DEPRECATED_HITS.load(Ordering::Relaxed)
+}
+
+/// Describes how an endpoint is deprecated.
+#[derive(Debug, Clone, Default)]
+pub struct Deprecation {
+    since: Option<OffsetDateTime>,
+    sunset: Option<OffsetDateTime>,
+    successor: Option<String>,
+}
+
+impl Deprecation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When the endpoint was deprecated (`Deprecation: @<unix>`).
+    pub fn since(mut self, date: OffsetDateTime) -> Self {
+        self.since = Some(date);
+        self
+    }
+
+    /// When the endpoint will stop responding (`Sunset: <http-date>`).
+    pub fn sunset(mut self, date: OffsetDateTime) -> Self {
+        self.sunset = Some(date);
+        self
+    }
+
+    /// URL of the replacement endpoint (`Link: <url>; rel="successor-version"`).
+    pub fn successor(mut self, url: impl Into<String>) -> Self {
+        self.successor = Some(url.into());
+        self
+    }
+
+    fn apply(&self, headers: &mut HeaderMap, path: Option<&str>) {
+        DEPRECATED_HITS.fetch_add(1, Ordering::Relaxed);
+        crate::metrics::counter(
+            "axtra_deprecated_requests_total",
+            "Requests served by deprecated endpoints.",
+        )
+        .inc();
+        tracing::warn!(
+            path = path.unwrap_or_default(),
+            successor = self.successor.as_deref().unwrap_or_default(),
+            "Deprecated endpoint called"
+        );
+
+        let deprecation = match self.since {
+            Some(since) => format!("@{}", since.unix_timestamp()),
+            None => "true".to_string(),
+        };
+        if let Ok(value) = HeaderValue::from_str(&deprecation) {
+            headers.insert(HeaderName::from_static("deprecation"), value);
+        }
+        if let Some(sunset) = self.sunset {
+            let date = httpdate::fmt_http_date(SystemTime::from(sunset));
+            if let Ok(value) = HeaderValue::from_str(&date) {
+                headers.insert(HeaderName::from_static("sunset"), value);
+            }
+        }
+        if let Some(successor) = &self.successor
+            && let Ok(value) =
+                HeaderValue::from_str(&format!("<{successor}>; rel=\"successor-version\""))
+        {
+            headers.append(header::LINK, value);
+        }
+    }
+}
+
+/// Wraps a response from a deprecated endpoint with deprecation headers.
+pub struct Deprecated<T> {
+    inner: T,
+    deprecation: Deprecation,
+}
+
+impl<T> Deprecated<T> {
+    pub fn new(inner: T, deprecation: Deprecation) -> Self {
+        Self { inner, deprecation }
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Deprecated<T> {
+    fn into_response(self) -> axum::response::Response {
+        let mut res = self.inner.into_response();
+        self.deprecation.apply(res.headers_mut(), None);
+        res
+    }
+}
+
+// DeprecationLayer factory
+#[derive(Debug, Clone)]
+pub struct DeprecationLayer {
+    deprecation: Deprecation,
+}
+
+impl DeprecationLayer {
+    pub fn new(deprecation: Deprecation) -> Self {
+        Self { deprecation }
+    }
+}
+
+impl<S> Layer<S> for DeprecationLayer {
+    type Service = DeprecationMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DeprecationMiddleware {
+            inner,
+            deprecation: self.deprecation.clone(),
+        }
+    }
+}
+
+// A middleware that marks every response of the wrapped routes as deprecated
+#[derive(Debug, Clone)]
+pub struct DeprecationMiddleware<S> {
+    inner: S,
+    deprecation: Deprecation,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for DeprecationMiddleware<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let deprecation = self.deprecation.clone();
+        let path = req.uri().path().to_owned();
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let mut res = inner.call(req).await?;
+            deprecation.apply(res.headers_mut(), Some(&path));
+            Ok(res)
+        })
+    }
+}
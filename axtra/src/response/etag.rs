@@ -0,0 +1,112 @@
+//! ETag generation and conditional `304 Not Modified` handling.
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderValue, StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::response::{WrappedJson, WrappedKey};
+
+/// The client's `If-None-Match` request header.
+///
+/// Extract it in a handler and pass it to [`WrappedJson::with_etag`].
+#[derive(Debug, Clone, Default)]
+pub struct IfNoneMatch(pub Option<String>);
+
+impl IfNoneMatch {
+    /// Returns true if the header matches the given (quoted) entity tag.
+    ///
+    /// Uses weak comparison as required for `If-None-Match`, so `W/"abc"` matches `"abc"`.
+    pub fn matches(&self, etag: &str) -> bool {
+        let Some(header) = self.0.as_deref() else {
+            return false;
+        };
+        let etag = etag.trim_start_matches("W/");
+        header
+            .split(',')
+            .map(str::trim)
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+    }
+}
+
+impl<S> FromRequestParts<S> for IfNoneMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(
+            parts
+                .headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        ))
+    }
+}
+
+/// Compute a strong ETag (FNV-1a, 64 bit) for a response body.
+pub fn etag_for(bytes: &[u8]) -> String {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let hash = bytes.iter().fold(OFFSET, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    });
+    format!("\"{hash:016x}\"")
+}
+
+/// A JSON response carrying an `ETag`, answered with `304` when the client already has it.
+pub struct ETagged {
+    body: Result<Vec<u8>, serde_json::Error>,
+    not_modified: bool,
+}
+
+impl<T> WrappedJson<T>
+where
+    T: Serialize + WrappedKey,
+{
+    /// Hash the serialized envelope, set `ETag`, and short-circuit to `304 Not Modified`
+    /// when it matches the request's `If-None-Match`.
+    pub fn with_etag(self, if_none_match: &IfNoneMatch) -> ETagged {
+        let body = self.to_bytes();
+        let not_modified = body
+            .as_ref()
+            .is_ok_and(|bytes| if_none_match.matches(&etag_for(bytes)));
+        ETagged { body, not_modified }
+    }
+}
+
+impl IntoResponse for ETagged {
+    fn into_response(self) -> Response {
+        let bytes = match self.body {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::error!("Failed to serialize JSON response: {e}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        };
+
+        let etag = HeaderValue::from_str(&etag_for(&bytes)).expect("hex etag is a valid header");
+        if self.not_modified {
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+        }
+
+        (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                ),
+                (header::ETAG, etag),
+            ],
+            bytes,
+        )
+            .into_response()
+    }
+}
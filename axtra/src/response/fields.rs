@@ -0,0 +1,122 @@
+//! Sparse fieldsets: prune wrapped responses to the fields a client asked for.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    app_error,
+    errors::AppError,
+    response::{CacheControlled, CachePolicy, WrappedJson, WrappedKey, json::json_response},
+};
+
+/// Fields requested via `?fields=id,name,email`.
+///
+/// Empty when the parameter is absent, which keeps every field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fields(pub Vec<String>);
+
+impl Fields {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Retain only the requested top-level fields of an object, or of each object in an array.
+    ///
+    /// Returns a BadRequest for fields that no object contains.
+    pub fn prune(&self, value: &mut Value) -> Result<(), AppError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let objects: Vec<&mut serde_json::Map<String, Value>> = match value {
+            Value::Object(map) => vec![map],
+            Value::Array(values) => values.iter_mut().filter_map(Value::as_object_mut).collect(),
+            _ => return Ok(()),
+        };
+
+        if !objects.is_empty()
+            && let Some(unknown) = self
+                .0
+                .iter()
+                .find(|field| !objects.iter().any(|object| object.contains_key(*field)))
+        {
+            return Err(app_error!(bad_request, json, "Unknown field '{}'", unknown));
+        }
+
+        for object in objects {
+            object.retain(|key, _| self.0.contains(key));
+        }
+        Ok(())
+    }
+}
+
+impl<S> FromRequestParts<S> for Fields
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        query_list(parts, "fields").map(Self)
+    }
+}
+
+// Parse a comma separated query parameter such as `?fields=id,name`
+pub(crate) fn query_list(parts: &Parts, name: &str) -> Result<Vec<String>, AppError> {
+    let Query(params) = Query::<HashMap<String, String>>::try_from_uri(&parts.uri).map_err(
+        app_error!(bad_request, json, with_error, "Invalid query string"),
+    )?;
+
+    Ok(params
+        .get(name)
+        .map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// An already-serialized `{ key: value }` envelope.
+///
+/// Produced by response shaping such as [`WrappedJson::fields`] and [`WrappedJson::include`].
+#[derive(Debug, Clone)]
+pub struct WrappedValue(pub Value);
+
+impl WrappedValue {
+    /// Set the `Cache-Control` policy for this response.
+    pub fn cache(self, policy: CachePolicy) -> CacheControlled<Self> {
+        CacheControlled::new(self, policy)
+    }
+}
+
+impl IntoResponse for WrappedValue {
+    fn into_response(self) -> Response {
+        json_response(&self.0)
+    }
+}
+
+impl<T> WrappedJson<T>
+where
+    T: Serialize + WrappedKey,
+{
+    /// Prune the payload to the fields requested with `?fields=`.
+    pub fn fields(self, fields: &Fields) -> Result<WrappedValue, AppError> {
+        let mut envelope =
+            self.to_value()
+                .map_err(app_error!(exception, json, "Failed to serialize response"))?;
+        if let Some(value) = envelope.get_mut(T::wrapped_key().as_ref()) {
+            fields.prune(value)?;
+        }
+        Ok(WrappedValue(envelope))
+    }
+}
@@ -0,0 +1,151 @@
+//! Relationship includes (`?include=author,comments`) in wrapped responses.
+
+use std::future::Future;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    app_error,
+    errors::AppError,
+    response::{ResponseKey, WrappedJson, WrappedKey, WrappedValue, fields::query_list},
+};
+
+/// Relationships requested via `?include=author,comments`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Include(pub Vec<String>);
+
+impl Include {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether the client asked for the named relationship.
+    pub fn contains(&self, relationship: &str) -> bool {
+        self.0.iter().any(|name| name == relationship)
+    }
+
+    /// Returns a BadRequest if any requested relationship is not supported by `T`.
+    pub fn validate<T: Includable>(&self) -> Result<(), AppError> {
+        match self
+            .0
+            .iter()
+            .find(|name| !T::RELATIONSHIPS.contains(&name.as_str()))
+        {
+            Some(unknown) => Err(app_error!(
+                bad_request,
+                json,
+                "Unknown relationship '{}'",
+                unknown
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for Include
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        query_list(parts, "include").map(Self)
+    }
+}
+
+/// Sub-resources attached under the `included` key, grouped by their plural response key.
+#[derive(Debug, Clone, Default)]
+pub struct Included(Map<String, Value>);
+
+impl Included {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a list of related resources, e.g. `{ "authors": [...] }`.
+    pub fn with<T>(mut self, items: Vec<T>) -> Result<Self, AppError>
+    where
+        T: Serialize + ResponseKey,
+    {
+        let value = serde_json::to_value(&items).map_err(app_error!(
+            exception,
+            json,
+            "Failed to serialize included resources"
+        ))?;
+        self.0.insert(Vec::<T>::wrapped_key().into_owned(), value);
+        Ok(self)
+    }
+}
+
+/// A resource whose relationships can be included alongside it.
+pub trait Includable: Sized {
+    /// Application state needed to load relationships, e.g. a database pool.
+    type Context: Sync;
+
+    /// Relationship names accepted in `?include=`.
+    const RELATIONSHIPS: &'static [&'static str];
+
+    /// Load the requested relationships for these resources.
+    fn load_included(
+        resources: &[Self],
+        include: &Include,
+        ctx: &Self::Context,
+    ) -> impl Future<Output = Result<Included, AppError>> + Send;
+}
+
+impl<T> WrappedJson<T>
+where
+    T: Serialize + ResponseKey + Includable,
+{
+    /// Attach the relationships requested with `?include=` under an `included` key.
+    pub async fn include(
+        self,
+        include: &Include,
+        ctx: &T::Context,
+    ) -> Result<WrappedValue, AppError> {
+        include.validate::<T>()?;
+        let included = load(std::slice::from_ref(&self.0), include, ctx).await?;
+        with_included(self.to_value(), included)
+    }
+}
+
+impl<T> WrappedJson<Vec<T>>
+where
+    T: Serialize + ResponseKey + Includable,
+{
+    /// Attach the relationships requested with `?include=` under an `included` key.
+    pub async fn include(
+        self,
+        include: &Include,
+        ctx: &T::Context,
+    ) -> Result<WrappedValue, AppError> {
+        include.validate::<T>()?;
+        let included = load(&self.0, include, ctx).await?;
+        with_included(self.to_value(), included)
+    }
+}
+
+async fn load<T: Includable>(
+    resources: &[T],
+    include: &Include,
+    ctx: &T::Context,
+) -> Result<Option<Included>, AppError> {
+    if include.is_empty() {
+        return Ok(None);
+    }
+    T::load_included(resources, include, ctx).await.map(Some)
+}
+
+fn with_included(
+    envelope: Result<Value, serde_json::Error>,
+    included: Option<Included>,
+) -> Result<WrappedValue, AppError> {
+    let mut envelope =
+        envelope.map_err(app_error!(exception, json, "Failed to serialize response"))?;
+    if let (Some(included), Some(map)) = (included, envelope.as_object_mut()) {
+        map.insert("included".to_string(), Value::Object(included.0));
+    }
+    Ok(WrappedValue(envelope))
+}
@@ -0,0 +1,57 @@
+//! Shared JSON serialization with an optional pretty-printing mode for development.
+
+use std::sync::OnceLock;
+
+use axum::{
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+static PRETTY_JSON: OnceLock<bool> = OnceLock::new();
+
+/// Whether JSON responses are pretty-printed.
+///
+/// Defaults to the `AXTRA_PRETTY_JSON` environment variable (`1` or `true`).
+pub fn pretty_json() -> bool {
+    *PRETTY_JSON.get_or_init(|| {
+        std::env::var("AXTRA_PRETTY_JSON")
+            .map(|value| matches!(value.trim(), "1" | "true"))
+            .unwrap_or(false)
+    })
+}
+
+/// Enable or disable pretty-printed JSON, overriding `AXTRA_PRETTY_JSON`.
+///
+/// Must be called before the first response is rendered; returns false if the
+/// setting was already fixed.
+pub fn set_pretty_json(enabled: bool) -> bool {
+    PRETTY_JSON.set(enabled).is_ok()
+}
+
+/// Serialize a value to JSON bytes, honoring [`pretty_json`].
+pub(crate) fn to_json_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, serde_json::Error> {
+    if pretty_json() {
+        serde_json::to_vec_pretty(value)
+    } else {
+        serde_json::to_vec(value)
+    }
+}
+
+/// Render a value as an `application/json` response, honoring [`pretty_json`].
+pub(crate) fn json_response<T: Serialize + ?Sized>(value: &T) -> Response {
+    match to_json_vec(value) {
+        Ok(bytes) => (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )],
+            bytes,
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to serialize JSON response: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Response types and helpers for Axtra.
+//!
+//! This module provides:
+//! - [`WrappedJson`] and the [`ResponseKey`] trait for predictable API envelopes
+//! - Pretty-printed JSON for development via `AXTRA_PRETTY_JSON` or [`set_pretty_json`]
+//! - [`CamelCaseLayer`] for converting outgoing JSON keys to camelCase
+//! - [`CachePolicy`] for per-response or per-route `Cache-Control` headers
+//! - [`WrappedJson::with_etag`] and [`IfNoneMatch`] for conditional `304` responses
+//! - Sparse fieldsets via the [`Fields`] extractor and [`WrappedJson::fields`]
+//! - JSON:API-style relationship includes via [`Include`] and [`Includable`]
+//! - [`StreamedJson`] for serializing very large lists incrementally
+//! - [`NoContent`] and [`Deleted`] for DELETE/PUT handlers
+//! - Typed Server-Sent Events via [`sse_event`], [`sse_stream`], and [`EventHub`]
+//! - [`ApiVersionLayer`] and the [`ApiVersion`] extractor for version negotiation
+//! - [`Deprecated`] and [`DeprecationLayer`] for `Deprecation`/`Sunset` headers
+//! - TypeScript envelope generation with [`TsEnvelopes`] / [`ts_envelope!`](crate::ts_envelope)
+//!
+//! See crate-level docs for usage examples.
+
+mod cache;
+mod case;
+mod deprecation;
+mod etag;
+mod fields;
+mod include;
+pub(crate) mod json;
+mod sse;
+mod status;
+mod stream;
+mod ts;
+mod version;
+mod wrapped;
+
+pub use cache::{CacheControlled, CachePolicy};
+pub use case::{CamelCaseLayer, camel_case_keys};
+pub use deprecation::{Deprecated, Deprecation, DeprecationLayer, deprecated_hits};
+pub use etag::{ETagged, IfNoneMatch, etag_for};
+pub use fields::{Fields, WrappedValue};
+pub use include::{Includable, Include, Included};
+pub use json::{pretty_json, set_pretty_json};
+pub use sse::{EventHub, sse_event, sse_stream};
+pub use status::{Deleted, NoContent};
+pub use stream::StreamedJson;
+pub use ts::TsEnvelopes;
+pub use version::{ApiVersion, ApiVersionLayer, X_API_VERSION};
+pub use wrapped::{ResponseKey, WrappedJson, WrappedKey};
@@ -0,0 +1,91 @@
+//! Typed Server-Sent Events built from [`ResponseKey`] payloads.
+
+use std::time::Duration;
+
+use axum::response::{
+    IntoResponse,
+    sse::{Event, KeepAlive, Sse},
+};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio_stream::{
+    Stream, StreamExt,
+    wrappers::{BroadcastStream, errors::BroadcastStreamRecvError},
+};
+
+use crate::response::ResponseKey;
+
+/// Build an SSE [`Event`] whose event name is the payload's response key.
+pub fn sse_event<T>(value: &T) -> Result<Event, axum::Error>
+where
+    T: Serialize + ResponseKey,
+{
+    Event::default().event(T::response_key()).json_data(value)
+}
+
+/// Stream typed payloads to a client as Server-Sent Events.
+///
+/// Each item is sent as an event named after its response key, with keep-alive
+/// comments sent every `keep_alive` to hold idle connections open.
+pub fn sse_stream<S, T>(stream: S, keep_alive: Duration) -> impl IntoResponse
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: Serialize + ResponseKey,
+{
+    Sse::new(stream.map(|value| sse_event(&value)))
+        .keep_alive(KeepAlive::new().interval(keep_alive))
+}
+
+/// A broadcast hub fanning typed events out to every connected SSE client.
+///
+/// Clone the hub into your application state, `publish` from anywhere, and return
+/// `hub.subscribe()` from the SSE handler.
+#[derive(Debug, Clone)]
+pub struct EventHub<T> {
+    sender: broadcast::Sender<T>,
+    keep_alive: Duration,
+}
+
+impl<T> EventHub<T>
+where
+    T: Serialize + ResponseKey + Clone + Send + 'static,
+{
+    /// Create a hub buffering up to `capacity` events for slow subscribers.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            keep_alive: Duration::from_secs(15),
+        }
+    }
+
+    pub fn keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = interval;
+        self
+    }
+
+    /// Publish an event to all current subscribers, returning how many received it.
+    pub fn publish(&self, event: T) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Number of connected subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Subscribe a new client, returning an SSE response for the handler.
+    ///
+    /// Subscribers that fall more than `capacity` events behind skip the missed events.
+    pub fn subscribe(&self) -> impl IntoResponse {
+        let stream =
+            BroadcastStream::new(self.sender.subscribe()).filter_map(|result| match result {
+                Ok(event) => Some(event),
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    tracing::warn!("SSE subscriber lagged, skipped {skipped} events");
+                    None
+                }
+            });
+        sse_stream(stream, self.keep_alive)
+    }
+}
@@ -0,0 +1,43 @@
+//! Status-only and confirmation responses for mutating handlers.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::response::{ResponseKey, WrappedJson};
+
+/// `204 No Content` for handlers that succeed without a body.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoContent;
+
+impl IntoResponse for NoContent {
+    fn into_response(self) -> Response {
+        StatusCode::NO_CONTENT.into_response()
+    }
+}
+
+/// Confirms a deletion, rendered as `{ "deleted": { "id": ... } }`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Deleted<I> {
+    pub id: I,
+}
+
+impl<I> Deleted<I> {
+    pub fn new(id: I) -> Self {
+        Self { id }
+    }
+}
+
+impl<I> ResponseKey for Deleted<I> {
+    fn response_key() -> &'static str {
+        "deleted"
+    }
+}
+
+impl<I: Serialize> IntoResponse for Deleted<I> {
+    fn into_response(self) -> Response {
+        WrappedJson(self).into_response()
+    }
+}
@@ -0,0 +1,128 @@
+//! Incremental (chunked) serialization for very large list responses.
+
+use axum::{
+    body::{Body, Bytes},
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::response::{ResponseKey, WrappedJson, WrappedKey};
+
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A list envelope serialized item by item into a chunked response body.
+///
+/// Produces the same `{ "users": [...] }` shape as `WrappedJson<Vec<T>>` without
+/// building the whole JSON document in memory first.
+pub struct StreamedJson<I> {
+    items: I,
+    chunk_size: usize,
+}
+
+impl<I, T> StreamedJson<I>
+where
+    I: Iterator<Item = T> + Send + 'static,
+    T: Serialize + ResponseKey,
+{
+    /// Stream any iterator of items, e.g. one lazily mapping database rows.
+    pub fn new(items: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            items: items.into_iter(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Approximate size in bytes of each body chunk (defaults to 64KiB).
+    pub fn chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+}
+
+impl<T> WrappedJson<Vec<T>>
+where
+    T: Serialize + ResponseKey + Send + 'static,
+{
+    /// Serialize the list incrementally into a chunked body instead of all at once.
+    pub fn streamed(self) -> StreamedJson<std::vec::IntoIter<T>> {
+        StreamedJson::new(self.0)
+    }
+}
+
+impl<I, T> IntoResponse for StreamedJson<I>
+where
+    I: Iterator<Item = T> + Send + 'static,
+    T: Serialize + ResponseKey,
+{
+    fn into_response(self) -> Response {
+        let chunks = Chunks {
+            items: self.items,
+            chunk_size: self.chunk_size,
+            started: false,
+            first: true,
+            done: false,
+        };
+
+        let mut res = Body::from_stream(tokio_stream::iter(chunks)).into_response();
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        res
+    }
+}
+
+// Iterator yielding the serialized envelope in roughly `chunk_size` pieces
+struct Chunks<I> {
+    items: I,
+    chunk_size: usize,
+    started: bool,
+    first: bool,
+    done: bool,
+}
+
+impl<I, T> Iterator for Chunks<I>
+where
+    I: Iterator<Item = T>,
+    T: Serialize + ResponseKey,
+{
+    type Item = Result<Bytes, serde_json::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut buf = Vec::with_capacity(self.chunk_size);
+        if !self.started {
+            self.started = true;
+            buf.push(b'{');
+            if let Err(e) = serde_json::to_writer(&mut buf, &Vec::<T>::wrapped_key()) {
+                self.done = true;
+                return Some(Err(e));
+            }
+            buf.extend_from_slice(b":[");
+        }
+
+        while buf.len() < self.chunk_size {
+            let Some(item) = self.items.next() else {
+                buf.extend_from_slice(b"]}");
+                self.done = true;
+                break;
+            };
+            if !self.first {
+                buf.push(b',');
+            }
+            self.first = false;
+            if let Err(e) = serde_json::to_writer(&mut buf, &item) {
+                // Headers are already sent; abort the body so the client sees a broken response
+                tracing::error!("Failed to serialize streamed JSON item: {e}");
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+
+        Some(Ok(Bytes::from(buf)))
+    }
+}
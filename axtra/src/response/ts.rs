@@ -0,0 +1,100 @@
+//! TypeScript generation for `WrappedJson` envelope shapes.
+
+use std::{fmt::Write, fs, path::Path};
+
+use ts_rs::{ExportError, TS};
+
+use crate::response::{ResponseKey, WrappedKey};
+
+type ExportFn = fn(&Path) -> Result<(), ExportError>;
+
+/// Collects `WrappedJson` envelope declarations and writes them to `envelopes.ts`.
+///
+/// For each registered type `User` this generates:
+///
+/// ```typescript
+/// export type UserResponse = { user: User, };
+/// export type UserListResponse = { users: Array<User>, };
+/// ```
+///
+/// See [`ts_envelope!`](crate::ts_envelope) for a shorthand.
+#[derive(Debug, Default)]
+pub struct TsEnvelopes {
+    imports: Vec<String>,
+    declarations: Vec<String>,
+    exports: Vec<ExportFn>,
+}
+
+impl TsEnvelopes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the single and list envelopes for `T`.
+    pub fn add<T>(mut self) -> Self
+    where
+        T: TS + ResponseKey + 'static,
+    {
+        let ident = T::ident();
+        let name = T::name();
+
+        let item = match T::output_path() {
+            Some(path) => {
+                let import = path.with_extension("").to_string_lossy().replace('\\', "/");
+                self.imports
+                    .push(format!("import type {{ {ident} }} from \"./{import}\";"));
+                self.exports.push(|dir| T::export_all_to(dir));
+                name
+            }
+            None => T::inline(),
+        };
+
+        self.declarations.push(format!(
+            "export type {ident}Response = {{ {}: {item}, }};",
+            T::wrapped_key()
+        ));
+        self.declarations.push(format!(
+            "export type {ident}ListResponse = {{ {}: Array<{item}>, }};",
+            Vec::<T>::wrapped_key()
+        ));
+        self
+    }
+
+    /// Render the envelope declarations without writing them.
+    pub fn to_ts(&self) -> String {
+        let mut out =
+            String::from("// This file was generated by axtra. Do not edit this file manually.\n");
+        for import in &self.imports {
+            let _ = writeln!(out, "{import}");
+        }
+        out.push('\n');
+        for declaration in &self.declarations {
+            let _ = writeln!(out, "{declaration}\n");
+        }
+        out
+    }
+
+    /// Export the registered types and their envelopes into `out_dir`.
+    ///
+    /// Inner types are exported with ts-rs as usual; the envelopes are written to
+    /// `out_dir/envelopes.ts`.
+    pub fn export_all_to(&self, out_dir: impl AsRef<Path>) -> Result<(), ExportError> {
+        let out_dir = out_dir.as_ref();
+        fs::create_dir_all(out_dir)?;
+        for export in &self.exports {
+            export(out_dir)?;
+        }
+        fs::write(out_dir.join("envelopes.ts"), self.to_ts())?;
+        Ok(())
+    }
+}
+
+/// Build a [`TsEnvelopes`] for the given response types.
+///
+/// Usage: `ts_envelope!(User, Account).export_all_to("types")?`
+#[macro_export]
+macro_rules! ts_envelope {
+    ($($ty:ty),* $(,)?) => {
+        $crate::response::TsEnvelopes::new()$(.add::<$ty>())*
+    };
+}
@@ -0,0 +1,83 @@
+//! API version negotiation between clients and the server.
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderName, HeaderValue, header, request::Parts},
+};
+use tower::Layer;
+use tower_http::set_header::SetResponseHeader;
+
+/// Header carrying the API version on requests and responses.
+pub static X_API_VERSION: HeaderName = HeaderName::from_static("x-api-version");
+
+/// Layer stamping every response with the server's `X-Api-Version`.
+#[derive(Debug, Clone)]
+pub struct ApiVersionLayer {
+    version: HeaderValue,
+}
+
+impl ApiVersionLayer {
+    /// Panics if `version` is not a valid header value.
+    pub fn new(version: impl AsRef<str>) -> Self {
+        Self {
+            version: HeaderValue::from_str(version.as_ref()).expect("invalid API version header"),
+        }
+    }
+}
+
+impl<S> Layer<S> for ApiVersionLayer {
+    type Service = SetResponseHeader<S, HeaderValue>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SetResponseHeader::overriding(inner, X_API_VERSION.clone(), self.version.clone())
+    }
+}
+
+/// The API version requested by the client, if any.
+///
+/// Read from the `X-Api-Version` header, falling back to a `version` parameter on
+/// the `Accept` header (`application/vnd.app+json; version=2`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApiVersion(pub Option<String>);
+
+impl ApiVersion {
+    pub fn as_deref(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+impl<S> FromRequestParts<S> for ApiVersion
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header_version = parts
+            .headers
+            .get(&X_API_VERSION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+
+        let accept_version = || {
+            parts
+                .headers
+                .get(header::ACCEPT)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|accept| {
+                    accept
+                        .split([',', ';'])
+                        .filter_map(|param| param.trim().strip_prefix("version="))
+                        .map(|version| version.trim_matches('"'))
+                        .next()
+                })
+        };
+
+        Ok(Self(
+            header_version.or_else(accept_version).map(str::to_string),
+        ))
+    }
+}
@@ -0,0 +1,121 @@
+use std::{borrow::Cow, sync::Arc};
+
+use axum::response::{IntoResponse, Response};
+use inflector::string::pluralize::to_plural;
+use serde::{Serialize, Serializer, ser::SerializeMap};
+use serde_json::Value;
+
+use crate::response::json::{json_response, to_json_vec};
+
+// Trait for getting the response key
+pub trait ResponseKey {
+    fn response_key() -> &'static str;
+}
+
+// Shared and borrowed data uses the key of the type it points to
+impl<T: ResponseKey + ?Sized> ResponseKey for &T {
+    fn response_key() -> &'static str {
+        T::response_key()
+    }
+}
+
+impl<T: ResponseKey + ?Sized> ResponseKey for Box<T> {
+    fn response_key() -> &'static str {
+        T::response_key()
+    }
+}
+
+impl<T: ResponseKey + ?Sized> ResponseKey for Arc<T> {
+    fn response_key() -> &'static str {
+        T::response_key()
+    }
+}
+
+impl<T: ResponseKey + ToOwned + ?Sized> ResponseKey for Cow<'_, T> {
+    fn response_key() -> &'static str {
+        T::response_key()
+    }
+}
+
+/// Resolves the envelope key for a wrapped payload.
+///
+/// Implemented for every [`ResponseKey`] type, and for lists of them (`Vec<T>`, `&[T]`,
+/// `Arc<[T]>`, ...) using the pluralized key.
+pub trait WrappedKey {
+    fn wrapped_key() -> Cow<'static, str>;
+}
+
+impl<T: ResponseKey> WrappedKey for T {
+    fn wrapped_key() -> Cow<'static, str> {
+        Cow::Borrowed(T::response_key())
+    }
+}
+
+macro_rules! impl_list_wrapped_key {
+    ($($list:ty),*) => {
+        $(
+            impl<T: ResponseKey> WrappedKey for $list {
+                fn wrapped_key() -> Cow<'static, str> {
+                    Cow::Owned(to_plural(T::response_key()))
+                }
+            }
+        )*
+    };
+}
+
+impl_list_wrapped_key!(Vec<T>, &[T], &Vec<T>, Box<[T]>, Arc<[T]>, Arc<Vec<T>>);
+
+// Single-entry JSON object `{ key: value }` serialized without an intermediate map
+pub(crate) struct Envelope<'a, T> {
+    pub(crate) key: &'a str,
+    pub(crate) value: &'a T,
+}
+
+impl<T: Serialize> Serialize for Envelope<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.key, self.value)?;
+        map.end()
+    }
+}
+
+// Custom response type that will handle the wrapping
+pub struct WrappedJson<T>(pub T);
+
+impl<T> WrappedJson<T>
+where
+    T: Serialize + WrappedKey,
+{
+    /// Serialize the wrapped payload into its JSON envelope bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        let key = T::wrapped_key();
+        to_json_vec(&Envelope {
+            key: &key,
+            value: &self.0,
+        })
+    }
+
+    /// Serialize the wrapped payload into a JSON envelope value.
+    pub fn to_value(&self) -> Result<Value, serde_json::Error> {
+        let mut envelope = serde_json::Map::with_capacity(1);
+        envelope.insert(
+            T::wrapped_key().into_owned(),
+            serde_json::to_value(&self.0)?,
+        );
+        Ok(Value::Object(envelope))
+    }
+}
+
+// Implementation to convert our types (and lists of them) into responses
+impl<T> IntoResponse for WrappedJson<T>
+where
+    T: Serialize + WrappedKey,
+{
+    fn into_response(self) -> Response {
+        let key = T::wrapped_key();
+        json_response(&Envelope {
+            key: &key,
+            value: &self.0,
+        })
+    }
+}
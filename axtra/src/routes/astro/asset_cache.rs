@@ -0,0 +1,233 @@
+//! In-memory cache of small, frequently served static files.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use axum::{
+    body::{Body, Bytes},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+// Response headers kept with a cached file
+const CACHED_HEADERS: [header::HeaderName; 4] = [
+    header::ACCEPT_RANGES,
+    header::CONTENT_TYPE,
+    header::CONTENT_ENCODING,
+    header::LAST_MODIFIED,
+];
+
+/// Keep small static files in memory so hot assets skip the filesystem read.
+///
+/// Disabled unless set with [`StaticConfig::asset_cache`](crate::routes::astro::StaticConfig::asset_cache).
+/// Entries are keyed by file and accepted precompressed encodings, evicted least recently
+/// used first, and revalidated against the file's size and modification time on every hit,
+/// so a redeploy is picked up without a restart.
+///
+/// ```rust, ignore
+/// let config = StaticConfig::default().asset_cache(AssetCache::new().max_entries(128));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetCache {
+    max_entries: usize,
+    max_file_size: u64,
+    extensions: Vec<String>,
+}
+
+impl Default for AssetCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AssetCache {
+    /// Up to 256 HTML, CSS, JS, JSON, SVG and text files of at most 64 KiB.
+    pub fn new() -> Self {
+        Self {
+            max_entries: 256,
+            max_file_size: 64 * 1024,
+            extensions: ["html", "css", "js", "mjs", "json", "svg", "txt", "xml"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Largest file (as served, after precompression) kept in memory, in bytes.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    /// File extensions eligible for caching, replacing the defaults.
+    pub fn extensions<I, E>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<String>,
+    {
+        self.extensions = extensions
+            .into_iter()
+            .map(|extension| extension.into().trim_start_matches('.').to_string())
+            .collect();
+        self
+    }
+}
+
+/// A cache key: the file and the precompressed encodings the client accepts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Key {
+    file: PathBuf,
+    encodings: String,
+}
+
+#[derive(Debug)]
+struct Entry {
+    body: Bytes,
+    headers: HeaderMap,
+    etag: String,
+    last_used: u64,
+}
+
+/// The runtime cache behind one static router.
+#[derive(Debug)]
+pub(crate) struct Store {
+    settings: AssetCache,
+    entries: Mutex<HashMap<Key, Entry>>,
+    clock: AtomicU64,
+}
+
+impl Store {
+    pub(crate) fn new(settings: AssetCache) -> Self {
+        Self {
+            settings,
+            entries: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// The key for a request, if its file may be cached.
+    pub(crate) fn key(
+        &self,
+        file: &Path,
+        request_headers: &HeaderMap,
+        precompressed: &[(&str, &str)],
+    ) -> Option<Key> {
+        if request_headers.contains_key(header::RANGE) {
+            return None;
+        }
+        let extension = file.extension()?.to_str()?;
+        self.settings
+            .extensions
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(extension))
+            .then(|| Key {
+                file: file.to_path_buf(),
+                encodings: precompressed
+                    .iter()
+                    .map(|(encoding, _)| *encoding)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            })
+    }
+
+    /// The cached response for `key`, if it's still current.
+    ///
+    /// `etag` computes the file's current ETag for the cached content encoding.
+    pub(crate) fn get(
+        &self,
+        key: &Key,
+        etag: impl FnOnce(Option<&str>) -> Option<String>,
+    ) -> Option<(Response, String)> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get_mut(key)?;
+        let encoding = entry
+            .headers
+            .get(header::CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok());
+        if etag(encoding).as_deref() != Some(entry.etag.as_str()) {
+            entries.remove(key);
+            return None;
+        }
+
+        entry.last_used = self.clock.fetch_add(1, Ordering::Relaxed);
+        let mut res = Response::new(Body::from(entry.body.clone()));
+        res.headers_mut().extend(entry.headers.clone());
+        Some((res, entry.etag.clone()))
+    }
+
+    /// Buffer a successful response small enough to cache and store it under `key`.
+    pub(crate) async fn fill(&self, key: Key, etag: &str, res: Response) -> Response {
+        let size = res
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        let cacheable = res.status() == StatusCode::OK
+            && size.is_some_and(|size| size <= self.settings.max_file_size)
+            && self.settings.max_entries > 0;
+        if !cacheable {
+            return res;
+        }
+
+        let (parts, body) = res.into_parts();
+        let limit = usize::try_from(self.settings.max_file_size).unwrap_or(usize::MAX);
+        let body = match axum::body::to_bytes(body, limit).await {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("Failed to buffer static file for the asset cache: {e}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        let mut headers = HeaderMap::new();
+        for name in CACHED_HEADERS {
+            if let Some(value) = parts.headers.get(&name) {
+                headers.insert(name, value.clone());
+            }
+        }
+        self.insert(
+            key,
+            Entry {
+                body: body.clone(),
+                headers,
+                etag: etag.to_string(),
+                last_used: self.clock.fetch_add(1, Ordering::Relaxed),
+            },
+        );
+        Response::from_parts(parts, Body::from(body))
+    }
+
+    /// Drop every cached file.
+    #[cfg(feature = "dev")]
+    pub(crate) fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    fn insert(&self, key: Key, entry: Entry) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if !entries.contains_key(&key) && entries.len() >= self.settings.max_entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, entry);
+    }
+}
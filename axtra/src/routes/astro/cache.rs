@@ -0,0 +1,114 @@
+//! `Cache-Control` rules for static files.
+
+use axum::{http::header, response::Response};
+use http::StatusCode;
+
+use crate::response::CachePolicy;
+
+// One year, for assets that never change under the same name
+const ONE_YEAR: u64 = 31_536_000;
+// One month
+const ONE_MONTH: u64 = 2_628_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Matcher {
+    PathPrefix(String),
+    PathContains(String),
+    Extension(String),
+}
+
+impl Matcher {
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            Matcher::PathPrefix(prefix) => path.starts_with(prefix.as_str()),
+            Matcher::PathContains(part) => path.contains(part.as_str()),
+            Matcher::Extension(extension) => path
+                .rsplit_once('.')
+                .is_some_and(|(_, ext)| ext.eq_ignore_ascii_case(extension)),
+        }
+    }
+}
+
+/// Which `Cache-Control` policy static files are served with, by request path.
+///
+/// Rules are checked in the order they were added and the first match wins; paths no
+/// rule matches get the default policy, or no header at all. `Default::default()` gives
+/// the built-in rules, caching Astro's `/_static/` for a year and `/_astro/` for a month.
+///
+/// ```rust, ignore
+/// let rules = CacheRules::new()
+///     .path_prefix("/_astro/", CachePolicy::public(31_536_000).immutable())
+///     .extension("woff2", CachePolicy::public(31_536_000))
+///     .default(CachePolicy::public(0).no_cache());
+///
+/// let config = StaticConfig::default().cache_rules(rules);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheRules {
+    rules: Vec<(Matcher, CachePolicy)>,
+    default: Option<CachePolicy>,
+}
+
+impl Default for CacheRules {
+    fn default() -> Self {
+        Self::new()
+            .path_contains("/_static/", CachePolicy::public(ONE_YEAR))
+            .path_contains("/_astro/", CachePolicy::public(ONE_MONTH))
+    }
+}
+
+impl CacheRules {
+    /// No rules: files are served without `Cache-Control`.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Paths starting with `prefix`, e.g. `/_astro/`.
+    pub fn path_prefix(mut self, prefix: impl Into<String>, policy: CachePolicy) -> Self {
+        self.rules
+            .push((Matcher::PathPrefix(prefix.into()), policy));
+        self
+    }
+
+    /// Paths containing `part` anywhere, e.g. `/_astro/` below a base path.
+    pub fn path_contains(mut self, part: impl Into<String>, policy: CachePolicy) -> Self {
+        self.rules
+            .push((Matcher::PathContains(part.into()), policy));
+        self
+    }
+
+    /// Files with the extension `extension` (case-insensitive, without the dot).
+    pub fn extension(mut self, extension: impl Into<String>, policy: CachePolicy) -> Self {
+        let extension = extension.into().trim_start_matches('.').to_string();
+        self.rules.push((Matcher::Extension(extension), policy));
+        self
+    }
+
+    /// Policy for paths no rule matches.
+    pub fn default(mut self, policy: CachePolicy) -> Self {
+        self.default = Some(policy);
+        self
+    }
+
+    /// The policy for a request path, if any.
+    pub fn policy_for(&self, path: &str) -> Option<&CachePolicy> {
+        self.rules
+            .iter()
+            .find(|(matcher, _)| matcher.matches(path))
+            .map(|(_, policy)| policy)
+            .or(self.default.as_ref())
+    }
+
+    pub(crate) fn apply(&self, path: &str, res: &mut Response) {
+        if res.status() != StatusCode::OK {
+            return;
+        }
+        if let Some(policy) = self.policy_for(path) {
+            res.headers_mut()
+                .insert(header::CACHE_CONTROL, policy.header_value());
+        }
+    }
+}
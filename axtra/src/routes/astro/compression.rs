@@ -0,0 +1,249 @@
+//! Response compression settings for static routes.
+
+use std::sync::Arc;
+
+use axum::body::HttpBody;
+use http::{HeaderMap, header};
+use tower_http::{
+    compression::{
+        CompressionLayer, CompressionLevel, Predicate,
+        predicate::{NotForContentType, SizeAbove},
+    },
+    services::{ServeDir, ServeFile},
+};
+
+// Already-compressed formats, plus media and PDFs, which keep `Accept-Ranges` uncompressed
+// so browsers can seek in them
+const DEFAULT_EXCLUDED: [&str; 8] = [
+    "image/",
+    "video/",
+    "audio/",
+    "font/woff",
+    "application/font-woff",
+    "application/pdf",
+    "application/zip",
+    "application/gzip",
+];
+
+/// Which encodings static routes compress responses with, and which precompressed
+/// siblings (`app.js.br`, `app.js.gz`, ...) they serve when the client accepts them.
+///
+/// Everything is enabled by default. Precompressed files are preferred over compressing
+/// on the fly, so run your build's compression step to skip the work per request.
+/// Responses under 32 bytes and already-compressed types (images other than SVG, media,
+/// `woff`/`woff2` fonts, PDFs and archives) are sent as-is.
+///
+/// ```rust, ignore
+/// let config = StaticConfig::default().compression(
+///     Compression::default()
+///         .zstd(false)
+///         .level(CompressionLevel::Fastest)
+///         .min_size(1024)
+///         .exclude("application/wasm"),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compression {
+    gzip: bool,
+    br: bool,
+    zstd: bool,
+    precompressed_gzip: bool,
+    precompressed_br: bool,
+    precompressed_zstd: bool,
+    level: CompressionLevel,
+    min_size: u16,
+    excluded: Vec<String>,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            gzip: true,
+            br: true,
+            zstd: true,
+            precompressed_gzip: true,
+            precompressed_br: true,
+            precompressed_zstd: true,
+            level: CompressionLevel::Default,
+            min_size: 32,
+            excluded: DEFAULT_EXCLUDED.map(str::to_string).to_vec(),
+        }
+    }
+}
+
+impl Compression {
+    /// No compression and no precompressed files.
+    pub fn none() -> Self {
+        Self {
+            gzip: false,
+            br: false,
+            zstd: false,
+            precompressed_gzip: false,
+            precompressed_br: false,
+            precompressed_zstd: false,
+            ..Self::default()
+        }
+    }
+
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    pub fn br(mut self, enabled: bool) -> Self {
+        self.br = enabled;
+        self
+    }
+
+    pub fn zstd(mut self, enabled: bool) -> Self {
+        self.zstd = enabled;
+        self
+    }
+
+    /// How hard to compress on the fly (defaults to each encoding's default level).
+    pub fn level(mut self, level: CompressionLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Send responses smaller than `bytes` uncompressed (defaults to 32).
+    pub fn min_size(mut self, bytes: u16) -> Self {
+        self.min_size = bytes;
+        self
+    }
+
+    /// Never compress responses whose `Content-Type` starts with `content_type`,
+    /// e.g. `font/` or `application/octet-stream`.
+    pub fn exclude(mut self, content_type: impl Into<String>) -> Self {
+        self.excluded.push(content_type.into());
+        self
+    }
+
+    /// Compress every content type, including images, media and fonts.
+    pub fn without_exclusions(mut self) -> Self {
+        self.excluded.clear();
+        self
+    }
+
+    /// Serve `{file}.gz` when it exists and the client accepts gzip.
+    pub fn precompressed_gzip(mut self, enabled: bool) -> Self {
+        self.precompressed_gzip = enabled;
+        self
+    }
+
+    /// Serve `{file}.br` when it exists and the client accepts brotli.
+    pub fn precompressed_br(mut self, enabled: bool) -> Self {
+        self.precompressed_br = enabled;
+        self
+    }
+
+    /// Serve `{file}.zst` when it exists and the client accepts zstd.
+    pub fn precompressed_zstd(mut self, enabled: bool) -> Self {
+        self.precompressed_zstd = enabled;
+        self
+    }
+
+    pub(crate) fn layer(&self) -> CompressionLayer<StaticPredicate> {
+        let predicate = StaticPredicate {
+            min_size: SizeAbove::new(self.min_size),
+            excluded: self.excluded.clone().into(),
+        };
+        CompressionLayer::new()
+            .gzip(self.gzip)
+            .br(self.br)
+            .zstd(self.zstd)
+            .quality(self.level)
+            .compress_when(predicate)
+    }
+
+    pub(crate) fn serve_dir<F>(&self, mut serve_dir: ServeDir<F>) -> ServeDir<F> {
+        if self.precompressed_br {
+            serve_dir = serve_dir.precompressed_br();
+        }
+        if self.precompressed_zstd {
+            serve_dir = serve_dir.precompressed_zstd();
+        }
+        if self.precompressed_gzip {
+            serve_dir = serve_dir.precompressed_gzip();
+        }
+        serve_dir
+    }
+
+    pub(crate) fn serve_file(&self, mut serve_file: ServeFile) -> ServeFile {
+        if self.precompressed_br {
+            serve_file = serve_file.precompressed_br();
+        }
+        if self.precompressed_zstd {
+            serve_file = serve_file.precompressed_zstd();
+        }
+        if self.precompressed_gzip {
+            serve_file = serve_file.precompressed_gzip();
+        }
+        serve_file
+    }
+
+    /// Precompressed encodings the client accepts, best first, as `(content-encoding, extension)`.
+    pub(crate) fn precompressed_for(
+        &self,
+        headers: &HeaderMap,
+    ) -> Vec<(&'static str, &'static str)> {
+        [
+            (self.precompressed_br, "br", "br"),
+            (self.precompressed_zstd, "zstd", "zst"),
+            (self.precompressed_gzip, "gzip", "gz"),
+        ]
+        .into_iter()
+        .filter(|(enabled, encoding, _)| *enabled && accepts(headers, encoding))
+        .map(|(_, encoding, extension)| (encoding, extension))
+        .collect()
+    }
+}
+
+/// Which responses the compression layer compresses.
+#[derive(Debug, Clone)]
+pub(crate) struct StaticPredicate {
+    min_size: SizeAbove,
+    excluded: Arc<[String]>,
+}
+
+impl Predicate for StaticPredicate {
+    fn should_compress<B>(&self, response: &http::Response<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        // SVG is text, so it's compressed even though other images aren't
+        let excluded = self.excluded.iter().any(|excluded| {
+            content_type.starts_with(excluded.as_str())
+                && (excluded.starts_with("image/svg") || !content_type.starts_with("image/svg+xml"))
+        });
+        !excluded
+            && self.min_size.should_compress(response)
+            && NotForContentType::GRPC.should_compress(response)
+            && NotForContentType::SSE.should_compress(response)
+    }
+}
+
+// Whether Accept-Encoding lists `encoding` without `q=0`
+fn accepts(headers: &HeaderMap, encoding: &str) -> bool {
+    headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            let rejected = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            name.eq_ignore_ascii_case(encoding) && !rejected
+        })
+}
@@ -0,0 +1,91 @@
+//! ETags and `304 Not Modified` answers for static files.
+
+use std::{
+    fs::Metadata,
+    path::{Component, Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::Response,
+};
+
+use percent_encoding::percent_decode_str;
+
+use crate::response::IfNoneMatch;
+
+// Headers a 304 keeps from the full response
+const NOT_MODIFIED_HEADERS: [header::HeaderName; 5] = [
+    header::CACHE_CONTROL,
+    header::ETAG,
+    header::EXPIRES,
+    header::LAST_MODIFIED,
+    header::VARY,
+];
+
+/// The file under `root` a request path is served from, with `index` for directories.
+pub(crate) fn resolve(root: &Path, uri_path: &str, index: &str) -> Option<PathBuf> {
+    let decoded = percent_decode_str(uri_path).decode_utf8().ok()?;
+    let mut file = root.to_path_buf();
+    for component in Path::new(decoded.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => file.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if decoded.ends_with('/') {
+        file.push(index);
+    }
+    Some(file)
+}
+
+/// A strong ETag from a file's size and modification time, distinct per content encoding.
+pub(crate) fn file_etag(metadata: &Metadata, encoding: Option<&HeaderValue>) -> Option<String> {
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let encoding = encoding
+        .and_then(|value| value.to_str().ok())
+        .map(|encoding| format!("-{encoding}"))
+        .unwrap_or_default();
+    Some(format!(
+        "\"{:x}-{:x}{encoding}\"",
+        metadata.len(),
+        modified.as_nanos()
+    ))
+}
+
+/// Set `etag` on a successful (or partial) response, answering with `304` when the
+/// request's `If-None-Match` already has it.
+pub(crate) fn respond(request_headers: &HeaderMap, etag: &str, mut res: Response) -> Response {
+    if !matches!(
+        res.status(),
+        StatusCode::OK | StatusCode::PARTIAL_CONTENT | StatusCode::NOT_MODIFIED
+    ) {
+        return res;
+    }
+    let Ok(value) = HeaderValue::from_str(etag) else {
+        return res;
+    };
+    res.headers_mut().insert(header::ETAG, value);
+
+    let if_none_match = IfNoneMatch(
+        request_headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    );
+    if !if_none_match.matches(etag) {
+        return res;
+    }
+
+    let mut not_modified = Response::new(Body::empty());
+    *not_modified.status_mut() = StatusCode::NOT_MODIFIED;
+    for name in NOT_MODIFIED_HEADERS {
+        if let Some(value) = res.headers().get(&name) {
+            not_modified.headers_mut().insert(name, value.clone());
+        }
+    }
+    not_modified
+}
@@ -0,0 +1,361 @@
+//! Static asset directory configuration.
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use http::StatusCode;
+#[cfg(feature = "embed")]
+use include_dir::Dir;
+
+#[cfg(feature = "ssr")]
+use crate::routes::astro::SsrProxy;
+use crate::routes::astro::{
+    AccessGuard, AssetCache, CacheRules, Compression, DynamicRoute, EnvInjection, ErrorPages,
+    HtmlMinify, Locales, NotFoundFormat, PathNormalization, PathSecurity, conditional, hardening,
+    minify,
+};
+#[cfg(feature = "dev")]
+use crate::routes::astro::{asset_cache, watch};
+
+/// Where the files are read from.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Source {
+    Directory(PathBuf),
+    #[cfg(feature = "embed")]
+    Embedded(&'static Dir<'static>),
+}
+
+/// Where static and SPA routes read their files from.
+///
+/// Defaults to `./dist` with `index.html` and [`ErrorPages`]' `404.html`, matching an Astro build.
+///
+/// ```rust, ignore
+/// let config = StaticConfig::new("/srv/app/public").not_found_page("errors/404.html");
+/// let app = Router::new().merge(serve_static_files_with(&config));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticConfig {
+    source: Source,
+    index: String,
+    error_pages: ErrorPages,
+    not_found: NotFoundFormat,
+    compression: Compression,
+    cache_rules: CacheRules,
+    asset_cache: Option<AssetCache>,
+    env: Option<EnvInjection>,
+    locales: Option<Locales>,
+    normalization: Option<PathNormalization>,
+    guard: Option<AccessGuard>,
+    security: PathSecurity,
+    dynamic_routes: Vec<DynamicRoute>,
+    minify: Option<Arc<minify::Store>>,
+    #[cfg(feature = "dev")]
+    watcher: Option<Arc<watch::Watcher>>,
+    #[cfg(feature = "ssr")]
+    ssr: Option<SsrProxy>,
+}
+
+impl Default for StaticConfig {
+    fn default() -> Self {
+        Self::new("./dist")
+    }
+}
+
+impl StaticConfig {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            source: Source::Directory(root.into()),
+            index: "index.html".to_string(),
+            error_pages: ErrorPages::default(),
+            not_found: NotFoundFormat::default(),
+            compression: Compression::default(),
+            cache_rules: Default::default(),
+            asset_cache: None,
+            env: None,
+            locales: None,
+            normalization: None,
+            guard: None,
+            security: PathSecurity::default(),
+            dynamic_routes: Vec::new(),
+            minify: None,
+            #[cfg(feature = "dev")]
+            watcher: None,
+            #[cfg(feature = "ssr")]
+            ssr: None,
+        }
+    }
+
+    /// Serve files compiled into the binary with `include_dir!` instead of the filesystem.
+    ///
+    /// ```rust, ignore
+    /// static DIST: Dir = include_dir::include_dir!("$CARGO_MANIFEST_DIR/dist");
+    ///
+    /// let config = StaticConfig::embedded(&DIST);
+    /// ```
+    #[cfg(feature = "embed")]
+    pub fn embedded(dir: &'static Dir<'static>) -> Self {
+        Self {
+            source: Source::Embedded(dir),
+            ..Self::default()
+        }
+    }
+
+    /// Root from the `AXTRA_STATIC_ROOT` environment variable, falling back to `./dist`.
+    pub fn from_env() -> Self {
+        std::env::var("AXTRA_STATIC_ROOT")
+            .map(Self::new)
+            .unwrap_or_default()
+    }
+
+    /// File served for directories and SPA routes (defaults to `index.html`).
+    pub fn index(mut self, index: impl Into<String>) -> Self {
+        self.index = index.into();
+        self
+    }
+
+    /// Error pages for missing files and `AppError`s, relative to the root.
+    pub fn error_pages(mut self, pages: ErrorPages) -> Self {
+        self.error_pages = pages;
+        self
+    }
+
+    /// Page served for missing files, relative to the root (defaults to `404.html`).
+    pub fn not_found_page(mut self, page: impl Into<String>) -> Self {
+        self.error_pages = self.error_pages.not_found(page);
+        self
+    }
+
+    /// Answer missing files with an empty 404 instead of a page.
+    pub fn without_not_found_page(mut self) -> Self {
+        self.error_pages = self.error_pages.without(StatusCode::NOT_FOUND);
+        self
+    }
+
+    /// How missing files are answered for API clients (defaults to JSON under `/api/` and
+    /// for requests that don't accept HTML).
+    pub fn not_found_format(mut self, format: NotFoundFormat) -> Self {
+        self.not_found = format;
+        self
+    }
+
+    /// Compression and precompressed file settings (defaults to gzip, brotli and zstd).
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// `Cache-Control` rules for static files (defaults to caching `/_static/` and `/_astro/`).
+    pub fn cache_rules(mut self, rules: CacheRules) -> Self {
+        self.cache_rules = rules;
+        self
+    }
+
+    /// Keep small, frequently served files in memory (filesystem roots only).
+    pub fn asset_cache(mut self, cache: AssetCache) -> Self {
+        self.asset_cache = Some(cache);
+        self
+    }
+
+    /// Inject runtime values into SPA index pages served by `serve_spa_with`.
+    pub fn inject_env(mut self, env: EnvInjection) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// Route `/{locale}/...` i18n builds by cookie and `Accept-Language`.
+    pub fn locales(mut self, locales: Locales) -> Self {
+        self.locales = Some(locales);
+        self
+    }
+
+    /// Redirect to canonical paths (trailing slashes, duplicate slashes, case).
+    pub fn normalize(mut self, normalization: PathNormalization) -> Self {
+        self.normalization = Some(normalization);
+        self
+    }
+
+    /// Serve the pages prerendered for a dynamic route, e.g. `/blog/:slug` from
+    /// `blog/[slug]/index.html`. Routes are tried in the order they're added.
+    pub fn dynamic_route(mut self, route: DynamicRoute) -> Self {
+        self.dynamic_routes.push(route);
+        self
+    }
+
+    /// Minify HTML pages as they're served, keeping the results in memory.
+    pub fn minify_html(mut self, settings: HtmlMinify) -> Self {
+        self.minify = Some(Arc::new(minify::Store::new(settings)));
+        self
+    }
+
+    /// Poll the root every `interval` and clear the asset cache, minified pages and the
+    /// registered [`AssetManifest`](crate::routes::astro::AssetManifest) when files change,
+    /// so `astro build --watch` output is served without a restart. Meant for development.
+    #[cfg(feature = "dev")]
+    pub fn watch(mut self, interval: std::time::Duration) -> Self {
+        self.watcher = Some(Arc::new(watch::Watcher::new(interval)));
+        self
+    }
+
+    /// Send requests for missing files, and every method other than `GET` and `HEAD`, to an
+    /// Astro SSR server instead of answering them with the not-found page.
+    #[cfg(feature = "ssr")]
+    pub fn ssr(mut self, proxy: SsrProxy) -> Self {
+        self.ssr = Some(proxy);
+        self
+    }
+
+    /// Require basic auth or an unlock cookie for every route, e.g. on staging previews.
+    pub fn protect(mut self, guard: AccessGuard) -> Self {
+        self.guard = Some(guard);
+        self
+    }
+
+    /// Traversal and symlink protections (defaults to rejecting both).
+    pub fn path_security(mut self, security: PathSecurity) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// The filesystem root, or `None` for embedded assets.
+    pub fn root(&self) -> Option<&Path> {
+        match &self.source {
+            Source::Directory(root) => Some(root),
+            #[cfg(feature = "embed")]
+            Source::Embedded(_) => None,
+        }
+    }
+
+    pub(crate) fn source(&self) -> &Source {
+        &self.source
+    }
+
+    pub(crate) fn index_name(&self) -> &str {
+        &self.index
+    }
+
+    pub(crate) fn compression_settings(&self) -> &Compression {
+        &self.compression
+    }
+
+    pub(crate) fn cache_settings(&self) -> &CacheRules {
+        &self.cache_rules
+    }
+
+    pub(crate) fn asset_cache_settings(&self) -> Option<&AssetCache> {
+        self.asset_cache.as_ref()
+    }
+
+    pub(crate) fn env_injection(&self) -> Option<&EnvInjection> {
+        self.env.as_ref()
+    }
+
+    pub(crate) fn locale_settings(&self) -> Option<&Locales> {
+        self.locales.as_ref()
+    }
+
+    pub(crate) fn normalization_settings(&self) -> Option<&PathNormalization> {
+        self.normalization.as_ref()
+    }
+
+    pub(crate) fn access_guard(&self) -> Option<&AccessGuard> {
+        self.guard.as_ref()
+    }
+
+    pub(crate) fn path_security_settings(&self) -> &PathSecurity {
+        &self.security
+    }
+
+    pub(crate) fn dynamic_route_settings(&self) -> &[DynamicRoute] {
+        &self.dynamic_routes
+    }
+
+    #[cfg(feature = "ssr")]
+    pub(crate) fn ssr_proxy(&self) -> Option<&SsrProxy> {
+        self.ssr.as_ref()
+    }
+
+    pub(crate) fn html_minifier(&self) -> Option<&minify::Store> {
+        self.minify.as_deref()
+    }
+
+    /// Clear `asset_cache` and the minified pages when files change, if watching.
+    #[cfg(feature = "dev")]
+    pub(crate) fn start_watching(&self, asset_cache: Option<&Arc<asset_cache::Store>>) {
+        let (Some(watcher), Source::Directory(root)) = (&self.watcher, &self.source) else {
+            return;
+        };
+        if let Some(store) = asset_cache {
+            let store: Arc<dyn watch::Invalidate> = store.clone();
+            watcher.register(Arc::downgrade(&store));
+        }
+        if let Some(store) = &self.minify {
+            let store: Arc<dyn watch::Invalidate> = store.clone();
+            watcher.register(Arc::downgrade(&store));
+        }
+        watcher.start(root);
+    }
+
+    /// Whether a request path names a directory in the source.
+    pub(crate) async fn is_dir(&self, uri_path: &str) -> bool {
+        match &self.source {
+            Source::Directory(root) => match conditional::resolve(root, uri_path, "") {
+                Some(path) => tokio::fs::metadata(path)
+                    .await
+                    .is_ok_and(|metadata| metadata.is_dir()),
+                None => false,
+            },
+            #[cfg(feature = "embed")]
+            Source::Embedded(dir) => percent_encoding::percent_decode_str(uri_path)
+                .decode_utf8()
+                .is_ok_and(|path| dir.get_dir(path.trim_matches('/')).is_some()),
+        }
+    }
+
+    /// Whether a request path names a file in the source that [`PathSecurity`] allows.
+    pub(crate) async fn is_file(&self, uri_path: &str) -> bool {
+        match &self.source {
+            Source::Directory(root) => match conditional::resolve(root, uri_path, &self.index) {
+                Some(path) => {
+                    tokio::fs::metadata(&path)
+                        .await
+                        .is_ok_and(|metadata| metadata.is_file())
+                        && self
+                            .security
+                            .allows_file(&hardening::canonical_root(root), &path)
+                            .await
+                }
+                None => false,
+            },
+            #[cfg(feature = "embed")]
+            Source::Embedded(dir) => percent_encoding::percent_decode_str(uri_path)
+                .decode_utf8()
+                .is_ok_and(|path| dir.get_file(path.trim_start_matches('/')).is_some()),
+        }
+    }
+
+    pub(crate) fn error_page_settings(&self) -> &ErrorPages {
+        &self.error_pages
+    }
+
+    pub(crate) fn not_found_settings(&self) -> &NotFoundFormat {
+        &self.not_found
+    }
+
+    pub(crate) fn not_found_page_name(&self) -> Option<&str> {
+        self.error_pages.not_found_page()
+    }
+
+    /// Read a file relative to the root, e.g. an error page.
+    pub(crate) fn read(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+        match &self.source {
+            Source::Directory(root) => std::fs::read(root.join(path)).ok().map(Cow::Owned),
+            #[cfg(feature = "embed")]
+            Source::Embedded(dir) => dir
+                .get_file(path)
+                .map(|file| Cow::Borrowed(file.contents())),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Prerendered pages of Astro's dynamic routes.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use percent_encoding::percent_decode_str;
+
+use crate::routes::astro::StaticConfig;
+
+/// A URL pattern served from the pages `getStaticPaths` prerendered for it.
+///
+/// Patterns use `:name` (or `{name}`) for one path segment and `*name` (or `{*name}`) for
+/// the rest of the path. The file is relative to the root, with `[name]` and `[...name]`
+/// replaced by the matching parameters, as in Astro's `src/pages` file names. Parameters
+/// can't be `.`, `..` or contain slashes (except rest parameters, between segments).
+///
+/// Files that exist at a matching URL, like `/blog/feed.xml`, are still served as-is.
+/// Missing pages get `404` with the route's own not-found page, falling back to the
+/// config's [`ErrorPages`](crate::routes::astro::ErrorPages) page.
+///
+/// ```rust, ignore
+/// let config = StaticConfig::default()
+///     .dynamic_route(
+///         DynamicRoute::new("/blog/:slug", "blog/[slug]/index.html")
+///             .not_found_page("blog/404.html"),
+///     )
+///     .dynamic_route(DynamicRoute::new("/docs/*path", "docs/[...path].html"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicRoute {
+    pattern: Vec<Segment>,
+    file: String,
+    not_found: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Rest(String),
+}
+
+impl Segment {
+    fn parse(segment: &str) -> Self {
+        let braced = segment
+            .strip_prefix('{')
+            .and_then(|braced| braced.strip_suffix('}'));
+        match braced {
+            Some(braced) => match braced.strip_prefix('*') {
+                Some(rest) => Self::Rest(rest.to_string()),
+                None => Self::Param(braced.to_string()),
+            },
+            None => match (segment.strip_prefix(':'), segment.strip_prefix('*')) {
+                (Some(param), _) => Self::Param(param.to_string()),
+                (_, Some(rest)) => Self::Rest(rest.to_string()),
+                _ => Self::Literal(segment.to_string()),
+            },
+        }
+    }
+}
+
+impl DynamicRoute {
+    /// Serve `file` for URLs matching `pattern`.
+    ///
+    /// Panics if a rest parameter isn't the pattern's last segment.
+    pub fn new(pattern: impl AsRef<str>, file: impl Into<String>) -> Self {
+        let pattern: Vec<Segment> = pattern
+            .as_ref()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(Segment::parse)
+            .collect();
+        assert!(
+            pattern
+                .iter()
+                .rev()
+                .skip(1)
+                .all(|segment| !matches!(segment, Segment::Rest(_))),
+            "DynamicRoute::new needs rest parameters at the end of the pattern"
+        );
+        Self {
+            pattern,
+            file: file.into().trim_start_matches('/').to_string(),
+            not_found: None,
+        }
+    }
+
+    /// Page served with `404` when the prerendered file for a URL doesn't exist.
+    pub fn not_found_page(mut self, page: impl Into<String>) -> Self {
+        self.not_found = Some(page.into());
+        self
+    }
+
+    /// The parameters in a request path, in pattern order, if the path matches.
+    ///
+    /// Values are still percent-encoded, as they appear in the URL.
+    pub fn params<'a>(&self, path: &'a str) -> Option<Vec<(&str, &'a str)>> {
+        let mut rest = path.trim_matches('/');
+        let mut params = Vec::new();
+        for segment in &self.pattern {
+            if let Segment::Rest(name) = segment {
+                let valid = !rest.is_empty() && rest.split('/').all(valid_param);
+                return valid.then(|| {
+                    params.push((name.as_str(), rest));
+                    params
+                });
+            }
+            let (part, remaining) = rest.split_once('/').unwrap_or((rest, ""));
+            match segment {
+                Segment::Literal(literal) if literal == part => {}
+                Segment::Param(name) if valid_param(part) => params.push((name.as_str(), part)),
+                _ => return None,
+            }
+            rest = remaining;
+        }
+        rest.is_empty().then_some(params)
+    }
+
+    /// The file, relative to the root, prerendered for a request path, if the path matches.
+    pub fn file_for(&self, path: &str) -> Option<String> {
+        let params = self.params(path)?;
+        let mut file = self.file.clone();
+        for (name, value) in params {
+            file = file
+                .replace(&format!("[...{name}]"), value)
+                .replace(&format!("[{name}]"), value);
+        }
+        Some(file)
+    }
+}
+
+// A parameter that stays within its directory once decoded
+fn valid_param(value: &str) -> bool {
+    let Ok(decoded) = percent_decode_str(value).decode_utf8() else {
+        return false;
+    };
+    !decoded.is_empty() && decoded != "." && decoded != ".." && !decoded.contains(['/', '\\', '\0'])
+}
+
+/// Middleware serving [`DynamicRoute`]s before the static files.
+pub(crate) async fn route(
+    State(config): State<Arc<StaticConfig>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return next.run(req).await;
+    }
+    let path = req.uri().path();
+    let Some((route, file)) = config
+        .dynamic_route_settings()
+        .iter()
+        .find_map(|route| route.file_for(path).map(|file| (route, file)))
+    else {
+        return next.run(req).await;
+    };
+    // Real files under the route's prefix, like `/blog/feed.xml`, are served as they are
+    if !path.ends_with('/') && config.is_file(path).await {
+        return next.run(req).await;
+    }
+
+    let file = match file.ends_with('/') || file.is_empty() {
+        true => format!("/{file}{}", config.index_name()),
+        false => format!("/{file}"),
+    };
+    if !config.is_file(&file).await {
+        return not_found(&config, route);
+    }
+
+    let uri = match req.uri().query() {
+        Some(query) => format!("{file}?{query}"),
+        None => file,
+    };
+    match uri.parse() {
+        Ok(uri) => {
+            *req.uri_mut() = uri;
+            next.run(req).await
+        }
+        Err(_) => not_found(&config, route),
+    }
+}
+
+fn not_found(config: &StaticConfig, route: &DynamicRoute) -> Response {
+    super::not_found_page(config, route.not_found.as_deref())
+}
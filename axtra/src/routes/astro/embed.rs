@@ -0,0 +1,172 @@
+//! Serving assets compiled into the binary.
+
+use axum::{
+    body::{Body, Bytes},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
+};
+use include_dir::Dir;
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    response::etag_for,
+    routes::astro::{StaticConfig, conditional, minify, range},
+};
+
+/// Resolve a request path against the embedded directory, mirroring `ServeDir`.
+pub(crate) fn serve(
+    dir: &'static Dir<'static>,
+    config: &StaticConfig,
+    uri_path: &str,
+    headers: &HeaderMap,
+) -> Response {
+    let Ok(decoded) = percent_decode_str(uri_path).decode_utf8() else {
+        return not_found(dir, config);
+    };
+    let relative = decoded.trim_start_matches('/');
+
+    if relative.is_empty() || relative.ends_with('/') {
+        let index = format!("{relative}{}", config.index_name());
+        return match dir.get_file(&index) {
+            Some(file) => file_response(dir, config, headers, &index, file.contents()),
+            None => not_found(dir, config),
+        };
+    }
+
+    if let Some(file) = dir.get_file(relative) {
+        return file_response(dir, config, headers, relative, file.contents());
+    }
+    // Directories are redirected to their trailing-slash form, like ServeDir does
+    if dir.get_dir(relative).is_some() {
+        return Redirect::temporary(&format!("{uri_path}/")).into_response();
+    }
+    not_found(dir, config)
+}
+
+/// Tag a successful response with a content hash ETag, answer `If-None-Match` with 304
+/// and `Range` with the requested bytes.
+pub(crate) fn revalidate(request_headers: &HeaderMap, res: Response) -> Response {
+    match res.extensions().get::<Embedded>().cloned() {
+        Some(Embedded(contents)) => {
+            let etag = etag_for(&contents);
+            let res = conditional::respond(request_headers, &etag, res);
+            slice(request_headers, &etag, contents, res)
+        }
+        None => res,
+    }
+}
+
+/// Serve a single embedded file, e.g. an SPA index.
+pub(crate) fn serve_file(
+    dir: &'static Dir<'static>,
+    config: &StaticConfig,
+    path: &str,
+    headers: &HeaderMap,
+) -> Response {
+    match dir.get_file(path) {
+        Some(file) => file_response(dir, config, headers, path, file.contents()),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+fn not_found(dir: &'static Dir<'static>, config: &StaticConfig) -> Response {
+    let page = config
+        .not_found_page_name()
+        .and_then(|page| dir.get_file(page).map(|file| (page, file)));
+    match page {
+        Some((page, file)) => {
+            let mut res = typed_response(page, Bytes::from_static(file.contents()));
+            *res.status_mut() = StatusCode::NOT_FOUND;
+            res
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+// Prefer a precompressed sibling (`app.js.br`, ...) the client accepts, unless a range
+// of the uncompressed file was requested
+fn file_response(
+    dir: &'static Dir<'static>,
+    config: &StaticConfig,
+    headers: &HeaderMap,
+    path: &str,
+    contents: &'static [u8],
+) -> Response {
+    if let Some(minifier) = config.html_minifier()
+        && minify::is_html(path)
+        && let Some(html) = minifier.page(config, path, "")
+    {
+        return typed_response(path, html);
+    }
+
+    let precompressed = match headers.contains_key(header::RANGE) {
+        true => Vec::new(),
+        false => config.compression_settings().precompressed_for(headers),
+    };
+    for (encoding, extension) in precompressed {
+        if let Some(file) = dir.get_file(format!("{path}.{extension}")) {
+            let mut res = typed_response(path, Bytes::from_static(file.contents()));
+            res.headers_mut()
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+            res.headers_mut()
+                .insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+            return res;
+        }
+    }
+    typed_response(path, Bytes::from_static(contents))
+}
+
+// The bytes behind an embedded response, for computing its ETag
+#[derive(Clone)]
+struct Embedded(Bytes);
+
+fn typed_response(path: &str, contents: Bytes) -> Response {
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    let mut res = Body::from(contents.clone()).into_response();
+    res.extensions_mut().insert(Embedded(contents));
+    if let Ok(value) = HeaderValue::from_str(mime.as_ref()) {
+        res.headers_mut().insert(header::CONTENT_TYPE, value);
+    }
+    res
+}
+
+// Answer a ranged request for in-memory `contents` with `206`, or `416` when the range
+// lies past the end. Full uncompressed responses advertise `Accept-Ranges`.
+fn slice(request_headers: &HeaderMap, etag: &str, contents: Bytes, mut res: Response) -> Response {
+    if res.status() != StatusCode::OK || res.headers().contains_key(header::CONTENT_ENCODING) {
+        return res;
+    }
+    res.headers_mut()
+        .insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    let range = request_headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+    let Some(range) = range.filter(|_| range::if_range_matches(request_headers, Some(etag), None))
+    else {
+        return res;
+    };
+
+    let len = contents.len() as u64;
+    let (status, content_range, body) = match range::parse(range, len) {
+        None => return res,
+        Some(Ok(range)) => (
+            StatusCode::PARTIAL_CONTENT,
+            format!("bytes {}-{}/{len}", range.start(), range.end()),
+            contents.slice(*range.start() as usize..=*range.end() as usize),
+        ),
+        Some(Err(())) => (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            format!("bytes */{len}"),
+            Bytes::new(),
+        ),
+    };
+
+    let (mut parts, _) = res.into_parts();
+    parts.status = status;
+    parts.headers.remove(header::CONTENT_LENGTH);
+    if let Ok(value) = HeaderValue::from_str(&content_range) {
+        parts.headers.insert(header::CONTENT_RANGE, value);
+    }
+    Response::from_parts(parts, Body::from(body))
+}
@@ -0,0 +1,84 @@
+//! HTML error pages shared by the static router and `AppError`.
+
+use std::collections::BTreeMap;
+
+use http::StatusCode;
+
+/// Which files, relative to the [`StaticConfig`](crate::routes::astro::StaticConfig)
+/// source, are served for errors.
+///
+/// The static router answers missing files with the `404` page, and HTML `AppError`s
+/// render the page for their status (falling back to the server error page) once the
+/// config is registered with [`set_error_pages`](crate::routes::astro::set_error_pages).
+/// Defaults to `404.html` and `500.html`.
+///
+/// ```rust, ignore
+/// let pages = ErrorPages::default()
+///     .not_found("errors/404.html")
+///     .page(StatusCode::FORBIDDEN, "errors/403.html");
+///
+/// let config = StaticConfig::embedded(&DIST).error_pages(pages);
+/// set_error_pages(config.clone());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorPages {
+    pages: BTreeMap<u16, String>,
+    server_error: Option<String>,
+}
+
+impl Default for ErrorPages {
+    fn default() -> Self {
+        Self::new().not_found("404.html").server_error("500.html")
+    }
+}
+
+impl ErrorPages {
+    /// No error pages: errors get an empty body or `AppError`'s built-in page.
+    pub fn new() -> Self {
+        Self {
+            pages: BTreeMap::new(),
+            server_error: None,
+        }
+    }
+
+    /// Page for a specific status.
+    pub fn page(mut self, status: StatusCode, path: impl Into<String>) -> Self {
+        self.pages.insert(status.as_u16(), path.into());
+        self
+    }
+
+    /// Page for `404 Not Found`, also served by the static router for missing files.
+    pub fn not_found(self, path: impl Into<String>) -> Self {
+        self.page(StatusCode::NOT_FOUND, path)
+    }
+
+    /// Page for every error status without its own page.
+    pub fn server_error(mut self, path: impl Into<String>) -> Self {
+        self.server_error = Some(path.into());
+        self
+    }
+
+    /// Stop serving a page for `status`.
+    pub fn without(mut self, status: StatusCode) -> Self {
+        self.pages.remove(&status.as_u16());
+        self
+    }
+
+    /// The page configured for `status`, if any.
+    pub fn page_for(&self, status: StatusCode) -> Option<&str> {
+        self.pages
+            .get(&status.as_u16())
+            .or(self.server_error.as_ref())
+            .map(String::as_str)
+    }
+
+    /// The page configured for exactly `status`, without the server error fallback.
+    pub(crate) fn exact_page(&self, status: StatusCode) -> Option<&str> {
+        self.pages.get(&status.as_u16()).map(String::as_str)
+    }
+
+    /// The page for missing static files, which never falls back to the server error page.
+    pub(crate) fn not_found_page(&self) -> Option<&str> {
+        self.exact_page(StatusCode::NOT_FOUND)
+    }
+}
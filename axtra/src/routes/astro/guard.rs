@@ -0,0 +1,323 @@
+//! Password protection for preview deployments of static sites.
+
+use std::{
+    fmt,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    extract::{Query, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::{net::constant_time_eq, routes::astro::StaticConfig};
+
+// How long an unlock cookie lasts
+const UNLOCK_MAX_AGE: u64 = 30 * 24 * 60 * 60;
+
+/// Keep a static site or SPA private, e.g. a staging preview.
+///
+/// Requests need valid basic auth credentials or the cookie set by visiting
+/// `/unlock?token=...` (with an optional `&redirect=/path`). Everything else gets `401`
+/// with the [`ErrorPages`](crate::routes::astro::ErrorPages) page for
+/// `401 Unauthorized`, if one is configured.
+///
+/// ```rust, ignore
+/// let guard = AccessGuard::new()
+///     .basic_auth("preview", "hunter2")
+///     .token(std::env::var("PREVIEW_TOKEN")?);
+///
+/// let config = StaticConfig::default()
+///     .protect(guard)
+///     .error_pages(ErrorPages::default().page(StatusCode::UNAUTHORIZED, "401.html"));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccessGuard {
+    basic_auth: Option<(String, String)>,
+    token: Option<String>,
+    realm: String,
+    unlock_path: String,
+    cookie: String,
+}
+
+impl fmt::Debug for AccessGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessGuard")
+            .field(
+                "basic_auth",
+                &self.basic_auth.as_ref().map(|(user, _)| user),
+            )
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("realm", &self.realm)
+            .field("unlock_path", &self.unlock_path)
+            .field("cookie", &self.cookie)
+            .finish()
+    }
+}
+
+impl Default for AccessGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccessGuard {
+    /// A guard letting nothing through until credentials are added.
+    pub fn new() -> Self {
+        Self {
+            basic_auth: None,
+            token: None,
+            realm: "Preview".to_string(),
+            unlock_path: "/unlock".to_string(),
+            cookie: "axtra_unlock".to_string(),
+        }
+    }
+
+    /// Credentials from `AXTRA_STATIC_USER` / `AXTRA_STATIC_PASSWORD` and
+    /// `AXTRA_STATIC_TOKEN`, or `None` when neither is set.
+    pub fn from_env() -> Option<Self> {
+        let mut guard = Self::new();
+        if let (Ok(user), Ok(password)) = (
+            std::env::var("AXTRA_STATIC_USER"),
+            std::env::var("AXTRA_STATIC_PASSWORD"),
+        ) {
+            guard = guard.basic_auth(user, password);
+        }
+        if let Ok(token) = std::env::var("AXTRA_STATIC_TOKEN") {
+            guard = guard.token(token);
+        }
+        (guard.basic_auth.is_some() || guard.token.is_some()).then_some(guard)
+    }
+
+    /// Accept these HTTP basic auth credentials.
+    pub fn basic_auth(mut self, user: impl Into<String>, password: impl Into<String>) -> Self {
+        self.basic_auth = Some((user.into(), password.into()));
+        self
+    }
+
+    /// Accept a shared token at the unlock route, which sets a cookie signed with it.
+    /// The unlock route is answered by
+    /// [`serve_static_files_with`](crate::routes::astro::serve_static_files_with), so mount
+    /// it even when the site is an SPA.
+    ///
+    /// Changing the token invalidates every cookie issued for the old one.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Realm shown in the browser's basic auth prompt (defaults to `Preview`).
+    pub fn realm(mut self, realm: impl Into<String>) -> Self {
+        self.realm = realm.into();
+        self
+    }
+
+    /// Path of the unlock route (defaults to `/unlock`).
+    pub fn unlock_path(mut self, path: impl Into<String>) -> Self {
+        self.unlock_path = path.into();
+        self
+    }
+
+    /// Name of the unlock cookie (defaults to `axtra_unlock`).
+    pub fn cookie(mut self, name: impl Into<String>) -> Self {
+        self.cookie = name.into();
+        self
+    }
+
+    // Whether the request carries valid credentials or a valid unlock cookie
+    fn allows(&self, headers: &HeaderMap) -> bool {
+        self.basic_auth_matches(headers) || self.cookie_matches(headers)
+    }
+
+    fn basic_auth_matches(&self, headers: &HeaderMap) -> bool {
+        let Some((user, password)) = &self.basic_auth else {
+            return false;
+        };
+        let expected = STANDARD.encode(format!("{user}:{password}"));
+        headers
+            .get_all(header::AUTHORIZATION)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.strip_prefix("Basic "))
+            .any(|credentials| constant_time_eq(credentials.trim().as_bytes(), expected.as_bytes()))
+    }
+
+    fn cookie_matches(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.token else {
+            return false;
+        };
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .filter(|(name, _)| *name == self.cookie)
+            .filter_map(|(_, value)| value.trim().split_once('.'))
+            .any(|(expires, signature)| {
+                let unexpired = expires.parse::<u64>().is_ok_and(|expires| expires > now());
+                let signature = URL_SAFE_NO_PAD.decode(signature).unwrap_or_default();
+                unexpired && mac(token, expires).verify_slice(&signature).is_ok()
+            })
+    }
+
+    fn unlock(&self, config: &StaticConfig, params: UnlockParams) -> Response {
+        let Some(token) = &self.token else {
+            return self.deny(config);
+        };
+        if !constant_time_eq(
+            params.token.unwrap_or_default().as_bytes(),
+            token.as_bytes(),
+        ) {
+            return self.deny(config);
+        }
+
+        // Only follow local paths, so the route can't be used as an open redirect
+        let redirect = params
+            .redirect
+            .filter(|path| is_local(path))
+            .unwrap_or_else(|| "/".to_string());
+        let expires = (now() + UNLOCK_MAX_AGE).to_string();
+        let signature = URL_SAFE_NO_PAD.encode(mac(token, &expires).finalize().into_bytes());
+        let cookie = format!(
+            "{}={expires}.{signature}; Path=/; Max-Age={UNLOCK_MAX_AGE}; \
+             HttpOnly; Secure; SameSite=Lax",
+            self.cookie
+        );
+
+        let mut res = Redirect::to(&redirect).into_response();
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            res.headers_mut().insert(header::SET_COOKIE, value);
+        }
+        res
+    }
+
+    fn deny(&self, config: &StaticConfig) -> Response {
+        let page = config
+            .error_page_settings()
+            .exact_page(StatusCode::UNAUTHORIZED)
+            .and_then(|page| config.read(page));
+        let mut res = match page {
+            Some(page) => (StatusCode::UNAUTHORIZED, Html(page.into_owned())).into_response(),
+            None => StatusCode::UNAUTHORIZED.into_response(),
+        };
+        if self.basic_auth.is_some() {
+            let challenge = format!(
+                "Basic realm=\"{}\", charset=\"UTF-8\"",
+                self.realm.replace('"', "")
+            );
+            if let Ok(value) = HeaderValue::from_str(&challenge) {
+                res.headers_mut().insert(header::WWW_AUTHENTICATE, value);
+            }
+        }
+        super::no_cache(&mut res);
+        res
+    }
+}
+
+#[derive(Deserialize)]
+struct UnlockParams {
+    token: Option<String>,
+    redirect: Option<String>,
+}
+
+// Unlock cookies are `{expires}.{mac}`, so only the token's holder can mint or extend one
+fn mac(token: &str, expires: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(token.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(b"axtra-static-unlock:");
+    mac.update(expires.as_bytes());
+    mac
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+// Browsers drop tabs and newlines from URLs, so `/\t/evil.com` would go to `//evil.com`
+fn is_local(path: &str) -> bool {
+    if path
+        .chars()
+        .any(|c| c.is_control() || c.is_whitespace() || c == '\\')
+    {
+        return false;
+    }
+    let mut chars = path.chars();
+    chars.next() == Some('/') && chars.next() != Some('/')
+}
+
+/// Middleware applying the configured [`AccessGuard`] to static and SPA routes.
+pub(crate) async fn route(
+    State(config): State<Arc<StaticConfig>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(guard) = config.access_guard() else {
+        return next.run(req).await;
+    };
+
+    if req.uri().path() == guard.unlock_path {
+        let params = Query::<UnlockParams>::try_from_uri(req.uri())
+            .map(|Query(params)| params)
+            .unwrap_or(UnlockParams {
+                token: None,
+                redirect: None,
+            });
+        return guard.unlock(&config, params);
+    }
+    if guard.allows(req.headers()) {
+        return next.run(req).await;
+    }
+    guard.deny(&config)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Uri;
+
+    use super::*;
+
+    #[test]
+    fn only_redirects_to_local_paths() {
+        assert!(is_local("/"));
+        assert!(is_local("/docs/?page=2"));
+        for path in [
+            "https://evil.com",
+            "//evil.com",
+            "/\\evil.com",
+            "\\\\evil.com",
+            "/\t/evil.com",
+            "/\r\n/evil.com",
+            "\t//evil.com",
+            "/ /evil.com",
+            "docs",
+            "",
+        ] {
+            assert!(!is_local(path), "{path:?} was treated as local");
+        }
+    }
+
+    #[test]
+    fn unlock_ignores_encoded_off_site_redirects() {
+        let uri: Uri = "/unlock?token=t&redirect=/%09/evil.com".parse().unwrap();
+        let Query(params) = Query::<UnlockParams>::try_from_uri(&uri).unwrap();
+        let res = AccessGuard::new()
+            .token("t")
+            .unlock(&StaticConfig::default(), params);
+        assert_eq!(res.headers()[header::LOCATION], "/");
+        assert!(res.headers().contains_key(header::SET_COOKIE));
+    }
+}
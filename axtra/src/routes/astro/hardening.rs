@@ -0,0 +1,309 @@
+//! Rejecting path traversal and symlink escapes on static routes.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use percent_encoding::percent_decode_str;
+
+#[cfg(feature = "bouncer")]
+use crate::bouncer::BouncerLayer;
+use crate::routes::astro::{Compression, StaticConfig};
+
+/// What static and SPA routes guarantee about the paths they serve.
+///
+/// By default:
+///
+/// - Requests with traversal sequences get `400 Bad Request` before any routing: `..`
+///   segments, encoded or not (`%2e%2e`, `.%2E`), backslashes (`\\`, `%5c`), encoded
+///   slashes (`%2f`), NUL bytes and double-encoded variants (`%252e%252e`).
+/// - Files are only served if their real path, after resolving symlinks, is inside the
+///   root. Symlinks pointing elsewhere are answered as missing. This covers static files,
+///   SPA index pages, [dynamic route](crate::routes::astro::DynamicRoute) targets and
+///   precompressed siblings, which are skipped for the plain file.
+///
+/// Rejected requests are logged, and with the `bouncer` feature their client can be banned.
+///
+/// ```rust, ignore
+/// let bouncer = BouncerLayer::new(BouncerConfig::from_preset_rules(&["wordpress"]));
+/// let config = StaticConfig::default().path_security(PathSecurity::new().ban_with(&bouncer));
+/// let app = Router::new()
+///     .merge(serve_static_files_with(&config))
+///     .layer(bouncer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PathSecurity {
+    reject_traversal: bool,
+    external_symlinks: bool,
+    #[cfg(feature = "bouncer")]
+    bouncer: Option<BouncerLayer>,
+}
+
+impl PartialEq for PathSecurity {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "bouncer")]
+        let same_bouncer = match (&self.bouncer, &other.bouncer) {
+            (Some(a), Some(b)) => Arc::ptr_eq(&a.banlist(), &b.banlist()),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        #[cfg(not(feature = "bouncer"))]
+        let same_bouncer = true;
+        self.reject_traversal == other.reject_traversal
+            && self.external_symlinks == other.external_symlinks
+            && same_bouncer
+    }
+}
+
+impl Default for PathSecurity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathSecurity {
+    /// Reject traversal sequences and symlinks leaving the root.
+    pub fn new() -> Self {
+        Self {
+            reject_traversal: true,
+            external_symlinks: false,
+            #[cfg(feature = "bouncer")]
+            bouncer: None,
+        }
+    }
+
+    /// Leave traversal sequences to the file resolution, which still never leaves the root.
+    pub fn allow_encoded_traversal(mut self) -> Self {
+        self.reject_traversal = false;
+        self
+    }
+
+    /// Follow symlinks wherever they point, e.g. for a root assembled from links into a
+    /// shared asset directory.
+    pub fn allow_external_symlinks(mut self) -> Self {
+        self.external_symlinks = true;
+        self
+    }
+
+    /// Ban clients sending traversal sequences with `bouncer`'s banlist, strikes and ban
+    /// duration. The bouncer layer must wrap the app for the ban to take effect.
+    #[cfg(feature = "bouncer")]
+    pub fn ban_with(mut self, bouncer: &BouncerLayer) -> Self {
+        self.bouncer = Some(bouncer.clone());
+        self
+    }
+
+    /// Whether `file`, resolved from a request path, may be served from `root`.
+    ///
+    /// `root` must already be canonical.
+    pub(crate) async fn allows_file(&self, root: &Path, file: &Path) -> bool {
+        if self.external_symlinks {
+            return true;
+        }
+        match tokio::fs::canonicalize(file).await {
+            Ok(real) if real.starts_with(root) => true,
+            Ok(real) => {
+                tracing::warn!(
+                    file = %file.display(),
+                    target = %real.display(),
+                    "Refused to serve a symlink leaving the static root"
+                );
+                false
+            }
+            // Missing files are answered by the not-found handling
+            Err(_) => true,
+        }
+    }
+
+    /// Drop `Accept-Encoding` from `headers` if a precompressed sibling of `file` the
+    /// client accepts (`app.js.br`, ...) leaves `root`, so the plain file is served instead.
+    ///
+    /// `root` must already be canonical.
+    pub(crate) async fn secure_precompressed(
+        &self,
+        root: &Path,
+        file: &Path,
+        compression: &Compression,
+        headers: &mut HeaderMap,
+    ) {
+        if self.external_symlinks {
+            return;
+        }
+        for (_, extension) in compression.precompressed_for(headers) {
+            let mut sibling = file.as_os_str().to_owned();
+            sibling.push(".");
+            sibling.push(extension);
+            if !self.allows_file(root, Path::new(&sibling)).await {
+                headers.remove(header::ACCEPT_ENCODING);
+                return;
+            }
+        }
+    }
+}
+
+/// The canonical form of a static root, for comparing against resolved files.
+pub(crate) fn canonical_root(root: &Path) -> PathBuf {
+    std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf())
+}
+
+// Whether a raw request path tries to leave its directory, in any encoding
+fn is_traversal(path: &str) -> bool {
+    let mut current = path.to_string();
+    // Decoding again catches double encoding (`%252e` decodes to `%2e`, then to `.`)
+    for _ in 0..3 {
+        let lower = current.to_ascii_lowercase();
+        if lower.contains("%2f") || lower.contains("%5c") || lower.contains("%00") {
+            return true;
+        }
+        if current.contains(['\\', '\0']) || current.split('/').any(|segment| segment == "..") {
+            return true;
+        }
+        let decoded = percent_decode_str(&current)
+            .decode_utf8_lossy()
+            .into_owned();
+        if decoded == current {
+            return false;
+        }
+        current = decoded;
+    }
+    true
+}
+
+/// Middleware rejecting traversal attempts before static and SPA routes see them.
+pub(crate) async fn route(
+    State(config): State<Arc<StaticConfig>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let security = config.path_security_settings();
+    let path = req.uri().path();
+    if !security.reject_traversal || !is_traversal(path) {
+        return next.run(req).await;
+    }
+
+    tracing::warn!(path = %path, "Rejected path traversal attempt");
+    #[cfg(feature = "bouncer")]
+    if let Some(bouncer) = &security.bouncer {
+        bouncer
+            .ban_client(
+                req.headers(),
+                req.extensions(),
+                path,
+                "Path traversal attempted",
+            )
+            .await;
+    }
+    StatusCode::BAD_REQUEST.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_traversal_in_any_encoding() {
+        for path in [
+            "/../etc/passwd",
+            "/assets/../../etc/passwd",
+            "/%2e%2e/etc/passwd",
+            "/%2E%2E/etc/passwd",
+            "/.%2E/etc/passwd",
+            "/%252e%252e/etc/passwd",
+            "/%25252e%25252e/etc/passwd",
+            "/..\\etc\\passwd",
+            "/assets%5c..%5cetc",
+            "/assets%5C..",
+            "/assets%2f..%2fetc",
+            "/index.html%00.png",
+        ] {
+            assert!(is_traversal(path), "{path} should be rejected");
+        }
+    }
+
+    #[test]
+    fn allows_ordinary_paths() {
+        for path in [
+            "/",
+            "/index.html",
+            "/assets/app.3f2a.js",
+            "/blog/hello-world/",
+            "/files/my%20report.pdf",
+            "/..well-known",
+            "/a..b/c",
+        ] {
+            assert!(!is_traversal(path), "{path} should be allowed");
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn refuses_symlinks_leaving_the_root() {
+        let base = std::env::temp_dir().join(format!("axtra-hardening-{}", std::process::id()));
+        let root = base.join("dist");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(base.join("secret.txt"), "secret").unwrap();
+        std::fs::write(root.join("page.html"), "page").unwrap();
+        std::os::unix::fs::symlink(base.join("secret.txt"), root.join("leak.txt")).unwrap();
+        std::os::unix::fs::symlink(root.join("page.html"), root.join("alias.html")).unwrap();
+        let canonical = canonical_root(&root);
+
+        let security = PathSecurity::new();
+        assert!(
+            security
+                .allows_file(&canonical, &root.join("page.html"))
+                .await
+        );
+        assert!(
+            security
+                .allows_file(&canonical, &root.join("alias.html"))
+                .await
+        );
+        assert!(
+            !security
+                .allows_file(&canonical, &root.join("leak.txt"))
+                .await
+        );
+        let external = PathSecurity::new().allow_external_symlinks();
+        assert!(
+            external
+                .allows_file(&canonical, &root.join("leak.txt"))
+                .await
+        );
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn skips_precompressed_siblings_leaving_the_root() {
+        let base = std::env::temp_dir().join(format!("axtra-siblings-{}", std::process::id()));
+        let root = base.join("dist");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(base.join("secret.br"), "secret").unwrap();
+        std::fs::write(root.join("app.js"), "app").unwrap();
+        std::os::unix::fs::symlink(base.join("secret.br"), root.join("app.js.br")).unwrap();
+        let canonical = canonical_root(&root);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, "br, gzip".parse().unwrap());
+        PathSecurity::new()
+            .secure_precompressed(
+                &canonical,
+                &root.join("app.js"),
+                &Compression::default(),
+                &mut headers,
+            )
+            .await;
+        assert!(headers.get(header::ACCEPT_ENCODING).is_none());
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
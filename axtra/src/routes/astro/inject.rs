@@ -0,0 +1,162 @@
+//! Runtime environment injection into SPA index pages.
+
+use std::{borrow::Cow, collections::BTreeMap};
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+
+use crate::{
+    response::etag_for,
+    routes::astro::{StaticConfig, conditional, config::Source, manifest, minify},
+};
+
+/// Values injected into an SPA's `index.html` at serve time, so one Astro build can run
+/// in every environment.
+///
+/// By default they're exposed as `window.__ENV__` through a `<script>` added before
+/// `</head>`. With [`placeholders`](Self::placeholders), `%NAME%` tokens in the HTML are
+/// replaced as well. [`asset`](Self::asset) values are looked up in the registered
+/// [`AssetManifest`](crate::routes::astro::AssetManifest) on every request, so they follow
+/// manifest reloads.
+///
+/// ```rust, ignore
+/// let env = EnvInjection::new()
+///     .from_env_prefix("PUBLIC_")
+///     .var("API_URL", "https://api.example.com");
+///
+/// let config = StaticConfig::default().inject_env(env);
+/// let app = Router::new().merge(serve_spa_with("app", &config));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvInjection {
+    vars: BTreeMap<String, String>,
+    assets: BTreeMap<String, String>,
+    global: Option<String>,
+    placeholders: bool,
+}
+
+impl Default for EnvInjection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvInjection {
+    pub fn new() -> Self {
+        Self {
+            vars: BTreeMap::new(),
+            assets: BTreeMap::new(),
+            global: Some("__ENV__".to_string()),
+            placeholders: false,
+        }
+    }
+
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// Expose the fingerprinted URL of `src` (e.g. `src/main.ts`) from the registered
+    /// [`AssetManifest`](crate::routes::astro::AssetManifest) as `name`.
+    ///
+    /// Skipped while no manifest is registered or it has no entry for `src`.
+    pub fn asset(mut self, name: impl Into<String>, src: impl Into<String>) -> Self {
+        self.assets.insert(name.into(), src.into());
+        self
+    }
+
+    /// Add every process environment variable starting with `prefix`, under its full name.
+    ///
+    /// Only expose variables meant for the browser: everything injected is public.
+    pub fn from_env_prefix(mut self, prefix: &str) -> Self {
+        self.vars.extend(
+            std::env::vars()
+                .filter(|(name, _)| name.starts_with(prefix) && name.len() > prefix.len()),
+        );
+        self
+    }
+
+    /// The `window` property the values are assigned to (defaults to `__ENV__`).
+    pub fn global(mut self, name: impl Into<String>) -> Self {
+        self.global = Some(name.into());
+        self
+    }
+
+    /// Don't add the `<script>`; only replace placeholders.
+    pub fn without_global(mut self) -> Self {
+        self.global = None;
+        self
+    }
+
+    /// Also replace `%NAME%` tokens in the HTML with each value, inserted as-is.
+    pub fn placeholders(mut self, enabled: bool) -> Self {
+        self.placeholders = enabled;
+        self
+    }
+
+    /// Inject the values into an HTML document.
+    pub(crate) fn apply(&self, html: &str) -> String {
+        let mut vars = Cow::Borrowed(&self.vars);
+        for (name, src) in &self.assets {
+            if let Some(url) = manifest::asset_url(src) {
+                vars.to_mut().insert(name.clone(), url);
+            }
+        }
+
+        let mut html = html.to_string();
+        if self.placeholders {
+            for (name, value) in vars.iter() {
+                html = html.replace(&format!("%{name}%"), value);
+            }
+        }
+
+        let Some(global) = &self.global else {
+            return html;
+        };
+        let script = format!(
+            "<script>window[{}] = {};</script>",
+            script_json(global),
+            script_json(&*vars)
+        );
+        match html.find("</head>") {
+            Some(head) => html.insert_str(head, &script),
+            None => html.insert_str(0, &script),
+        }
+        html
+    }
+}
+
+// JSON that can't close the surrounding <script> element
+fn script_json(value: &impl serde::Serialize) -> String {
+    serde_json::to_string(value)
+        .unwrap_or_else(|_| "null".to_string())
+        .replace('<', "\\u003c")
+}
+
+/// Serve `index` from the configured source with `env` injected.
+pub(crate) async fn serve(
+    config: &StaticConfig,
+    env: &EnvInjection,
+    index: &str,
+    request_headers: &HeaderMap,
+) -> Response {
+    let bytes = match (minify::page(config, index).await, config.source()) {
+        (Some(html), _) => Some(Cow::Owned(html.to_vec())),
+        (None, Source::Directory(root)) => {
+            tokio::fs::read(root.join(index)).await.ok().map(Cow::Owned)
+        }
+        #[cfg(feature = "embed")]
+        (None, Source::Embedded(_)) => config.read(index),
+    };
+    let Some(bytes) = bytes else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let html = env.apply(&String::from_utf8_lossy(&bytes));
+    let etag = etag_for(html.as_bytes());
+    let mut res = Html(html).into_response();
+    super::no_cache(&mut res);
+    conditional::respond(request_headers, &etag, res)
+}
@@ -0,0 +1,184 @@
+//! Locale negotiation for Astro i18n builds.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+
+/// Locale routing for builds laid out as `/{locale}/...`, as Astro's i18n routing emits.
+///
+/// - `/` redirects to the best locale for the client: a supported value in the locale
+///   cookie, then the best `Accept-Language` match, then the default locale.
+/// - Missing pages without a locale prefix redirect to the same page under the client's locale.
+/// - With a [`fallback`](Self::fallback), pages missing from one locale redirect to the
+///   fallback locale's page.
+///
+/// ```rust, ignore
+/// let locales = Locales::new(["en", "fr", "de"]).fallback("en");
+/// let config = StaticConfig::default().locales(locales);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locales {
+    locales: Vec<String>,
+    default: String,
+    fallback: Option<String>,
+    cookie: String,
+    redirect_root: bool,
+}
+
+impl Locales {
+    /// Supported locales; the first is the default. Panics if `locales` is empty.
+    pub fn new<I, L>(locales: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: Into<String>,
+    {
+        let locales: Vec<String> = locales.into_iter().map(Into::into).collect();
+        let default = locales
+            .first()
+            .cloned()
+            .expect("Locales::new needs at least one locale");
+        Self {
+            locales,
+            default,
+            fallback: None,
+            cookie: "locale".to_string(),
+            redirect_root: true,
+        }
+    }
+
+    /// Locale used when neither the cookie nor `Accept-Language` matches.
+    pub fn default_locale(mut self, locale: impl Into<String>) -> Self {
+        self.default = locale.into();
+        self
+    }
+
+    /// Locale whose pages stand in for pages missing from other locales.
+    pub fn fallback(mut self, locale: impl Into<String>) -> Self {
+        self.fallback = Some(locale.into());
+        self
+    }
+
+    /// Cookie holding the user's chosen locale (defaults to `locale`).
+    pub fn cookie(mut self, name: impl Into<String>) -> Self {
+        self.cookie = name.into();
+        self
+    }
+
+    /// Serve `/` as-is instead of redirecting, for builds with an unprefixed default locale.
+    pub fn without_root_redirect(mut self) -> Self {
+        self.redirect_root = false;
+        self
+    }
+
+    /// The best supported locale for a request.
+    pub fn negotiate(&self, headers: &HeaderMap) -> &str {
+        self.cookie_locale(headers)
+            .or_else(|| self.accept_language_locale(headers))
+            .unwrap_or(&self.default)
+    }
+
+    fn supported(&self, tag: &str) -> Option<&str> {
+        self.locales
+            .iter()
+            .find(|locale| locale.eq_ignore_ascii_case(tag))
+            .map(String::as_str)
+    }
+
+    fn cookie_locale(&self, headers: &HeaderMap) -> Option<&str> {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == self.cookie)
+            .and_then(|(_, value)| self.supported(value.trim()))
+    }
+
+    fn accept_language_locale(&self, headers: &HeaderMap) -> Option<&str> {
+        let mut ranges: Vec<(&str, f32)> = headers
+            .get_all(header::ACCEPT_LANGUAGE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|item| {
+                let mut parts = item.split(';').map(str::trim);
+                let tag = parts.next().filter(|tag| !tag.is_empty() && *tag != "*")?;
+                let quality = parts
+                    .find_map(|param| param.strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable, so equal qualities keep the client's order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        ranges.into_iter().find_map(|(tag, _)| {
+            self.supported(tag).or_else(|| {
+                // `fr-CA` matches a supported `fr`
+                let primary = tag.split('-').next()?;
+                self.supported(primary)
+            })
+        })
+    }
+
+    // The supported locale prefixing `path`, and the rest of the path
+    fn split<'a>(&self, path: &'a str) -> Option<(&str, &'a str)> {
+        let trimmed = path.strip_prefix('/')?;
+        let (first, rest) = match trimmed.find('/') {
+            Some(end) => (&trimmed[..end], &trimmed[end..]),
+            None => (trimmed, ""),
+        };
+        self.supported(first).map(|locale| (locale, rest))
+    }
+}
+
+/// Middleware applying [`Locales`] to the static router.
+pub(crate) async fn route(
+    State(locales): State<Arc<Locales>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path().to_string();
+    let query = req
+        .uri()
+        .query()
+        .map(|query| format!("?{query}"))
+        .unwrap_or_default();
+    let locale = locales.negotiate(req.headers()).to_string();
+
+    if path == "/" && locales.redirect_root {
+        return negotiated_redirect(&format!("/{locale}/{query}"));
+    }
+
+    let res = next.run(req).await;
+    if res.status() != StatusCode::NOT_FOUND {
+        return res;
+    }
+
+    match locales.split(&path) {
+        Some((current, rest)) => match &locales.fallback {
+            Some(fallback) if fallback != current => {
+                Redirect::temporary(&format!("/{fallback}{rest}{query}")).into_response()
+            }
+            _ => res,
+        },
+        None => negotiated_redirect(&format!("/{locale}{path}{query}")),
+    }
+}
+
+// A redirect whose target depends on the client's language preferences
+fn negotiated_redirect(location: &str) -> Response {
+    let mut res = Redirect::temporary(location).into_response();
+    res.headers_mut().insert(
+        header::VARY,
+        HeaderValue::from_static("accept-language, cookie"),
+    );
+    res
+}
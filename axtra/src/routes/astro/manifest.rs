@@ -0,0 +1,177 @@
+//! Lookup of fingerprinted build assets through Vite's `manifest.json`.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock, RwLock},
+    time::SystemTime,
+};
+
+use serde::Deserialize;
+
+static ASSET_MANIFEST: OnceLock<AssetManifest> = OnceLock::new();
+
+type ManifestResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+/// One source file's build output, as listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ManifestEntry {
+    /// Output file, relative to the build directory (`_astro/main.abc123.js`).
+    pub file: String,
+    #[serde(default)]
+    pub css: Vec<String>,
+    #[serde(default)]
+    pub imports: Vec<String>,
+    #[serde(default, rename = "isEntry")]
+    pub is_entry: bool,
+}
+
+#[derive(Debug, Default)]
+struct Loaded {
+    entries: Arc<HashMap<String, ManifestEntry>>,
+    modified: Option<SystemTime>,
+}
+
+/// Vite/Astro's `manifest.json` (`build.manifest: true`), mapping source files to their
+/// fingerprinted output.
+///
+/// Register it with [`set_asset_manifest`] to use [`asset_url`] anywhere, including
+/// [`EnvInjection::asset`](crate::routes::astro::EnvInjection::asset).
+///
+/// ```rust, ignore
+/// let manifest = AssetManifest::load("dist/.vite/manifest.json")?;
+/// assert_eq!(manifest.asset_url("src/main.ts").as_deref(), Some("/_astro/main.abc123.js"));
+/// set_asset_manifest(manifest);
+/// ```
+#[derive(Debug)]
+pub struct AssetManifest {
+    path: Option<PathBuf>,
+    base: String,
+    loaded: RwLock<Loaded>,
+}
+
+impl AssetManifest {
+    /// Read the manifest at `path`; [`reload`](Self::reload) reads it again.
+    pub fn load(path: impl Into<PathBuf>) -> ManifestResult<Self> {
+        let manifest = Self {
+            path: Some(path.into()),
+            base: "/".to_string(),
+            loaded: RwLock::new(Loaded::default()),
+        };
+        manifest.reload()?;
+        Ok(manifest)
+    }
+
+    /// Parse a manifest that isn't backed by a file.
+    pub fn from_json(json: &str) -> ManifestResult<Self> {
+        Ok(Self {
+            path: None,
+            base: "/".to_string(),
+            loaded: RwLock::new(Loaded {
+                entries: Arc::new(serde_json::from_str(json)?),
+                modified: None,
+            }),
+        })
+    }
+
+    /// URL prefix for output files (defaults to `/`).
+    pub fn base(mut self, base: impl Into<String>) -> Self {
+        let mut base = base.into();
+        if !base.ends_with('/') {
+            base.push('/');
+        }
+        self.base = base;
+        self
+    }
+
+    /// Re-read the manifest file, e.g. after a new build was deployed.
+    pub fn reload(&self) -> ManifestResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let entries: HashMap<String, ManifestEntry> =
+            serde_json::from_slice(&std::fs::read(path)?)?;
+        let mut loaded = self.loaded.write().unwrap_or_else(|e| e.into_inner());
+        *loaded = Loaded {
+            entries: Arc::new(entries),
+            modified: modified(path),
+        };
+        Ok(())
+    }
+
+    /// Reload only if the file changed since it was last read. Returns true if it did.
+    pub fn reload_if_changed(&self) -> ManifestResult<bool> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+        let current = modified(path);
+        let previous = self
+            .loaded
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .modified;
+        if current.is_some() && current == previous {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    /// The manifest entry for a source file.
+    pub fn entry(&self, src: &str) -> Option<ManifestEntry> {
+        self.entries().get(src).cloned()
+    }
+
+    /// The public URL of a source file's output, e.g. `/_astro/main.abc123.js`.
+    pub fn asset_url(&self, src: &str) -> Option<String> {
+        self.entries()
+            .get(src)
+            .map(|entry| format!("{}{}", self.base, entry.file))
+    }
+
+    /// The stylesheets a source file's output depends on.
+    pub fn css_urls(&self, src: &str) -> Vec<String> {
+        self.entries()
+            .get(src)
+            .map(|entry| {
+                entry
+                    .css
+                    .iter()
+                    .map(|css| format!("{}{css}", self.base))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn entries(&self) -> Arc<HashMap<String, ManifestEntry>> {
+        self.loaded
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .entries
+            .clone()
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Register the manifest used by [`asset_url`].
+///
+/// Returns false if a manifest was already registered.
+pub fn set_asset_manifest(manifest: AssetManifest) -> bool {
+    ASSET_MANIFEST.set(manifest).is_ok()
+}
+
+/// The registered manifest, if any.
+pub fn asset_manifest() -> Option<&'static AssetManifest> {
+    ASSET_MANIFEST.get()
+}
+
+/// The public URL of a source file's output in the registered manifest.
+pub fn asset_url(src: &str) -> Option<String> {
+    asset_manifest()?.asset_url(src)
+}
@@ -0,0 +1,275 @@
+//! HTML minification at serve time.
+
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use axum::{
+    body::Bytes,
+    response::{Html, IntoResponse, Response},
+};
+
+use crate::{
+    response::etag_for,
+    routes::astro::{StaticConfig, conditional, config::Source},
+};
+
+// Elements whose content is copied untouched
+const RAW_ELEMENTS: [&str; 4] = ["pre", "textarea", "script", "style"];
+
+/// Minify HTML pages as they're served, for builds that keep whitespace and comments.
+///
+/// Pages are minified on first read and kept in memory until their file changes.
+/// Comments (except conditional comments) are removed and whitespace runs between tags are
+/// collapsed; tags and `<pre>`, `<textarea>`, `<script>` and `<style>` content are kept as-is.
+///
+/// ```rust, ignore
+/// let config = StaticConfig::default().minify_html(HtmlMinify::new().inline_css(4096));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HtmlMinify {
+    inline_css: u64,
+}
+
+impl HtmlMinify {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace `<link rel="stylesheet">` tags for local stylesheets up to `max_bytes` with
+    /// their content, saving a request for small critical CSS.
+    ///
+    /// Inlined stylesheets are read with the page, so a changed stylesheet is picked up
+    /// once the page itself changes.
+    pub fn inline_css(mut self, max_bytes: u64) -> Self {
+        self.inline_css = max_bytes;
+        self
+    }
+
+    /// The minified form of `html`.
+    pub fn minify(&self, html: &str) -> String {
+        let mut out = String::with_capacity(html.len());
+        let mut rest = html;
+        while !rest.is_empty() {
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                let end = comment.find("-->").map_or(comment.len(), |end| end + 3);
+                if comment.starts_with("[if") || comment.starts_with("<![endif]") {
+                    out.push_str(&rest[..4 + end]);
+                }
+                rest = &comment[end..];
+            } else if rest.starts_with('<') {
+                let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+                let tag = &rest[..end];
+                out.push_str(tag);
+                rest = &rest[end..];
+                if let Some(name) = raw_element(tag) {
+                    let close = rest
+                        .to_ascii_lowercase()
+                        .find(&format!("</{name}"))
+                        .unwrap_or(rest.len());
+                    out.push_str(&rest[..close]);
+                    rest = &rest[close..];
+                }
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                collapse_whitespace(&rest[..end], &mut out);
+                rest = &rest[end..];
+            }
+        }
+        out.trim().to_string()
+    }
+
+    // Inline small local stylesheets read from `config`
+    fn inline_stylesheets(&self, html: String, config: &StaticConfig) -> String {
+        if self.inline_css == 0 {
+            return html;
+        }
+        let mut out = String::with_capacity(html.len());
+        let mut rest = html.as_str();
+        while let Some(start) = rest.find("<link") {
+            out.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('>')
+                .map_or(rest.len(), |end| start + end + 1);
+            let tag = &rest[start..end];
+            match self.stylesheet(tag, config) {
+                Some(css) => {
+                    out.push_str("<style>");
+                    out.push_str(css.trim());
+                    out.push_str("</style>");
+                }
+                None => out.push_str(tag),
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    // The content of a `<link>` tag's stylesheet, if it's local, small and safe to inline
+    fn stylesheet(&self, tag: &str, config: &StaticConfig) -> Option<String> {
+        let rel = attribute(tag, "rel")?;
+        if !rel.eq_ignore_ascii_case("stylesheet") || attribute(tag, "media").is_some() {
+            return None;
+        }
+        let href = attribute(tag, "href")
+            .filter(|href| href.starts_with('/') && !href.starts_with("//"))?;
+        let path = href.split(['?', '#']).next()?.trim_start_matches('/');
+        let css = config
+            .read(path)
+            .filter(|css| css.len() as u64 <= self.inline_css)?;
+        let css = String::from_utf8(css.into_owned()).ok()?;
+        (!css.to_ascii_lowercase().contains("</style")).then_some(css)
+    }
+}
+
+// The raw-text element a start tag opens, if any
+fn raw_element(tag: &str) -> Option<&'static str> {
+    let name: String = tag
+        .strip_prefix('<')?
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect();
+    RAW_ELEMENTS
+        .into_iter()
+        .find(|raw| raw.eq_ignore_ascii_case(&name))
+        .filter(|_| !tag.ends_with("/>"))
+}
+
+// Collapse each whitespace run in text to one space, or one newline if it had any
+fn collapse_whitespace(text: &str, out: &mut String) {
+    let mut pending: Option<char> = None;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            pending = match (pending, c) {
+                (Some('\n'), _) | (_, '\n') => Some('\n'),
+                _ => Some(' '),
+            };
+            continue;
+        }
+        if let Some(space) = pending.take() {
+            push_space(out, space);
+        }
+        out.push(c);
+    }
+    if let Some(space) = pending {
+        push_space(out, space);
+    }
+}
+
+// Whitespace around a removed comment would otherwise end up doubled
+fn push_space(out: &mut String, space: char) {
+    if out.ends_with([' ', '\n']) {
+        if space == '\n' && out.ends_with(' ') {
+            out.pop();
+            out.push('\n');
+        }
+    } else {
+        out.push(space);
+    }
+}
+
+// A quoted attribute's value in a tag, e.g. `href="/app.css"`
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let lower = tag.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(found) = lower[search..].find(name) {
+        let start = search + found;
+        search = start + name.len();
+        let preceded = lower[..start].ends_with(|c: char| c.is_ascii_whitespace());
+        let rest = &tag[search..];
+        let Some(quote) = rest
+            .strip_prefix('=')
+            .and_then(|value| value.chars().next())
+        else {
+            continue;
+        };
+        if preceded && (quote == '"' || quote == '\'') {
+            let value = &rest[2..];
+            return value.find(quote).map(|end| &value[..end]);
+        }
+    }
+    None
+}
+
+/// Minified pages for one config, shared by every router built from it.
+#[derive(Debug)]
+pub(crate) struct Store {
+    settings: HtmlMinify,
+    pages: Mutex<HashMap<String, (String, Bytes)>>,
+}
+
+impl PartialEq for Store {
+    fn eq(&self, other: &Self) -> bool {
+        self.settings == other.settings
+    }
+}
+
+impl Store {
+    pub(crate) fn new(settings: HtmlMinify) -> Self {
+        Self {
+            settings,
+            pages: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The minified page at `path`, relative to the source, read on first use and again
+    /// whenever `version` changes.
+    pub(crate) fn page(&self, config: &StaticConfig, path: &str, version: &str) -> Option<Bytes> {
+        {
+            let pages = self.pages.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((cached, html)) = pages.get(path)
+                && cached == version
+            {
+                return Some(html.clone());
+            }
+        }
+
+        let html = config.read(path)?;
+        let html = self.settings.minify(&String::from_utf8_lossy(&html));
+        let html = Bytes::from(self.settings.inline_stylesheets(html, config));
+        self.pages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(path.to_string(), (version.to_string(), html.clone()));
+        Some(html)
+    }
+
+    /// Drop every minified page.
+    #[cfg(feature = "dev")]
+    pub(crate) fn clear(&self) {
+        self.pages.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// Whether `path` names an HTML page.
+pub(crate) fn is_html(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("html"))
+}
+
+/// The minified page at `path`, or `None` when minification is off or `path` isn't HTML.
+pub(crate) async fn page(config: &StaticConfig, path: &str) -> Option<Bytes> {
+    let minifier = config.html_minifier().filter(|_| is_html(path))?;
+    let version = version(config, path).await?;
+    minifier.page(config, path, &version)
+}
+
+/// An HTML response for a minified page, with its ETag.
+pub(crate) fn response(html: Bytes) -> (Response, String) {
+    let etag = etag_for(&html);
+    (Html(html).into_response(), etag)
+}
+
+// What identifies the current content of `path`: its size and modification time on disk,
+// and nothing for embedded files, which can't change
+async fn version(config: &StaticConfig, path: &str) -> Option<String> {
+    match config.source() {
+        Source::Directory(root) => {
+            let metadata = tokio::fs::metadata(root.join(path)).await.ok()?;
+            conditional::file_etag(&metadata, None)
+        }
+        #[cfg(feature = "embed")]
+        Source::Embedded(_) => Some(String::new()),
+    }
+}
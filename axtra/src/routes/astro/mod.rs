@@ -0,0 +1,467 @@
+//! Static file and single page app (SPA) routes for Astro builds.
+//!
+//! - [`serve_static_files`] / [`serve_static_files_with`]: serve a build directory with compression and cache headers.
+//! - [`serve_spa`] / [`serve_spa_with`]: serve an SPA's index for every route under a prefix.
+//! - [`AstroRouter`]: mount several SPAs and the static files together, in the right order.
+//! - [`StaticConfig`]: where the files live (defaults to `./dist`, or embedded with the `embed` feature).
+//! - [`CacheRules`]: `Cache-Control` policies by path prefix or extension.
+//! - [`AssetCache`]: keep hot, small files in memory.
+//! - [`Compression`] / [`CompressionLevel`]: gzip/brotli/zstd, levels, size and type thresholds, and precompressed `.br`/`.zst`/`.gz` siblings.
+//! - [`AssetManifest`] / [`asset_url`]: fingerprinted asset URLs from Vite's `manifest.json`.
+//! - [`HtmlMinify`]: strip comments and whitespace from served HTML, optionally inlining small stylesheets.
+//! - [`EnvInjection`]: per-environment values injected into SPA index pages at serve time.
+//! - [`DynamicRoute`]: serve the pages prerendered for dynamic routes like `/blog/:slug`.
+//! - [`Locales`]: locale redirects for Astro i18n builds.
+//! - [`PathNormalization`]: one canonical URL per page (trailing slashes, duplicate slashes, case).
+//! - [`SsrProxy`] / [`ssr_proxy`]: forward everything else to an Astro SSR server (`ssr` feature).
+//! - [`NotFoundFormat`]: JSON or a minimal body instead of the 404 page for API clients.
+//! - [`PathSecurity`]: reject traversal attempts and symlinks leaving the root, optionally banning with the bouncer.
+//! - [`AccessGuard`]: basic auth or an unlock token for private previews.
+//! - [`ErrorPages`] / [`set_error_pages`]: error pages shared by the static 404 and HTML `AppError`s.
+
+mod asset_cache;
+mod cache;
+mod compression;
+mod conditional;
+mod config;
+mod dynamic;
+#[cfg(feature = "embed")]
+mod embed;
+mod error_pages;
+mod guard;
+mod hardening;
+mod inject;
+mod locale;
+mod manifest;
+mod minify;
+mod normalize;
+mod not_found;
+mod range;
+mod router;
+#[cfg(feature = "ssr")]
+mod ssr;
+#[cfg(feature = "dev")]
+mod watch;
+
+pub use asset_cache::AssetCache;
+pub use cache::CacheRules;
+pub use compression::Compression;
+pub use config::StaticConfig;
+pub use dynamic::DynamicRoute;
+pub use error_pages::ErrorPages;
+pub use guard::AccessGuard;
+pub use hardening::PathSecurity;
+#[cfg(feature = "embed")]
+pub use include_dir::Dir;
+pub use inject::EnvInjection;
+pub use locale::Locales;
+pub use manifest::{AssetManifest, ManifestEntry, asset_manifest, asset_url, set_asset_manifest};
+pub use minify::HtmlMinify;
+pub use normalize::{PathNormalization, TrailingSlash};
+pub use not_found::NotFoundFormat;
+pub use router::AstroRouter;
+#[cfg(feature = "ssr")]
+pub use ssr::{SsrProxy, ssr_proxy};
+pub use tower_http::compression::CompressionLevel;
+
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    sync::{Arc, OnceLock},
+};
+
+use axum::{
+    RequestPartsExt, Router,
+    body::Body,
+    extract::{OriginalUri, Request},
+    middleware,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+use http::{StatusCode, header};
+use tower::{ServiceExt, util::BoxCloneSyncService};
+use tower_http::services::{ServeDir, ServeFile};
+
+use config::Source;
+
+static ERROR_PAGES: OnceLock<StaticConfig> = OnceLock::new();
+
+/// Render HTML `AppError`s with `config`'s [`ErrorPages`], read from its source
+/// (defaults to `./dist/404.html` and `./dist/500.html`).
+///
+/// Returns false if error pages were already configured.
+pub fn set_error_pages(config: StaticConfig) -> bool {
+    ERROR_PAGES.set(config).is_ok()
+}
+
+/// Read the error page for `status` from the registered error page source.
+pub(crate) fn error_page(status: StatusCode) -> Option<Cow<'static, [u8]>> {
+    let config = ERROR_PAGES.get_or_init(StaticConfig::default);
+    config
+        .error_page_settings()
+        .page_for(status)
+        .and_then(|page| config.read(page))
+}
+
+/// Serve `./dist/{path}/index.html` for `/{path}` and every route below it.
+pub fn serve_spa<S>(path: impl AsRef<str>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    serve_spa_with(path, &StaticConfig::default())
+}
+
+/// Serve `{root}/{path}/{index}` for `/{path}` and every route below it.
+///
+/// Only those routes are guarded by the config's [`AccessGuard`], so its unlock route isn't
+/// served here. For a token-protected SPA, also mount [`serve_static_files_with`] with the
+/// same config, which answers the unlock route:
+///
+/// ```rust, ignore
+/// let config = StaticConfig::default().protect(AccessGuard::new().token(token));
+/// let app = Router::new()
+///     .merge(serve_spa_with("app", &config))
+///     .merge(serve_static_files_with(&config));
+/// ```
+pub fn serve_spa_with<S>(path: impl AsRef<str>, config: &StaticConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let path = path.as_ref();
+    let index = format!("{path}/{}", config.index_name());
+    let canonical_root = match config.source() {
+        Source::Directory(root) => Some(hardening::canonical_root(root)),
+        #[cfg(feature = "embed")]
+        Source::Embedded(_) => None,
+    };
+
+    let serve_index = {
+        let config = config.clone();
+        move |mut req: Request<Body>| {
+            let config = config.clone();
+            let index = index.clone();
+            let canonical_root = canonical_root.clone();
+            async move {
+                if let (Source::Directory(root), Some(canonical_root)) =
+                    (config.source(), &canonical_root)
+                {
+                    let file = root.join(&index);
+                    let security = config.path_security_settings();
+                    if !security.allows_file(canonical_root, &file).await {
+                        return not_found_page(&config, None);
+                    }
+                    security
+                        .secure_precompressed(
+                            canonical_root,
+                            &file,
+                            config.compression_settings(),
+                            req.headers_mut(),
+                        )
+                        .await;
+                }
+                if let Some(env) = config.env_injection() {
+                    return inject::serve(&config, env, &index, req.headers()).await;
+                }
+                match config.source() {
+                    Source::Directory(root) => {
+                        if let Some(html) = minify::page(&config, &index).await {
+                            let (mut res, etag) = minify::response(html);
+                            no_cache(&mut res);
+                            return conditional::respond(req.headers(), &etag, res);
+                        }
+                        let file = root.join(&index);
+                        let headers = req.headers().clone();
+                        let mut res = config
+                            .compression_settings()
+                            .serve_file(ServeFile::new(&file))
+                            .oneshot(req)
+                            .await
+                            .into_response();
+                        no_cache(&mut res);
+                        let etag = tokio::fs::metadata(&file).await.ok().and_then(|metadata| {
+                            conditional::file_etag(
+                                &metadata,
+                                res.headers().get(header::CONTENT_ENCODING),
+                            )
+                        });
+                        match etag {
+                            Some(etag) => conditional::respond(&headers, &etag, res),
+                            None => res,
+                        }
+                    }
+                    #[cfg(feature = "embed")]
+                    Source::Embedded(dir) => {
+                        let mut res = embed::serve_file(dir, &config, &index, req.headers());
+                        no_cache(&mut res);
+                        embed::revalidate(req.headers(), res)
+                    }
+                }
+            }
+        }
+    };
+
+    #[cfg(feature = "dev")]
+    config.start_watching(None);
+
+    let router = Router::new()
+        .route(&format!("/{path}"), get(serve_index.clone()))
+        .route(&format!("/{path}/"), get(serve_index.clone()))
+        .route(&format!("/{path}/{{*route}}"), get(serve_index));
+    secured(guarded(normalized(router, config), config), config)
+        .layer(config.compression_settings().layer())
+}
+
+/// Serve files from `./dist` with compression and cache headers, falling back to `404.html`.
+pub fn serve_static_files<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    serve_static_files_with(&StaticConfig::default())
+}
+
+/// Serve files from the configured source with compression and cache headers.
+pub fn serve_static_files_with<S>(config: &StaticConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let compression_layer = config.compression_settings().layer();
+
+    let router = match config.source() {
+        Source::Directory(root) => {
+            let serve_dir = config
+                .compression_settings()
+                .serve_dir(ServeDir::new(root).append_index_html_on_directories(true));
+            let not_found = config.not_found_page_name().map(|page| root.join(page));
+            let fallback_service: BoxCloneSyncService<Request, Response, Infallible> =
+                match not_found {
+                    Some(not_found) => {
+                        BoxCloneSyncService::new(ServiceExt::<Request>::map_response(
+                            serve_dir.not_found_service(
+                                config
+                                    .compression_settings()
+                                    .serve_file(ServeFile::new(not_found)),
+                            ),
+                            IntoResponse::into_response,
+                        ))
+                    }
+                    None => BoxCloneSyncService::new(ServiceExt::<Request>::map_response(
+                        serve_dir,
+                        IntoResponse::into_response,
+                    )),
+                };
+            let index = config.index_name().to_owned();
+            let canonical_root = hardening::canonical_root(root);
+            let root = root.clone();
+            let config = config.clone();
+            let cache_rules = config.cache_settings().clone();
+            let compression = config.compression_settings().clone();
+            let asset_cache = config
+                .asset_cache_settings()
+                .map(|settings| Arc::new(asset_cache::Store::new(settings.clone())));
+            #[cfg(feature = "dev")]
+            config.start_watching(asset_cache.as_ref());
+
+            Router::new().fallback(get(move |req: Request| async move {
+                let (mut parts, body) = req.into_parts();
+                let uri: OriginalUri = parts.extract().await?;
+
+                // ServeDir only knows index.html, so point directory requests at a custom index
+                if index != "index.html" && parts.uri.path().ends_with('/') {
+                    let path = format!("{}{index}", parts.uri.path());
+                    if let Ok(rewritten) = path.parse() {
+                        parts.uri = rewritten;
+                    }
+                }
+
+                let file = conditional::resolve(&root, parts.uri.path(), &index);
+                let metadata = match &file {
+                    Some(file) => tokio::fs::metadata(file).await.ok(),
+                    None => None,
+                };
+                if let Some(file) = &file
+                    && metadata.is_some()
+                    && !config
+                        .path_security_settings()
+                        .allows_file(&canonical_root, file)
+                        .await
+                {
+                    return Ok(not_found_page(&config, None));
+                }
+                if let Some(file) = &file {
+                    config
+                        .path_security_settings()
+                        .secure_precompressed(
+                            &canonical_root,
+                            file,
+                            &compression,
+                            &mut parts.headers,
+                        )
+                        .await;
+                }
+                if let Some(metadata) = &metadata {
+                    range::prepare(
+                        &mut parts.headers,
+                        conditional::file_etag(metadata, None).as_deref(),
+                        metadata.modified().ok(),
+                    );
+                }
+                let headers = parts.headers.clone();
+
+                let page = file
+                    .as_deref()
+                    .filter(|_| metadata.as_ref().is_some_and(|metadata| metadata.is_file()))
+                    .and_then(|file| file.strip_prefix(&root).ok())
+                    .and_then(|relative| relative.to_str());
+                if let Some(page) = page
+                    && let Some(html) = minify::page(&config, page).await
+                {
+                    let (mut res, etag) = minify::response(html);
+                    cache_rules.apply(uri.path(), &mut res);
+                    return Ok(conditional::respond(&headers, &etag, res));
+                }
+
+                let cache_key = match (&asset_cache, &file) {
+                    (Some(store), Some(file)) => {
+                        store.key(file, &headers, &compression.precompressed_for(&headers))
+                    }
+                    _ => None,
+                };
+                if let (Some(store), Some(key), Some(metadata)) =
+                    (&asset_cache, &cache_key, &metadata)
+                    && let Some((mut res, etag)) = store.get(key, |encoding| {
+                        let encoding = encoding.and_then(|encoding| encoding.parse().ok());
+                        conditional::file_etag(metadata, encoding.as_ref())
+                    })
+                {
+                    cache_rules.apply(uri.path(), &mut res);
+                    return Ok(conditional::respond(&headers, &etag, res));
+                }
+
+                let req = Request::from_parts(parts, body);
+                match fallback_service.oneshot(req).await {
+                    Ok(mut res) => {
+                        let etag = metadata.and_then(|metadata| {
+                            conditional::file_etag(
+                                &metadata,
+                                res.headers().get(header::CONTENT_ENCODING),
+                            )
+                        });
+                        let Some(etag) = etag else {
+                            cache_rules.apply(uri.path(), &mut res);
+                            return Ok(res);
+                        };
+                        if let (Some(store), Some(key)) = (&asset_cache, cache_key) {
+                            res = store.fill(key, &etag, res).await;
+                        }
+                        cache_rules.apply(uri.path(), &mut res);
+                        Ok(conditional::respond(&headers, &etag, res))
+                    }
+                    Err(e) => {
+                        tracing::error!("Static file serve error: {e}");
+                        Err(e)
+                    }
+                }
+            }))
+        }
+        #[cfg(feature = "embed")]
+        Source::Embedded(dir) => {
+            let dir = *dir;
+            let config = config.clone();
+            Router::new().fallback(get(
+                move |original: OriginalUri, uri: http::Uri, headers: http::HeaderMap| async move {
+                    let mut res = embed::serve(dir, &config, uri.path(), &headers);
+                    config.cache_settings().apply(original.path(), &mut res);
+                    embed::revalidate(&headers, res)
+                },
+            ))
+        }
+    };
+
+    let router = match config.dynamic_route_settings() {
+        [] => router,
+        _ => router.layer(middleware::from_fn_with_state(
+            Arc::new(config.clone()),
+            dynamic::route,
+        )),
+    };
+
+    let router = router.layer(middleware::from_fn_with_state(
+        Arc::new(config.clone()),
+        not_found::route,
+    ));
+
+    #[cfg(feature = "ssr")]
+    let router = match config.ssr_proxy() {
+        Some(proxy) => router.layer(middleware::from_fn_with_state(
+            Arc::new(proxy.clone()),
+            ssr::route,
+        )),
+        None => router,
+    };
+
+    let router = match config.locale_settings() {
+        Some(locales) => router.layer(middleware::from_fn_with_state(
+            Arc::new(locales.clone()),
+            locale::route,
+        )),
+        None => router,
+    };
+    secured(guarded(normalized(router, config), config), config).layer(compression_layer)
+}
+
+fn normalized<S>(router: Router<S>, config: &StaticConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    match config.normalization_settings() {
+        Some(_) => router.layer(middleware::from_fn_with_state(
+            Arc::new(config.clone()),
+            normalize::route,
+        )),
+        None => router,
+    }
+}
+
+fn guarded<S>(router: Router<S>, config: &StaticConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    match config.access_guard() {
+        Some(_) => router.layer(middleware::from_fn_with_state(
+            Arc::new(config.clone()),
+            guard::route,
+        )),
+        None => router,
+    }
+}
+
+fn secured<S>(router: Router<S>, config: &StaticConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(middleware::from_fn_with_state(
+        Arc::new(config.clone()),
+        hardening::route,
+    ))
+}
+
+/// The not-found page, `page` or the configured one, with `404`.
+fn not_found_page(config: &StaticConfig, page: Option<&str>) -> Response {
+    let page = page
+        .or(config.not_found_page_name())
+        .and_then(|page| config.read(page));
+    match page {
+        Some(page) => (StatusCode::NOT_FOUND, Html(page.into_owned())).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+// Always revalidate index.html, so a deploy is picked up on the next load
+fn no_cache(res: &mut Response) {
+    res.headers_mut()
+        .insert(header::CACHE_CONTROL, "no-cache".parse().unwrap());
+    res.headers_mut()
+        .insert(header::PRAGMA, "no-cache".parse().unwrap());
+    res.headers_mut()
+        .insert(header::EXPIRES, "0".parse().unwrap());
+}
@@ -0,0 +1,140 @@
+//! Canonical URLs for static and SPA routes.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+
+use crate::routes::astro::StaticConfig;
+
+/// How paths end, matching Astro's `trailingSlash` setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// Redirect `/about` to `/about/`.
+    Always,
+    /// Redirect `/about/` to `/about`, serving the directory's index at `/about`.
+    Never,
+    /// Serve both forms.
+    #[default]
+    Ignore,
+}
+
+/// Redirect requests to one canonical URL per page, so the same page isn't served at two.
+///
+/// Paths whose last segment has a file extension (`/_astro/app.js`) keep their form
+/// regardless of [`TrailingSlash`]. Redirects are permanent (`308`).
+///
+/// ```rust, ignore
+/// let config = StaticConfig::default().normalize(
+///     PathNormalization::new()
+///         .trailing_slash(TrailingSlash::Never)
+///         .collapse_slashes(true),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathNormalization {
+    trailing_slash: TrailingSlash,
+    collapse_slashes: bool,
+    lowercase: bool,
+}
+
+impl PathNormalization {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> Self {
+        self.trailing_slash = trailing_slash;
+        self
+    }
+
+    /// Redirect `//docs///intro` to `/docs/intro`.
+    pub fn collapse_slashes(mut self, enabled: bool) -> Self {
+        self.collapse_slashes = enabled;
+        self
+    }
+
+    /// Redirect paths with uppercase letters to their lowercase form.
+    pub fn lowercase(mut self, enabled: bool) -> Self {
+        self.lowercase = enabled;
+        self
+    }
+
+    /// The canonical form of `path`.
+    pub fn normalize(&self, path: &str) -> String {
+        let mut path = path.to_string();
+        if self.collapse_slashes {
+            let mut collapsed = String::with_capacity(path.len());
+            for c in path.chars() {
+                if !(c == '/' && collapsed.ends_with('/')) {
+                    collapsed.push(c);
+                }
+            }
+            path = collapsed;
+        }
+        if self.lowercase {
+            path = path.to_ascii_lowercase();
+        }
+        if path == "/" || is_file(&path) {
+            return path;
+        }
+        match self.trailing_slash {
+            TrailingSlash::Always if !path.ends_with('/') => path.push('/'),
+            TrailingSlash::Never => {
+                while path.len() > 1 && path.ends_with('/') {
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+        path
+    }
+}
+
+// Whether the last path segment looks like a file name
+fn is_file(path: &str) -> bool {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .is_some_and(|segment| segment.contains('.'))
+}
+
+/// Middleware redirecting to canonical paths and, with [`TrailingSlash::Never`], serving
+/// directory indexes without the trailing slash.
+pub(crate) async fn route(
+    State(config): State<Arc<StaticConfig>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(normalization) = config.normalization_settings() else {
+        return next.run(req).await;
+    };
+
+    let path = req.uri().path();
+    let normalized = normalization.normalize(path);
+    if normalized != path {
+        let location = match req.uri().query() {
+            Some(query) => format!("{normalized}?{query}"),
+            None => normalized,
+        };
+        return Redirect::permanent(&location).into_response();
+    }
+
+    // `/about` would otherwise be redirected to `/about/` by the file server
+    if normalization.trailing_slash == TrailingSlash::Never
+        && path != "/"
+        && config.is_dir(path).await
+    {
+        let rewritten = match req.uri().query() {
+            Some(query) => format!("{path}/?{query}"),
+            None => format!("{path}/"),
+        };
+        if let Ok(uri) = rewritten.parse() {
+            *req.uri_mut() = uri;
+        }
+    }
+    next.run(req).await
+}
@@ -0,0 +1,135 @@
+//! Answering missing static files in the format the client asked for.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{app_error, routes::astro::StaticConfig};
+
+/// How the static router answers missing files for clients that don't want HTML.
+///
+/// The HTML not-found page is only served to requests accepting `text/html` (browsers,
+/// and clients sending no `Accept` or `*/*`). Requests under an API prefix (defaults to
+/// `/api/`) or preferring JSON get a JSON `AppError::NotFound` instead, or the
+/// [`minimal_body`](Self::minimal_body) if one is set.
+///
+/// ```rust, ignore
+/// let config = StaticConfig::default().not_found_format(
+///     NotFoundFormat::new().api_prefix("/graphql").minimal_body("Not Found"),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotFoundFormat {
+    api_prefixes: Vec<String>,
+    minimal_body: Option<String>,
+}
+
+impl Default for NotFoundFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotFoundFormat {
+    pub fn new() -> Self {
+        Self {
+            api_prefixes: vec!["/api/".to_string()],
+            minimal_body: None,
+        }
+    }
+
+    /// Treat paths under `prefix` as API routes, which never get the HTML page.
+    pub fn api_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.api_prefixes.push(prefix.into());
+        self
+    }
+
+    /// Don't treat any path as an API route, relying on `Accept` alone.
+    pub fn without_api_prefixes(mut self) -> Self {
+        self.api_prefixes.clear();
+        self
+    }
+
+    /// Answer with this `text/plain` body instead of JSON.
+    pub fn minimal_body(mut self, body: impl Into<String>) -> Self {
+        self.minimal_body = Some(body.into());
+        self
+    }
+
+    // Whether a missing file should be answered with the HTML page
+    fn wants_html(&self, path: &str, headers: &HeaderMap) -> bool {
+        let api = self.api_prefixes.iter().any(|prefix| {
+            path.starts_with(prefix.as_str()) || path == prefix.trim_end_matches('/')
+        });
+        !api && accepts_html(headers)
+    }
+
+    fn response(&self, path: &str) -> Response {
+        match &self.minimal_body {
+            Some(body) => {
+                let mut res = (StatusCode::NOT_FOUND, body.clone()).into_response();
+                res.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/plain; charset=utf-8"),
+                );
+                res
+            }
+            None => app_error!(not_found, json, path).into_response(),
+        }
+    }
+}
+
+// Whether the client prefers HTML over JSON, going by `Accept`
+fn accepts_html(headers: &HeaderMap) -> bool {
+    let mut ranges: Vec<(&str, f32)> = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let media = parts.next().filter(|media| !media.is_empty())?;
+            let quality = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((media, quality))
+        })
+        .collect();
+    if ranges.is_empty() {
+        return true;
+    }
+    // Stable, so equal qualities keep the client's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let html = |media: &str| media == "text/html" || media == "application/xhtml+xml";
+    let json = |media: &str| media == "application/json" || media.ends_with("+json");
+    match ranges.iter().find(|(media, _)| html(media) || json(media)) {
+        Some((media, _)) => html(media),
+        None => ranges
+            .iter()
+            .any(|(media, _)| *media == "*/*" || *media == "text/*"),
+    }
+}
+
+/// Middleware replacing the HTML not-found page for clients that don't want HTML.
+pub(crate) async fn route(
+    State(config): State<Arc<StaticConfig>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let format = config.not_found_settings();
+    let path = req.uri().path().to_string();
+    let wants_html = format.wants_html(&path, req.headers());
+
+    let res = next.run(req).await;
+    if res.status() != StatusCode::NOT_FOUND || wants_html {
+        return res;
+    }
+    format.response(&path)
+}
@@ -0,0 +1,76 @@
+//! Byte range requests, so browsers can seek in media and large documents.
+
+use std::{ops::RangeInclusive, time::SystemTime};
+
+use axum::http::{HeaderMap, header};
+use httpdate::HttpDate;
+
+/// The bytes a `Range` header asks for: `None` when it isn't a single byte range, so the
+/// whole file is served, and `Err` when it starts past the end.
+pub(crate) fn parse(value: &str, len: u64) -> Option<Result<RangeInclusive<u64>, ()>> {
+    let (start, end) = value
+        .trim()
+        .strip_prefix("bytes=")
+        .filter(|spec| !spec.contains(','))?
+        .split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    // `bytes=-500` is the last 500 bytes
+    if start.is_empty() {
+        let suffix = end.parse::<u64>().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(Err(()));
+        }
+        return Some(Ok(len.saturating_sub(suffix)..=len - 1));
+    }
+
+    let start = start.parse::<u64>().ok()?;
+    let end = match end {
+        "" => u64::MAX,
+        end => end.parse::<u64>().ok().filter(|end| *end >= start)?,
+    };
+    if start >= len {
+        return Some(Err(()));
+    }
+    Some(Ok(start..=end.min(len - 1)))
+}
+
+/// Whether `If-Range`, if sent, still names the current version of the file.
+pub(crate) fn if_range_matches(
+    headers: &HeaderMap,
+    etag: Option<&str>,
+    modified: Option<SystemTime>,
+) -> bool {
+    let Some(value) = headers
+        .get(header::IF_RANGE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return headers.get(header::IF_RANGE).is_none();
+    };
+    if value.starts_with('"') {
+        return etag == Some(value);
+    }
+    match (value.parse::<HttpDate>(), modified) {
+        (Ok(date), Some(modified)) => date == HttpDate::from(modified),
+        _ => false,
+    }
+}
+
+/// Prepare a request for a file whose current version is `etag` / `modified`.
+///
+/// `Range` is dropped when it isn't a single byte range or `If-Range` names an older
+/// version, so the whole file is served. Otherwise `Accept-Encoding` is dropped: offsets
+/// address the uncompressed file, and the response is left uncompressed.
+pub(crate) fn prepare(headers: &mut HeaderMap, etag: Option<&str>, modified: Option<SystemTime>) {
+    let Some(range) = headers.get(header::RANGE) else {
+        return;
+    };
+    let single = range
+        .to_str()
+        .is_ok_and(|value| parse(value, u64::MAX).is_some());
+    if single && if_range_matches(headers, etag, modified) {
+        headers.remove(header::ACCEPT_ENCODING);
+    } else {
+        headers.remove(header::RANGE);
+    }
+}
@@ -0,0 +1,82 @@
+//! Mounting several SPAs and the static build as one router.
+
+use std::path::PathBuf;
+
+use axum::Router;
+
+use crate::routes::astro::{StaticConfig, serve_spa_with, serve_static_files_with};
+
+/// Builder composing SPA routes and the static file fallback over one [`StaticConfig`],
+/// so they share compression, cache rules and the asset root.
+///
+/// SPAs are mounted most specific first and static files last, as the router's fallback,
+/// so `/admin/...` and `/app/...` reach their SPA and everything else is served from disk.
+///
+/// ```rust, ignore
+/// let app = Router::new().merge(
+///     AstroRouter::new("./dist")
+///         .spa("admin")
+///         .spa("app")
+///         .static_assets()
+///         .into_router(),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct AstroRouter {
+    config: StaticConfig,
+    spas: Vec<String>,
+    static_assets: bool,
+}
+
+impl AstroRouter {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::with_config(StaticConfig::new(root))
+    }
+
+    pub fn with_config(config: StaticConfig) -> Self {
+        Self {
+            config,
+            spas: Vec::new(),
+            static_assets: false,
+        }
+    }
+
+    /// Serve `{path}/index.html` for `/{path}` and every route below it.
+    ///
+    /// Panics if `path` is empty: the root is served by [`static_assets`](Self::static_assets).
+    pub fn spa(mut self, path: impl AsRef<str>) -> Self {
+        let path = path.as_ref().trim_matches('/');
+        assert!(
+            !path.is_empty(),
+            "AstroRouter::spa needs a path; the root is served by static_assets()"
+        );
+        if !self.spas.iter().any(|existing| existing == path) {
+            self.spas.push(path.to_string());
+        }
+        self
+    }
+
+    /// Serve the rest of the build as static files, falling back to the not-found page.
+    pub fn static_assets(mut self) -> Self {
+        self.static_assets = true;
+        self
+    }
+
+    pub fn into_router<S>(mut self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        // Deepest paths first, so nested SPAs (`app/admin`) are registered before their parents
+        self.spas
+            .sort_by_key(|path| std::cmp::Reverse(path.split('/').count()));
+
+        let mut router = Router::new();
+        for path in &self.spas {
+            router = router.merge(serve_spa_with(path, &self.config));
+        }
+        if self.static_assets {
+            router = router.merge(serve_static_files_with(&self.config));
+        }
+        router
+    }
+}
@@ -0,0 +1,251 @@
+//! Forwarding requests to an Astro SSR server, for hybrid rendering.
+
+use std::{
+    convert::Infallible,
+    fmt,
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hyper_util::{
+    client::legacy::{Client, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use tower::Service;
+
+// Headers describing one connection, which a proxy must not forward
+const HOP_BY_HOP: [HeaderName; 8] = [
+    header::CONNECTION,
+    HeaderName::from_static("keep-alive"),
+    header::PROXY_AUTHENTICATE,
+    header::PROXY_AUTHORIZATION,
+    header::TE,
+    header::TRAILER,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+/// A reverse proxy to a Node Astro SSR server (`@astrojs/node` in standalone mode).
+///
+/// Request and response bodies are streamed, headers are forwarded (minus hop-by-hop
+/// ones) and `X-Forwarded-For`/`-Host`/`-Proto` are set. Unreachable upstreams get
+/// `502 Bad Gateway`, and slow ones `504 Gateway Timeout`. Only `http://` upstreams are
+/// supported, which suits an SSR server on the same host or network.
+///
+/// ```rust, ignore
+/// // Static files first, everything else rendered by Astro
+/// let config = StaticConfig::default().ssr(SsrProxy::new("http://127.0.0.1:4321"));
+/// let app = Router::new()
+///     .nest("/api", api_routes())
+///     .merge(serve_static_files_with(&config));
+/// ```
+#[derive(Clone)]
+pub struct SsrProxy {
+    upstream: Uri,
+    timeout: Option<Duration>,
+    client: Client<HttpConnector, Body>,
+}
+
+impl fmt::Debug for SsrProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SsrProxy")
+            .field("upstream", &self.upstream)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl PartialEq for SsrProxy {
+    fn eq(&self, other: &Self) -> bool {
+        self.upstream == other.upstream && self.timeout == other.timeout
+    }
+}
+
+impl SsrProxy {
+    /// Proxy to `upstream`, e.g. `http://127.0.0.1:4321`. A path in the URL prefixes every
+    /// forwarded path.
+    ///
+    /// Panics if `upstream` isn't an absolute `http://` URL.
+    pub fn new(upstream: impl AsRef<str>) -> Self {
+        let upstream: Uri = upstream
+            .as_ref()
+            .parse()
+            .expect("SsrProxy::new needs a valid upstream URL");
+        assert!(
+            upstream.scheme_str() == Some("http") && upstream.authority().is_some(),
+            "SsrProxy::new needs an absolute http:// upstream URL"
+        );
+        Self {
+            upstream,
+            timeout: Some(Duration::from_secs(30)),
+            client: Client::builder(TokioExecutor::new()).build_http(),
+        }
+    }
+
+    /// How long to wait for the upstream's response headers (defaults to 30 seconds).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Wait for the upstream as long as it takes.
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Forward `req` to the upstream and stream back its response.
+    pub(crate) async fn forward(&self, req: Request) -> Response {
+        let (mut parts, body) = req.into_parts();
+        let Some(uri) = self.upstream_uri(&parts.uri) else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+
+        let client_ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0.ip());
+        let host = parts.headers.get(header::HOST).cloned();
+        strip_hop_by_hop(&mut parts.headers);
+        forwarded_headers(&mut parts.headers, client_ip, host);
+        parts.headers.remove(header::HOST);
+        parts.uri = uri;
+        parts.version = axum::http::Version::HTTP_11;
+
+        let request = self.client.request(Request::from_parts(parts, body));
+        let result = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, request).await {
+                Ok(result) => result,
+                Err(_) => {
+                    tracing::warn!(upstream = %self.upstream, "SSR upstream timed out");
+                    return StatusCode::GATEWAY_TIMEOUT.into_response();
+                }
+            },
+            None => request.await,
+        };
+
+        match result {
+            Ok(res) => {
+                let mut res = res.map(Body::new);
+                strip_hop_by_hop(res.headers_mut());
+                res
+            }
+            Err(e) => {
+                tracing::error!(upstream = %self.upstream, "SSR upstream request failed: {e}");
+                StatusCode::BAD_GATEWAY.into_response()
+            }
+        }
+    }
+
+    // The upstream URL for a request's path and query
+    fn upstream_uri(&self, uri: &Uri) -> Option<Uri> {
+        let base = self.upstream.path().trim_end_matches('/');
+        let path_and_query = uri.path_and_query().map_or("/", |pq| pq.as_str());
+        Uri::builder()
+            .scheme("http")
+            .authority(self.upstream.authority()?.clone())
+            .path_and_query(format!("{base}{path_and_query}"))
+            .build()
+            .ok()
+    }
+}
+
+impl Service<Request> for SsrProxy {
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let proxy = self.clone();
+        Box::pin(async move { Ok(proxy.forward(req).await) })
+    }
+}
+
+fn strip_hop_by_hop(headers: &mut HeaderMap) {
+    // Headers named by `Connection` are connection-specific too
+    let named: Vec<HeaderName> = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| name.trim().parse().ok())
+        .collect();
+    for name in HOP_BY_HOP.iter().chain(&named) {
+        headers.remove(name);
+    }
+}
+
+fn forwarded_headers(
+    headers: &mut HeaderMap,
+    client_ip: Option<IpAddr>,
+    host: Option<HeaderValue>,
+) {
+    if let Some(ip) = client_ip {
+        let forwarded_for = match headers
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+        {
+            Some(existing) => format!("{existing}, {ip}"),
+            None => ip.to_string(),
+        };
+        if let Ok(value) = HeaderValue::from_str(&forwarded_for) {
+            headers.insert("x-forwarded-for", value);
+        }
+    }
+    if let Some(host) = host
+        && !headers.contains_key("x-forwarded-host")
+    {
+        headers.insert("x-forwarded-host", host);
+    }
+    if !headers.contains_key("x-forwarded-proto") {
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("http"));
+    }
+}
+
+/// A router forwarding every request it gets to an Astro SSR server at `upstream`, as its
+/// fallback.
+///
+/// To serve static files first, use [`StaticConfig::ssr`](crate::routes::astro::StaticConfig::ssr)
+/// instead. Panics if `upstream` isn't an absolute `http://` URL.
+pub fn ssr_proxy<S>(upstream: impl AsRef<str>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().fallback_service(SsrProxy::new(upstream))
+}
+
+/// Middleware sending requests the static files can't answer to the SSR server: missing
+/// files, and every method other than `GET` and `HEAD`.
+pub(crate) async fn route(
+    State(proxy): State<Arc<SsrProxy>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return proxy.forward(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let retry = Request::from_parts(parts.clone(), Body::empty());
+    let res = next.run(Request::from_parts(parts, body)).await;
+    if res.status() == StatusCode::NOT_FOUND {
+        return proxy.forward(retry).await;
+    }
+    res
+}
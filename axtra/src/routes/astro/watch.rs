@@ -0,0 +1,134 @@
+//! Dropping cached static files when the build directory changes, for development.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Once, Weak},
+    time::{Duration, SystemTime},
+};
+
+use crate::routes::astro::{asset_cache, asset_manifest, minify};
+
+/// A cache of static files the watcher clears on changes.
+pub(crate) trait Invalidate: Send + Sync {
+    fn invalidate(&self);
+}
+
+impl Invalidate for asset_cache::Store {
+    fn invalidate(&self) {
+        self.clear();
+    }
+}
+
+impl Invalidate for minify::Store {
+    fn invalidate(&self) {
+        self.clear();
+    }
+}
+
+/// Polls a directory tree and clears registered caches when any file in it changes.
+#[derive(Debug)]
+pub(crate) struct Watcher {
+    interval: Duration,
+    caches: Mutex<Vec<Weak<dyn Invalidate>>>,
+    started: Once,
+}
+
+impl PartialEq for Watcher {
+    fn eq(&self, other: &Self) -> bool {
+        self.interval == other.interval
+    }
+}
+
+impl Watcher {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            caches: Mutex::new(Vec::new()),
+            started: Once::new(),
+        }
+    }
+
+    /// Clear `cache` whenever a watched file changes, for as long as it's alive.
+    pub(crate) fn register(&self, cache: Weak<dyn Invalidate>) {
+        self.caches
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(cache);
+    }
+
+    /// Start polling `root`, once per watcher.
+    pub(crate) fn start(self: &Arc<Self>, root: &Path) {
+        self.started.call_once(|| {
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                tracing::warn!("Static file watcher needs a Tokio runtime; not watching");
+                return;
+            };
+            let watcher = Arc::downgrade(self);
+            let root = root.to_path_buf();
+            runtime.spawn(async move {
+                let mut previous = snapshot(root.clone()).await;
+                loop {
+                    let Some(interval) = watcher.upgrade().map(|watcher| watcher.interval) else {
+                        return;
+                    };
+                    tokio::time::sleep(interval).await;
+                    let current = snapshot(root.clone()).await;
+                    if current == previous {
+                        continue;
+                    }
+                    previous = current;
+                    match watcher.upgrade() {
+                        Some(watcher) => watcher.changed(&root),
+                        None => return,
+                    }
+                }
+            });
+        });
+    }
+
+    fn changed(&self, root: &Path) {
+        tracing::debug!(root = %root.display(), "Static files changed; clearing caches");
+        let mut caches = self.caches.lock().unwrap_or_else(|e| e.into_inner());
+        caches.retain(|cache| match cache.upgrade() {
+            Some(cache) => {
+                cache.invalidate();
+                true
+            }
+            None => false,
+        });
+        if let Some(manifest) = asset_manifest()
+            && let Err(e) = manifest.reload_if_changed()
+        {
+            tracing::warn!("Failed to reload asset manifest: {e}");
+        }
+    }
+}
+
+type Snapshot = HashMap<PathBuf, (SystemTime, u64)>;
+
+// The modification time and size of every file under `root`
+async fn snapshot(root: PathBuf) -> Snapshot {
+    tokio::task::spawn_blocking(move || {
+        let mut files = Snapshot::new();
+        let mut dirs = vec![root];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    dirs.push(entry.path());
+                } else if let Ok(modified) = metadata.modified() {
+                    files.insert(entry.path(), (modified, metadata.len()));
+                }
+            }
+        }
+        files
+    })
+    .await
+    .unwrap_or_default()
+}
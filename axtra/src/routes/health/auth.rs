@@ -0,0 +1,87 @@
+//! Access control for the detailed health payload.
+
+use std::net::IpAddr;
+
+use axum::http::{Extensions, HeaderMap, header};
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::net::{client_ip, constant_time_eq};
+
+/// Credentials allowed to see the detailed health report.
+///
+/// A request is authorized if it matches any configured bearer token, basic auth
+/// credential or allowlisted IP. Everyone else gets a minimal `{"status": "ok"}` and no
+/// checks are run on their behalf.
+///
+/// ```rust, ignore
+/// let health = HealthRouter::new()
+///     .check("postgres", PostgresCheck::new(pool))
+///     .auth(HealthAuth::new().bearer(env::var("HEALTH_TOKEN")?).allow_ip([10, 0, 0, 5].into()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HealthAuth {
+    bearer_tokens: Vec<String>,
+    basic_credentials: Vec<String>,
+    allowed_ips: Vec<IpAddr>,
+    trust_proxy: bool,
+}
+
+impl HealthAuth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept `Authorization: Bearer <token>`.
+    pub fn bearer(mut self, token: impl Into<String>) -> Self {
+        self.bearer_tokens.push(token.into());
+        self
+    }
+
+    /// Accept `Authorization: Basic` with these credentials.
+    pub fn basic(mut self, username: &str, password: &str) -> Self {
+        self.basic_credentials
+            .push(STANDARD.encode(format!("{username}:{password}")));
+        self
+    }
+
+    /// Accept any request from this IP.
+    pub fn allow_ip(mut self, ip: IpAddr) -> Self {
+        self.allowed_ips.push(ip);
+        self
+    }
+
+    /// Read the client IP from proxy headers; only enable behind a trusted proxy.
+    pub fn trust_proxy(mut self, trust: bool) -> Self {
+        self.trust_proxy = trust;
+        self
+    }
+
+    pub(crate) fn authorized(&self, headers: &HeaderMap, extensions: &Extensions) -> bool {
+        if !self.allowed_ips.is_empty()
+            && client_ip(headers, extensions, self.trust_proxy)
+                .is_some_and(|ip| self.allowed_ips.contains(&ip))
+        {
+            return true;
+        }
+
+        let Some(authorization) = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return false;
+        };
+
+        if let Some(token) = authorization.strip_prefix("Bearer ") {
+            return self
+                .bearer_tokens
+                .iter()
+                .any(|expected| constant_time_eq(expected.as_bytes(), token.trim().as_bytes()));
+        }
+        if let Some(credentials) = authorization.strip_prefix("Basic ") {
+            return self.basic_credentials.iter().any(|expected| {
+                constant_time_eq(expected.as_bytes(), credentials.trim().as_bytes())
+            });
+        }
+        false
+    }
+}
@@ -0,0 +1,69 @@
+//! Build metadata reported by the health and `/version` routes.
+
+use std::sync::OnceLock;
+
+use axum::{Json, Router, routing::get};
+use serde::Serialize;
+
+static BUILD_INFO: OnceLock<BuildInfo> = OnceLock::new();
+
+/// Which build of the application is running.
+///
+/// Construct it with [`build_info!`](crate::build_info) so the values are captured from
+/// the application crate at compile time, not from axtra's own build.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_timestamp: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<&'static str>,
+}
+
+/// Capture the calling crate's [`BuildInfo`] at compile time.
+///
+/// `name` and `version` come from Cargo; `git_sha`, `build_timestamp` and `rust_version`
+/// are read from the `GIT_SHA`, `BUILD_TIMESTAMP` and `RUSTC_VERSION` environment
+/// variables at build time (set them from CI or a `build.rs`).
+///
+/// ```rust, ignore
+/// let app = Router::new()
+///     .merge(health::version(axtra::build_info!()))
+///     .merge(health::basic());
+/// ```
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::routes::health::BuildInfo {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: option_env!("GIT_SHA"),
+            build_timestamp: option_env!("BUILD_TIMESTAMP"),
+            rust_version: option_env!("RUSTC_VERSION"),
+        }
+    };
+}
+
+/// The registered build metadata, if any.
+pub fn build_info() -> Option<&'static BuildInfo> {
+    BUILD_INFO.get()
+}
+
+/// Register build metadata to include in every health payload.
+///
+/// Returns false if build info was already registered.
+pub fn set_build_info(info: BuildInfo) -> bool {
+    BUILD_INFO.set(info).is_ok()
+}
+
+/// A `/version` route serving `info`, which is also registered for the health payloads.
+pub fn version<S>(info: BuildInfo) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    set_build_info(info.clone());
+    Router::new().route("/version", get(move || async move { Json(info) }))
+}
@@ -0,0 +1,81 @@
+//! Heartbeat indicator for background workers.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+
+use crate::routes::health::{CheckFuture, CheckResult, HealthIndicator};
+
+/// A handle background tasks [`beat`](Heartbeat::beat) on to show they are still alive.
+///
+/// Registered as an indicator, it reports `unhealthy` once no beat has arrived within
+/// the window. A new heartbeat counts as fresh, so workers get one window to start up.
+///
+/// ```rust, ignore
+/// let consumer = Heartbeat::new(Duration::from_secs(60));
+///
+/// tokio::spawn({
+///     let consumer = consumer.clone();
+///     async move {
+///         loop {
+///             process_batch().await;
+///             consumer.beat();
+///         }
+///     }
+/// });
+///
+/// let health = HealthRouter::new().check("queue_consumer", consumer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    window: Duration,
+    started: Instant,
+    // Milliseconds between `started` and the last beat
+    last_beat: Arc<AtomicU64>,
+}
+
+impl Heartbeat {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            started: Instant::now(),
+            last_beat: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Record that the worker is alive.
+    pub fn beat(&self) {
+        self.last_beat
+            .store(self.started.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Time since the last beat (or since creation if there hasn't been one).
+    pub fn since_last_beat(&self) -> Duration {
+        let last = Duration::from_millis(self.last_beat.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last)
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.since_last_beat() > self.window
+    }
+}
+
+impl HealthIndicator for Heartbeat {
+    fn check(&self) -> CheckFuture<'_> {
+        Box::pin(async move {
+            let since = self.since_last_beat();
+            let result = if since > self.window {
+                CheckResult::unhealthy(format!("Stale: no heartbeat for {}s", since.as_secs()))
+            } else {
+                CheckResult::healthy()
+            };
+            result.with_details(json!({ "last_beat_secs_ago": since.as_secs() }))
+        })
+    }
+}
@@ -0,0 +1,129 @@
+//! Rolling history of health reports.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::routes::health::{HealthReport, HealthStatus};
+
+// Upper bound on retained reports regardless of the window, to cap memory use
+const MAX_SAMPLES: usize = 1024;
+
+/// Success rates over the history window, served on `/health/history`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthHistory {
+    pub uptime_seconds: u64,
+    pub window_seconds: u64,
+    pub samples: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<HealthStatus>,
+    pub success_rate: f64,
+    pub checks: BTreeMap<String, CheckHistory>,
+}
+
+/// Success rate of a single check over the history window.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckHistory {
+    pub samples: usize,
+    pub success_rate: f64,
+}
+
+#[derive(Debug)]
+struct Sample {
+    at: Instant,
+    status: HealthStatus,
+    checks: Vec<(String, bool)>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct History {
+    window: Duration,
+    started: Instant,
+    samples: Arc<Mutex<VecDeque<Sample>>>,
+}
+
+impl History {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            started: Instant::now(),
+            samples: Arc::default(),
+        }
+    }
+
+    pub(crate) fn record(&self, report: &HealthReport) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        samples.push_back(Sample {
+            at: Instant::now(),
+            status: report.status,
+            checks: report
+                .checks
+                .iter()
+                .map(|(name, result)| (name.clone(), result.ok))
+                .collect(),
+        });
+        while samples.len() > MAX_SAMPLES {
+            samples.pop_front();
+        }
+        self.prune(&mut samples);
+    }
+
+    pub(crate) fn summary(&self) -> HealthHistory {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        self.prune(&mut samples);
+
+        let mut checks: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+        for sample in samples.iter() {
+            for (name, ok) in &sample.checks {
+                let (total, successes) = checks.entry(name.clone()).or_default();
+                *total += 1;
+                *successes += usize::from(*ok);
+            }
+        }
+        let successes = samples
+            .iter()
+            .filter(|sample| sample.status != HealthStatus::Unhealthy)
+            .count();
+
+        HealthHistory {
+            uptime_seconds: self.started.elapsed().as_secs(),
+            window_seconds: self.window.as_secs(),
+            samples: samples.len(),
+            status: samples.back().map(|sample| sample.status),
+            success_rate: rate(successes, samples.len()),
+            checks: checks
+                .into_iter()
+                .map(|(name, (total, successes))| {
+                    (
+                        name,
+                        CheckHistory {
+                            samples: total,
+                            success_rate: rate(successes, total),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn prune(&self, samples: &mut VecDeque<Sample>) {
+        while samples
+            .front()
+            .is_some_and(|sample| sample.at.elapsed() > self.window)
+        {
+            samples.pop_front();
+        }
+    }
+}
+
+// Fraction of successes rounded to 4 decimals; 1.0 when there is no data yet
+fn rate(successes: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 1.0;
+    }
+    (successes as f64 / total as f64 * 10_000.0).round() / 10_000.0
+}
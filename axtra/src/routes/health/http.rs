@@ -0,0 +1,101 @@
+//! Outbound HTTP dependency health indicator.
+
+use std::{
+    ops::{Bound, RangeBounds, RangeInclusive},
+    time::Duration,
+};
+
+use serde_json::json;
+
+use crate::routes::health::{CheckFuture, CheckResult, HealthIndicator};
+
+/// Checks that an external HTTP dependency answers with an expected status.
+///
+/// ```rust, ignore
+/// let health = HealthRouter::new().check(
+///     "stripe",
+///     HttpCheck::new("stripe", "https://status.stripe.com/api/v2/status.json")
+///         .timeout(Duration::from_secs(2))
+///         .expect_status(200..300),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpCheck {
+    name: String,
+    url: String,
+    timeout: Duration,
+    expected: RangeInclusive<u16>,
+    client: reqwest::Client,
+}
+
+impl HttpCheck {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            timeout: Duration::from_secs(5),
+            expected: 200..=299,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Give up on the request after `timeout` (defaults to 5s).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Status codes counted as healthy (defaults to `200..300`).
+    pub fn expect_status(mut self, range: impl RangeBounds<u16>) -> Self {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end,
+            Bound::Excluded(&end) => end.saturating_sub(1),
+            Bound::Unbounded => u16::MAX,
+        };
+        self.expected = start..=end;
+        self
+    }
+
+    /// Reuse an existing client (proxies, TLS settings, default headers).
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+impl HealthIndicator for HttpCheck {
+    fn check(&self) -> CheckFuture<'_> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(&self.url)
+                .timeout(self.timeout)
+                .send()
+                .await;
+
+            match response {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    let result = if self.expected.contains(&status) {
+                        CheckResult::healthy()
+                    } else {
+                        CheckResult::unhealthy(format!(
+                            "{} responded with unexpected status {status}",
+                            self.name
+                        ))
+                    };
+                    result.with_details(json!({ "status_code": status }))
+                }
+                Err(e) if e.is_timeout() => {
+                    CheckResult::unhealthy(format!("{} timed out", self.name))
+                }
+                Err(e) => CheckResult::unhealthy(format!("{} unreachable: {e}", self.name)),
+            }
+        })
+    }
+}
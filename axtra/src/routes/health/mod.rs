@@ -0,0 +1,139 @@
+//! Health check routes.
+//!
+//! - [`HealthRouter`]: register any number of [`HealthIndicator`]s and serve their aggregate status.
+//! - [`basic`] and [`with_checks`]: dependency-free shortcuts returning a ready router.
+//! - [`startup`] and [`Warmup`]: a startup probe that turns ready once warm-up tasks finish.
+//! - [`version`]: a `/version` route serving [`BuildInfo`], also included in health payloads.
+//! - [`check_health`]: the original Postgres-only handler (`postgres` feature).
+
+mod auth;
+mod build;
+mod heartbeat;
+mod history;
+#[cfg(feature = "health-http")]
+mod http;
+#[cfg(feature = "postgres")]
+mod migrations;
+#[cfg(feature = "health-redis")]
+mod redis;
+mod registry;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+mod sql;
+mod startup;
+#[cfg(feature = "health-system")]
+mod system;
+#[cfg(feature = "notifier")]
+mod transitions;
+
+pub use auth::HealthAuth;
+pub use build::{BuildInfo, build_info, set_build_info, version};
+pub use heartbeat::Heartbeat;
+pub use history::{CheckHistory, HealthHistory};
+#[cfg(feature = "health-http")]
+pub use http::HttpCheck;
+#[cfg(feature = "postgres")]
+pub use migrations::MigrationsCheck;
+#[cfg(feature = "health-redis")]
+pub use redis::RedisCheck;
+pub use registry::{
+    CheckFuture, CheckResult, HealthIndicator, HealthReport, HealthRouter, HealthStatus,
+};
+#[cfg(feature = "mysql")]
+pub use sql::MySqlCheck;
+#[cfg(feature = "postgres")]
+pub use sql::PostgresCheck;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub use sql::SqlCheck;
+#[cfg(feature = "sqlite")]
+pub use sql::SqliteCheck;
+pub use startup::{ReadyFlag, Warmup, startup};
+#[cfg(feature = "health-system")]
+pub use system::{DiskSpaceCheck, MemoryCheck};
+
+use axum::Router;
+#[cfg(feature = "postgres")]
+use axum::{Json, extract::State, http::StatusCode};
+#[cfg(feature = "postgres")]
+use serde::Serialize;
+#[cfg(feature = "postgres")]
+use sqlx::PgPool;
+#[cfg(feature = "postgres")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "postgres")]
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// A `/health` route with no dependency checks.
+///
+/// Reports healthy whenever the process can serve requests.
+pub fn basic<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    HealthRouter::new().into_router()
+}
+
+/// A `/health` route aggregating the given named indicators.
+pub fn with_checks<S, I, N>(checks: I) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    I: IntoIterator<Item = (N, Box<dyn HealthIndicator>)>,
+    N: Into<String>,
+{
+    checks
+        .into_iter()
+        .fold(HealthRouter::new(), |health, (name, indicator)| {
+            health.check(name, indicator)
+        })
+        .into_router()
+}
+
+#[cfg(feature = "postgres")]
+#[derive(Serialize)]
+pub struct HealthCheck {
+    status: String,
+    postgres: bool,
+    postgres_latency_ms: u64,
+    timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build: Option<&'static BuildInfo>,
+}
+
+/// Postgres-only health handler.
+///
+/// Prefer [`HealthRouter`] with a [`PostgresCheck`] to report on several dependencies.
+#[cfg(feature = "postgres")]
+pub async fn check_health(State(pool): State<PgPool>) -> Result<Json<HealthCheck>, StatusCode> {
+    // Try to execute a simple query with timeout
+    let start = Instant::now();
+    let db_connected = match tokio::time::timeout(
+        Duration::from_secs(5),
+        sqlx::query("SELECT (1) as ok").fetch_one(&pool),
+    )
+    .await
+    {
+        Ok(Ok(_)) => true,
+        Ok(Err(_)) | Err(_) => false,
+    };
+    let postgres_latency_ms = start.elapsed().as_millis() as u64;
+
+    let now = OffsetDateTime::now_utc();
+    let timestamp = now.format(&Rfc3339).unwrap_or_default();
+
+    let health = HealthCheck {
+        status: if db_connected {
+            "healthy".to_string()
+        } else {
+            "degraded".to_string()
+        },
+        postgres: db_connected,
+        postgres_latency_ms,
+        timestamp,
+        build: build_info(),
+    };
+
+    if db_connected {
+        Ok(Json(health))
+    } else {
+        Err(StatusCode::SERVICE_UNAVAILABLE)
+    }
+}
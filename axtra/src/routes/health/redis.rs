@@ -0,0 +1,68 @@
+//! Redis health indicator.
+
+use std::time::Duration;
+
+use redis::{Client, aio::ConnectionManager};
+
+use crate::routes::health::{CheckFuture, CheckResult, HealthIndicator};
+
+#[derive(Clone)]
+enum Connection {
+    Client(Client),
+    Manager(ConnectionManager),
+}
+
+/// Checks Redis with a `PING`.
+#[derive(Clone)]
+pub struct RedisCheck {
+    connection: Connection,
+    timeout: Duration,
+}
+
+impl RedisCheck {
+    /// Open a fresh multiplexed connection from the client on every check.
+    pub fn new(client: Client) -> Self {
+        Self {
+            connection: Connection::Client(client),
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    /// Reuse an existing connection manager.
+    pub fn from_manager(manager: ConnectionManager) -> Self {
+        Self {
+            connection: Connection::Manager(manager),
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn ping(&self) -> redis::RedisResult<String> {
+        match &self.connection {
+            Connection::Client(client) => {
+                let mut conn = client.get_multiplexed_async_connection().await?;
+                redis::cmd("PING").query_async(&mut conn).await
+            }
+            Connection::Manager(manager) => {
+                let mut conn = manager.clone();
+                redis::cmd("PING").query_async(&mut conn).await
+            }
+        }
+    }
+}
+
+impl HealthIndicator for RedisCheck {
+    fn check(&self) -> CheckFuture<'_> {
+        Box::pin(async move {
+            match tokio::time::timeout(self.timeout, self.ping()).await {
+                Ok(Ok(_)) => CheckResult::healthy(),
+                Ok(Err(e)) => CheckResult::unhealthy(e.to_string()),
+                Err(_) => CheckResult::unhealthy("Timed out"),
+            }
+        })
+    }
+}
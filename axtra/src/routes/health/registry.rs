@@ -0,0 +1,605 @@
+//! Pluggable health check registry.
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures_util::future::join_all;
+
+use axum::{
+    Json, Router,
+    extract::Query,
+    http::{HeaderMap, StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+#[cfg(feature = "notifier")]
+use super::transitions::TransitionNotifier;
+use super::{BuildInfo, HealthAuth, HealthHistory, build_info, history::History};
+use crate::app_error;
+#[cfg(feature = "notifier")]
+use crate::notifier::Notifier;
+
+const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+#[cfg(feature = "notifier")]
+const DEFAULT_NOTIFY_DEBOUNCE: Duration = Duration::from_secs(60);
+
+// Last report and when it was produced
+type CachedReport = Arc<tokio::sync::Mutex<Option<(Instant, HealthReport)>>>;
+
+/// Boxed future returned by [`HealthIndicator::check`].
+pub type CheckFuture<'a> = Pin<Box<dyn Future<Output = CheckResult> + Send + 'a>>;
+
+/// Status of a single dependency or of the service as a whole, ordered by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+impl HealthStatus {
+    /// HTTP status for a probe: degraded services still accept traffic.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            HealthStatus::Healthy | HealthStatus::Degraded => StatusCode::OK,
+            HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Single-word form used by plain-text probes.
+    pub fn as_text(&self) -> &'static str {
+        match self {
+            HealthStatus::Healthy => "ok",
+            HealthStatus::Degraded => "degraded",
+            HealthStatus::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+/// Outcome of a single health check.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub ok: bool,
+    pub status: HealthStatus,
+    /// How long the check took; filled in by [`HealthRouter::run`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl CheckResult {
+    fn new(status: HealthStatus, message: Option<String>) -> Self {
+        Self {
+            ok: status != HealthStatus::Unhealthy,
+            status,
+            latency_ms: None,
+            message,
+            details: None,
+        }
+    }
+
+    pub fn healthy() -> Self {
+        Self::new(HealthStatus::Healthy, None)
+    }
+
+    /// Working, but worth attention (e.g. close to a resource threshold).
+    pub fn degraded(message: impl Into<String>) -> Self {
+        Self::new(HealthStatus::Degraded, Some(message.into()))
+    }
+
+    pub fn unhealthy(message: impl Into<String>) -> Self {
+        Self::new(HealthStatus::Unhealthy, Some(message.into()))
+    }
+
+    /// Attach extra check-specific data to the JSON output.
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl From<bool> for CheckResult {
+    fn from(ok: bool) -> Self {
+        if ok {
+            Self::healthy()
+        } else {
+            Self::new(HealthStatus::Unhealthy, None)
+        }
+    }
+}
+
+impl<E: std::fmt::Display> From<Result<(), E>> for CheckResult {
+    fn from(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self::healthy(),
+            Err(e) => Self::unhealthy(e.to_string()),
+        }
+    }
+}
+
+/// A dependency that can report its health.
+///
+/// Implemented for async closures returning anything convertible into a [`CheckResult`]
+/// (`bool`, `Result<(), E>`, or `CheckResult` itself).
+pub trait HealthIndicator: Send + Sync + 'static {
+    fn check(&self) -> CheckFuture<'_>;
+}
+
+impl<F, Fut, R> HealthIndicator for F
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = R> + Send + 'static,
+    R: Into<CheckResult>,
+{
+    fn check(&self) -> CheckFuture<'_> {
+        let fut = self();
+        Box::pin(async move { fut.await.into() })
+    }
+}
+
+impl HealthIndicator for Box<dyn HealthIndicator> {
+    fn check(&self) -> CheckFuture<'_> {
+        (**self).check()
+    }
+}
+
+/// Aggregated result of every registered check.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: BTreeMap<String, CheckResult>,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<&'static BuildInfo>,
+}
+
+impl HealthReport {
+    /// Just the overall status as `text/plain`, for probes that can't parse JSON.
+    pub fn plain_text(&self) -> Response {
+        (
+            self.status.status_code(),
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            self.status.as_text(),
+        )
+            .into_response()
+    }
+}
+
+impl IntoResponse for HealthReport {
+    fn into_response(self) -> axum::response::Response {
+        (self.status.status_code(), Json(self)).into_response()
+    }
+}
+
+/// Builder registering named health indicators and serving their aggregate status.
+///
+/// ```rust, ignore
+/// let health = HealthRouter::new()
+///     .check("postgres", PostgresCheck::new(pool.clone()))
+///     .check("redis", || async { redis_ping().await.is_ok() });
+///
+/// let app = Router::new().merge(health.into_router());
+/// ```
+#[derive(Clone)]
+pub struct HealthRouter {
+    path: String,
+    plain_text_path: Option<String>,
+    checks: Vec<Registered>,
+    check_timeout: Duration,
+    deadline: Option<Duration>,
+    cache_ttl: Option<Duration>,
+    cache: CachedReport,
+    public_force: bool,
+    auth: Option<HealthAuth>,
+    history: Option<History>,
+    #[cfg(feature = "notifier")]
+    transitions: Option<TransitionNotifier>,
+    #[cfg(feature = "notifier")]
+    notify_debounce: Duration,
+}
+
+#[derive(Clone)]
+struct Registered {
+    name: String,
+    indicator: Arc<dyn HealthIndicator>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Deserialize)]
+struct HealthQuery {
+    #[serde(default)]
+    force: bool,
+}
+
+impl Default for HealthRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthRouter {
+    pub fn new() -> Self {
+        Self {
+            path: "/health".to_string(),
+            plain_text_path: None,
+            checks: Vec::new(),
+            check_timeout: DEFAULT_CHECK_TIMEOUT,
+            deadline: None,
+            cache_ttl: None,
+            cache: CachedReport::default(),
+            public_force: false,
+            auth: None,
+            history: None,
+            #[cfg(feature = "notifier")]
+            transitions: None,
+            #[cfg(feature = "notifier")]
+            notify_debounce: DEFAULT_NOTIFY_DEBOUNCE,
+        }
+    }
+
+    /// Route the health endpoint is served on (defaults to `/health`).
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Also serve the bare status (`ok`, `degraded` or `unhealthy`) as `text/plain` on `path`.
+    ///
+    /// Requests to the JSON route that only accept `text/plain` get the same output.
+    pub fn plain_text_path(mut self, path: impl Into<String>) -> Self {
+        self.plain_text_path = Some(path.into());
+        self
+    }
+
+    /// Register a named indicator.
+    pub fn check(mut self, name: impl Into<String>, indicator: impl HealthIndicator) -> Self {
+        self.checks.push(Registered {
+            name: name.into(),
+            indicator: Arc::new(indicator),
+            timeout: None,
+        });
+        self
+    }
+
+    /// Register a named indicator with its own timeout.
+    pub fn check_with_timeout(
+        mut self,
+        name: impl Into<String>,
+        indicator: impl HealthIndicator,
+        timeout: Duration,
+    ) -> Self {
+        self.checks.push(Registered {
+            name: name.into(),
+            indicator: Arc::new(indicator),
+            timeout: Some(timeout),
+        });
+        self
+    }
+
+    /// Default time each check may take before it is reported unhealthy (defaults to 5s).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.check_timeout = timeout;
+        self
+    }
+
+    /// Upper bound for the whole report; checks still running by then are reported unhealthy.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Reuse a report for `ttl` before running the checks again.
+    ///
+    /// Keeps frequent load-balancer probes from hitting every dependency; concurrent
+    /// probes wait for a single run. Callers authorized by [`auth`](Self::auth) can request
+    /// `?force=true` to bypass the cache.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Honor `?force=true` from every caller when no [`auth`](Self::auth) is set. Off by
+    /// default, so anonymous probes can't turn every request back into a run of the checks.
+    pub fn public_force(mut self, allow: bool) -> Self {
+        self.public_force = allow;
+        self
+    }
+
+    /// Only serve the detailed report to requests authorized by `auth`.
+    ///
+    /// Other callers get just the overall status (`{"status": "ok"}`, `"degraded"` or
+    /// `"unhealthy"`) with the same HTTP status, so dependency names and errors aren't
+    /// exposed publicly.
+    pub fn auth(mut self, auth: HealthAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Send a message through `notifier` when the overall status changes (e.g. healthy to
+    /// degraded, or back), listing the checks that aren't healthy.
+    ///
+    /// Changes are only announced once they have lasted a minute; adjust with
+    /// [`notify_debounce`](Self::notify_debounce). Status is evaluated whenever the
+    /// checks run, so this relies on regular probes.
+    #[cfg(feature = "notifier")]
+    pub fn notify(mut self, notifier: Notifier) -> Self {
+        self.transitions = Some(TransitionNotifier::new(notifier, self.notify_debounce));
+        self
+    }
+
+    /// How long a new status must persist before it is announced, whether set before or
+    /// after [`notify`](Self::notify).
+    #[cfg(feature = "notifier")]
+    pub fn notify_debounce(mut self, debounce: Duration) -> Self {
+        self.notify_debounce = debounce;
+        if let Some(transitions) = &mut self.transitions {
+            transitions.debounce(debounce);
+        }
+        self
+    }
+
+    /// Keep the reports from the last `window` and serve per-check success rates and
+    /// process uptime on `{path}/history`.
+    ///
+    /// At most 1024 reports are retained; history is only recorded when the checks run.
+    pub fn history(mut self, window: Duration) -> Self {
+        self.history = Some(History::new(window));
+        self
+    }
+
+    /// Success rates over the history window, if [`history`](Self::history) is enabled.
+    pub fn history_summary(&self) -> Option<HealthHistory> {
+        self.history.as_ref().map(History::summary)
+    }
+
+    /// The cached report if it is fresher than the configured TTL, otherwise a new run.
+    pub async fn report(&self, force: bool) -> HealthReport {
+        let Some(ttl) = self.cache_ttl else {
+            return self.run().await;
+        };
+
+        let mut cache = self.cache.lock().await;
+        if !force
+            && let Some((at, report)) = cache.as_ref()
+            && at.elapsed() < ttl
+        {
+            return report.clone();
+        }
+
+        let report = self.run().await;
+        *cache = Some((Instant::now(), report.clone()));
+        report
+    }
+
+    /// Run every check concurrently and aggregate the results; the worst status wins.
+    pub async fn run(&self) -> HealthReport {
+        let results = join_all(self.checks.iter().map(|registered| async move {
+            // All checks start together, so the deadline caps each one individually
+            let mut timeout = registered.timeout.unwrap_or(self.check_timeout);
+            if let Some(deadline) = self.deadline {
+                timeout = timeout.min(deadline);
+            }
+
+            let start = Instant::now();
+            let mut result = match tokio::time::timeout(timeout, registered.indicator.check()).await
+            {
+                Ok(result) => result,
+                Err(_) => CheckResult::unhealthy("Timed out"),
+            };
+            result.latency_ms = Some(start.elapsed().as_millis() as u64);
+            (registered.name.clone(), result)
+        }))
+        .await;
+        let checks: BTreeMap<String, CheckResult> = results.into_iter().collect();
+
+        let status = checks
+            .values()
+            .map(|result| result.status)
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+
+        let report = HealthReport {
+            status,
+            checks,
+            timestamp: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default(),
+            build: build_info(),
+        };
+
+        if let Some(history) = &self.history {
+            history.record(&report);
+        }
+
+        #[cfg(feature = "notifier")]
+        if let Some(transitions) = &self.transitions {
+            transitions.observe(&report);
+        }
+
+        report
+    }
+
+    // Whether the caller may bypass the cache with `?force=true`
+    fn may_force(&self, parts: &Parts) -> bool {
+        match &self.auth {
+            Some(auth) => auth.authorized(&parts.headers, &parts.extensions),
+            None => self.public_force,
+        }
+    }
+
+    async fn respond(&self, force: bool, parts: &Parts) -> Response {
+        let force = force && self.may_force(parts);
+        // Plain-text output only exposes the overall status, so it skips auth
+        if wants_plain_text(&parts.headers) {
+            return self.report(force).await.plain_text();
+        }
+        if let Some(auth) = &self.auth
+            && !auth.authorized(&parts.headers, &parts.extensions)
+        {
+            let status = self.report(false).await.status;
+            return (
+                status.status_code(),
+                Json(serde_json::json!({ "status": status.as_text() })),
+            )
+                .into_response();
+        }
+        self.report(force).await.into_response()
+    }
+
+    fn respond_history(&self, parts: &Parts) -> Response {
+        if let Some(auth) = &self.auth
+            && !auth.authorized(&parts.headers, &parts.extensions)
+        {
+            return app_error!(unauthenticated, json).into_response();
+        }
+        Json(self.history_summary()).into_response()
+    }
+
+    /// Build a router serving the aggregated report as JSON.
+    ///
+    /// Honors [`cache_ttl`](Self::cache_ttl), [`auth`](Self::auth) and the `?force=true`
+    /// query parameter for callers allowed to force a run, and adds the [`history`](Self::history) and
+    /// [`plain_text_path`](Self::plain_text_path) routes when configured.
+    pub fn into_router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let path = self.path.clone();
+        let plain_text_path = self.plain_text_path.clone();
+        let health = Arc::new(self);
+
+        let mut router = Router::new().route(
+            &path,
+            get({
+                let health = health.clone();
+                move |Query(query): Query<HealthQuery>, parts: Parts| {
+                    let health = health.clone();
+                    async move { health.respond(query.force, &parts).await }
+                }
+            }),
+        );
+        if health.history.is_some() {
+            let history_path = format!("{}/history", path.trim_end_matches('/'));
+            let health = health.clone();
+            router = router.route(
+                &history_path,
+                get(move |parts: Parts| {
+                    let health = health.clone();
+                    async move { health.respond_history(&parts) }
+                }),
+            );
+        }
+        if let Some(plain_text_path) = plain_text_path {
+            router = router.route(
+                &plain_text_path,
+                get(move |Query(query): Query<HealthQuery>, parts: Parts| {
+                    let health = health.clone();
+                    async move {
+                        let force = query.force && health.may_force(&parts);
+                        health.report(force).await.plain_text()
+                    }
+                }),
+            );
+        }
+        router
+    }
+}
+
+fn wants_plain_text(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/plain") && !accept.contains("json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    // A router whose single check counts its runs
+    fn counted(health: HealthRouter) -> (Router, Arc<AtomicUsize>) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let health = health
+            .check("db", move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { true }
+            })
+            .cache_ttl(Duration::from_secs(60))
+            .plain_text_path("/healthz");
+        (health.into_router(), runs)
+    }
+
+    async fn get(router: &Router, uri: &str, token: Option<&str>) -> Response {
+        let mut req = Request::get(uri);
+        if let Some(token) = token {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        router
+            .clone()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn force_needs_auth_or_public_force() {
+        let (router, runs) = counted(HealthRouter::new());
+        get(&router, "/health", None).await;
+        get(&router, "/health?force=true", None).await;
+        get(&router, "/healthz?force=true", None).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        let (router, runs) = counted(HealthRouter::new().public_force(true));
+        get(&router, "/health", None).await;
+        get(&router, "/healthz?force=true", None).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        let (router, runs) = counted(HealthRouter::new().auth(HealthAuth::new().bearer("t")));
+        get(&router, "/health", None).await;
+        get(&router, "/health?force=true", Some("wrong")).await;
+        get(&router, "/healthz?force=true", None).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        get(&router, "/health?force=true", Some("t")).await;
+        get(&router, "/healthz?force=true", Some("t")).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn unauthorized_callers_get_the_real_status_without_checks() {
+        let health = HealthRouter::new()
+            .check("db", || async { false })
+            .auth(HealthAuth::new().bearer("t"));
+        let router: Router = health.into_router();
+
+        let res = get(&router, "/health", None).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, r#"{"status":"unhealthy"}"#);
+
+        let res = get(&router, "/health", Some("t")).await;
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains(r#""db""#));
+    }
+}
@@ -0,0 +1,83 @@
+//! SQL database health indicators for any sqlx pool.
+
+use sqlx::{Database, Executor, IntoArguments, Pool};
+
+#[cfg(feature = "mysql")]
+use sqlx::MySql;
+#[cfg(feature = "postgres")]
+use sqlx::Postgres;
+#[cfg(feature = "sqlite")]
+use sqlx::Sqlite;
+
+use crate::routes::health::{CheckFuture, HealthIndicator};
+
+/// Checks database connectivity with a `SELECT 1` through a sqlx pool.
+///
+/// Use it through [`PostgresCheck`], [`MySqlCheck`] or [`SqliteCheck`].
+#[derive(Debug, Clone)]
+pub struct SqlCheck<DB: Database> {
+    pool: Pool<DB>,
+}
+
+/// Checks Postgres connectivity with a `SELECT 1`.
+#[cfg(feature = "postgres")]
+pub type PostgresCheck = SqlCheck<Postgres>;
+
+/// Checks MySQL connectivity with a `SELECT 1`.
+#[cfg(feature = "mysql")]
+pub type MySqlCheck = SqlCheck<MySql>;
+
+/// Checks SQLite connectivity with a `SELECT 1`.
+#[cfg(feature = "sqlite")]
+pub type SqliteCheck = SqlCheck<Sqlite>;
+
+impl<DB: Database> SqlCheck<DB> {
+    pub fn new(pool: Pool<DB>) -> Self {
+        Self { pool }
+    }
+}
+
+impl<DB> HealthIndicator for SqlCheck<DB>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    fn check(&self) -> CheckFuture<'_> {
+        Box::pin(async move {
+            sqlx::query::<DB>("SELECT (1) as ok")
+                .fetch_one(&self.pool)
+                .await
+                .map(|_| ())
+                .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indicator<T: HealthIndicator>() {}
+
+    #[test]
+    fn every_alias_is_an_indicator() {
+        #[cfg(feature = "postgres")]
+        indicator::<PostgresCheck>();
+        #[cfg(feature = "mysql")]
+        indicator::<MySqlCheck>();
+        #[cfg(feature = "sqlite")]
+        indicator::<SqliteCheck>();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn reports_whether_the_database_answers() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let check = SqliteCheck::new(pool.clone());
+        assert!(check.check().await.ok);
+
+        pool.close().await;
+        assert!(!check.check().await.ok);
+    }
+}
@@ -0,0 +1,137 @@
+//! Startup probe and warm-up tasks.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+use axum::{Json, Router, http::StatusCode, routing::get};
+use serde_json::json;
+
+use crate::routes::health::{CheckFuture, CheckResult, HealthIndicator};
+
+type WarmupTask =
+    Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send>>;
+
+/// Shared flag flipped once the service has finished starting up.
+///
+/// Registered as an indicator it reports `unhealthy` until ready, so readiness probes
+/// can wait on it as well.
+#[derive(Debug, Clone, Default)]
+pub struct ReadyFlag(Arc<AtomicBool>);
+
+impl ReadyFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_ready(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+impl HealthIndicator for ReadyFlag {
+    fn check(&self) -> CheckFuture<'_> {
+        Box::pin(async move {
+            if self.is_ready() {
+                CheckResult::healthy()
+            } else {
+                CheckResult::unhealthy("Starting up")
+            }
+        })
+    }
+}
+
+/// A `/startup` route for startup probes: 503 until `ready` is set, then 200.
+pub fn startup<S>(ready: ReadyFlag) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().route(
+        "/startup",
+        get(move || {
+            let ready = ready.clone();
+            async move {
+                if ready.is_ready() {
+                    (StatusCode::OK, Json(json!({ "status": "started" })))
+                } else {
+                    (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        Json(json!({ "status": "starting" })),
+                    )
+                }
+            }
+        }),
+    )
+}
+
+/// Warm-up tasks (connecting pools, priming caches) that must finish before the
+/// service reports ready.
+///
+/// ```rust, ignore
+/// let warmup = Warmup::new()
+///     .task("postgres", async move { pool.acquire().await.map(|_| ()) })
+///     .task("cache", async move { prime_cache().await });
+/// let ready = warmup.ready_flag();
+///
+/// let app = Router::new().merge(health::startup(ready.clone()));
+/// tokio::spawn(warmup.run());
+/// ```
+#[derive(Default)]
+pub struct Warmup {
+    tasks: Vec<(String, WarmupTask)>,
+    ready: ReadyFlag,
+}
+
+impl Warmup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a named task; tasks run in the order they were added.
+    pub fn task<F, E>(mut self, name: impl Into<String>, task: F) -> Self
+    where
+        F: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        self.tasks.push((
+            name.into(),
+            Box::pin(async move { task.await.map_err(Into::into) }),
+        ));
+        self
+    }
+
+    /// The flag set once every task has succeeded.
+    pub fn ready_flag(&self) -> ReadyFlag {
+        self.ready.clone()
+    }
+
+    /// Run every task, then mark the service ready.
+    ///
+    /// Stops at the first failing task and leaves the service not ready.
+    pub async fn run(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for (name, task) in self.tasks {
+            let start = Instant::now();
+            if let Err(e) = task.await {
+                tracing::error!(task = %name, "Warm-up task failed: {e}");
+                return Err(e);
+            }
+            tracing::info!(
+                task = %name,
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Warm-up task finished"
+            );
+        }
+        self.ready.set_ready();
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+//! Disk space and process memory health indicators.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+use sysinfo::{Disks, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::routes::health::{CheckFuture, CheckResult, HealthIndicator};
+
+/// Reports free space on the disk holding `path`, degraded below a threshold.
+///
+/// Defaults to warning when less than 10% of the disk is free.
+#[derive(Debug, Clone)]
+pub struct DiskSpaceCheck {
+    path: PathBuf,
+    min_free_bytes: Option<u64>,
+    min_free_percent: Option<f64>,
+}
+
+impl DiskSpaceCheck {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            min_free_bytes: None,
+            min_free_percent: Some(10.0),
+        }
+    }
+
+    /// Degrade when fewer than `bytes` are available.
+    pub fn min_free_bytes(mut self, bytes: u64) -> Self {
+        self.min_free_bytes = Some(bytes);
+        self
+    }
+
+    /// Degrade when less than `percent` (0-100) of the disk is available.
+    pub fn min_free_percent(mut self, percent: f64) -> Self {
+        self.min_free_percent = Some(percent);
+        self
+    }
+
+    fn measure(&self) -> CheckResult {
+        let path = match self.path.canonicalize() {
+            Ok(path) => path,
+            Err(e) => return CheckResult::unhealthy(format!("{}: {e}", self.path.display())),
+        };
+
+        // The disk holding the path is the one with the longest matching mount point
+        let disks = Disks::new_with_refreshed_list();
+        let Some(disk) = disks
+            .list()
+            .iter()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().components().count())
+        else {
+            return CheckResult::unhealthy(format!("No disk found for {}", path.display()));
+        };
+
+        let available = disk.available_space();
+        let total = disk.total_space();
+        let free_percent = if total == 0 {
+            0.0
+        } else {
+            available as f64 / total as f64 * 100.0
+        };
+
+        let low = self.min_free_bytes.is_some_and(|min| available < min)
+            || self.min_free_percent.is_some_and(|min| free_percent < min);
+        let result = if low {
+            CheckResult::degraded(format!(
+                "Low disk space: {free_percent:.1}% free on {}",
+                disk.mount_point().display()
+            ))
+        } else {
+            CheckResult::healthy()
+        };
+
+        result.with_details(json!({
+            "path": display(&self.path),
+            "mount_point": display(disk.mount_point()),
+            "available_bytes": available,
+            "total_bytes": total,
+            "free_percent": (free_percent * 10.0).round() / 10.0,
+        }))
+    }
+}
+
+impl HealthIndicator for DiskSpaceCheck {
+    fn check(&self) -> CheckFuture<'_> {
+        Box::pin(async move {
+            let check = self.clone();
+            tokio::task::spawn_blocking(move || check.measure())
+                .await
+                .unwrap_or_else(|e| CheckResult::unhealthy(e.to_string()))
+        })
+    }
+}
+
+/// Reports the resident memory (RSS) of the current process, degraded above a threshold.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCheck {
+    max_rss_bytes: Option<u64>,
+}
+
+impl MemoryCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Degrade when the process uses more than `bytes` of resident memory.
+    pub fn max_rss_bytes(mut self, bytes: u64) -> Self {
+        self.max_rss_bytes = Some(bytes);
+        self
+    }
+
+    fn measure(&self) -> CheckResult {
+        let pid = match sysinfo::get_current_pid() {
+            Ok(pid) => pid,
+            Err(e) => return CheckResult::unhealthy(e),
+        };
+
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[pid]),
+            false,
+            ProcessRefreshKind::nothing().with_memory(),
+        );
+        let Some(rss) = system.process(pid).map(|process| process.memory()) else {
+            return CheckResult::unhealthy("Process memory unavailable");
+        };
+
+        let result = match self.max_rss_bytes {
+            Some(max) if rss > max => {
+                CheckResult::degraded(format!("High memory usage: {rss} bytes resident"))
+            }
+            _ => CheckResult::healthy(),
+        };
+        result.with_details(json!({ "rss_bytes": rss }))
+    }
+}
+
+impl HealthIndicator for MemoryCheck {
+    fn check(&self) -> CheckFuture<'_> {
+        Box::pin(async move {
+            let check = self.clone();
+            tokio::task::spawn_blocking(move || check.measure())
+                .await
+                .unwrap_or_else(|e| CheckResult::unhealthy(e.to_string()))
+        })
+    }
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
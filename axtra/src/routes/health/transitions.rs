@@ -0,0 +1,111 @@
+//! Notifications when the aggregate health status changes.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::notifier::{Notifier, skipped};
+use crate::routes::health::{HealthReport, HealthStatus};
+
+// Status last announced and a candidate waiting out the debounce window
+#[derive(Debug)]
+struct State {
+    notified: HealthStatus,
+    pending: Option<(HealthStatus, Instant)>,
+}
+
+/// Sends a message through a [`Notifier`] when the aggregate status changes and stays
+/// changed for the debounce window, so flapping checks don't spam the channel.
+#[derive(Clone)]
+pub(crate) struct TransitionNotifier {
+    notifier: Arc<Notifier>,
+    debounce: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl TransitionNotifier {
+    pub(crate) fn new(notifier: Notifier, debounce: Duration) -> Self {
+        Self {
+            notifier: Arc::new(notifier),
+            debounce,
+            state: Arc::new(Mutex::new(State {
+                notified: HealthStatus::Healthy,
+                pending: None,
+            })),
+        }
+    }
+
+    pub(crate) fn debounce(&mut self, debounce: Duration) {
+        self.debounce = debounce;
+    }
+
+    pub(crate) fn observe(&self, report: &HealthReport) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if report.status == state.notified {
+            state.pending = None;
+            return;
+        }
+
+        let since = match state.pending {
+            Some((status, since)) if status == report.status => since,
+            _ => {
+                let now = Instant::now();
+                state.pending = Some((report.status, now));
+                now
+            }
+        };
+        if since.elapsed() < self.debounce {
+            return;
+        }
+
+        let previous = state.notified;
+        state.notified = report.status;
+        state.pending = None;
+        drop(state);
+
+        let message = transition_message(previous, report);
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            match notifier.notify(&message).await {
+                Err(e) if skipped(&*e) => {
+                    tracing::debug!("Health transition notification not sent: {e}")
+                }
+                Err(e) => tracing::warn!("Failed to send health transition notification: {e}"),
+                Ok(()) => {}
+            }
+        });
+    }
+}
+
+fn transition_message(previous: HealthStatus, report: &HealthReport) -> String {
+    let app_name = crate::notifier::app_name();
+    let mut message = format!(
+        "[{app_name}] Health changed from {} to {}",
+        label(previous),
+        label(report.status)
+    );
+
+    let failing: Vec<String> = report
+        .checks
+        .iter()
+        .filter(|(_, result)| result.status != HealthStatus::Healthy)
+        .map(|(name, result)| match &result.message {
+            Some(reason) => format!("- {name} ({}): {reason}", label(result.status)),
+            None => format!("- {name} ({})", label(result.status)),
+        })
+        .collect();
+    if !failing.is_empty() {
+        message.push('\n');
+        message.push_str(&failing.join("\n"));
+    }
+    message
+}
+
+fn label(status: HealthStatus) -> &'static str {
+    match status {
+        HealthStatus::Healthy => "healthy",
+        HealthStatus::Degraded => "degraded",
+        HealthStatus::Unhealthy => "unhealthy",
+    }
+}
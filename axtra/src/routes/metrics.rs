@@ -0,0 +1,125 @@
+//! Prometheus `/metrics` route.
+
+use std::{
+    sync::OnceLock,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    Router,
+    http::{HeaderValue, header},
+    response::IntoResponse,
+    routing::get,
+};
+
+use crate::metrics;
+
+static STARTED: OnceLock<(Instant, SystemTime)> = OnceLock::new();
+
+/// A `/metrics` route exposing the [`metrics`](crate::metrics) registry with process metrics.
+pub fn metrics<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    MetricsRouter::new().into_router()
+}
+
+/// Builder for the Prometheus scrape endpoint.
+#[derive(Debug, Clone)]
+pub struct MetricsRouter {
+    path: String,
+    process_metrics: bool,
+}
+
+impl Default for MetricsRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsRouter {
+    pub fn new() -> Self {
+        Self {
+            path: "/metrics".to_string(),
+            process_metrics: true,
+        }
+    }
+
+    /// Route the endpoint is served on (defaults to `/metrics`).
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Include the app's start time and uptime, and with `health-system` the process start
+    /// time and resident memory.
+    pub fn process_metrics(mut self, enabled: bool) -> Self {
+        self.process_metrics = enabled;
+        self
+    }
+
+    pub fn into_router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        STARTED.get_or_init(|| (Instant::now(), SystemTime::now()));
+        let process_metrics = self.process_metrics;
+        Router::new().route(
+            &self.path,
+            get(move || async move {
+                if process_metrics {
+                    record_process_metrics();
+                }
+                (
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+                    )],
+                    metrics::render(),
+                )
+                    .into_response()
+            }),
+        )
+    }
+}
+
+fn record_process_metrics() {
+    if let Some((started, started_at)) = STARTED.get() {
+        let start_time = started_at
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs_f64())
+            .unwrap_or_default();
+        metrics::gauge(
+            "axtra_start_time_seconds",
+            "When the metrics route was set up, around app start, since unix epoch in seconds.",
+        )
+        .set(start_time);
+        metrics::gauge(
+            "axtra_uptime_seconds",
+            "Seconds since the metrics route was set up.",
+        )
+        .set(started.elapsed().as_secs_f64());
+    }
+
+    #[cfg(feature = "health-system")]
+    if let Ok(pid) = sysinfo::get_current_pid() {
+        let mut system = sysinfo::System::new();
+        system.refresh_processes_specifics(
+            sysinfo::ProcessesToUpdate::Some(&[pid]),
+            false,
+            sysinfo::ProcessRefreshKind::nothing().with_memory(),
+        );
+        if let Some(process) = system.process(pid) {
+            metrics::gauge(
+                "process_resident_memory_bytes",
+                "Resident memory size in bytes.",
+            )
+            .set(process.memory() as f64);
+            metrics::gauge(
+                "process_start_time_seconds",
+                "Start time of the process since unix epoch in seconds.",
+            )
+            .set(process.start_time() as f64);
+        }
+    }
+}
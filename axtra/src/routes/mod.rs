@@ -1,2 +1,11 @@
 pub mod astro;
 pub mod health;
+mod metrics;
+mod robots;
+mod security;
+mod stats;
+
+pub use metrics::{MetricsRouter, metrics};
+pub use robots::{RobotsPolicy, robots};
+pub use security::{SecurityHeadersLayer, SecurityHeadersMiddleware, security_headers};
+pub use stats::{RouteSummary, StatsLayer, StatsMiddleware, route_stats, stats};
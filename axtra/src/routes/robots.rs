@@ -0,0 +1,116 @@
+//! `robots.txt` and `X-Robots-Tag` for keeping non-production deployments out of search.
+
+use std::fmt::Write;
+
+use axum::{
+    Router,
+    http::{HeaderName, HeaderValue, header},
+    routing::get,
+};
+use tower_http::set_header::SetResponseHeaderLayer;
+
+static X_ROBOTS_TAG: HeaderName = HeaderName::from_static("x-robots-tag");
+
+/// What crawlers may index.
+///
+/// ```rust, ignore
+/// // Disallow everything when AXTRA_NOINDEX=1 (staging, previews)
+/// let policy = RobotsPolicy::from_env().sitemap("https://example.com/sitemap.xml");
+///
+/// let app = Router::new()
+///     .merge(routes::robots(policy.clone()))
+///     .merge(serve_static_files())
+///     .layer(policy.noindex_layer());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobotsPolicy {
+    index: bool,
+    disallow: Vec<String>,
+    sitemaps: Vec<String>,
+}
+
+impl RobotsPolicy {
+    /// Let crawlers index everything not explicitly disallowed.
+    pub fn allow_all() -> Self {
+        Self {
+            index: true,
+            disallow: Vec::new(),
+            sitemaps: Vec::new(),
+        }
+    }
+
+    /// Keep crawlers out of the whole site.
+    pub fn disallow_all() -> Self {
+        Self {
+            index: false,
+            ..Self::allow_all()
+        }
+    }
+
+    /// [`disallow_all`](Self::disallow_all) when `AXTRA_NOINDEX` is `1` or `true`,
+    /// [`allow_all`](Self::allow_all) otherwise.
+    pub fn from_env() -> Self {
+        let noindex = std::env::var("AXTRA_NOINDEX")
+            .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if noindex {
+            Self::disallow_all()
+        } else {
+            Self::allow_all()
+        }
+    }
+
+    /// Keep crawlers out of paths starting with `path`, e.g. `/admin/`.
+    pub fn disallow(mut self, path: impl Into<String>) -> Self {
+        self.disallow.push(path.into());
+        self
+    }
+
+    /// Advertise a sitemap URL (only while indexing is allowed).
+    pub fn sitemap(mut self, url: impl Into<String>) -> Self {
+        self.sitemaps.push(url.into());
+        self
+    }
+
+    pub fn allows_indexing(&self) -> bool {
+        self.index
+    }
+
+    /// The generated `robots.txt`.
+    pub fn robots_txt(&self) -> String {
+        let mut out = String::from("User-agent: *\n");
+        if !self.index {
+            out.push_str("Disallow: /\n");
+            return out;
+        }
+        if self.disallow.is_empty() {
+            out.push_str("Allow: /\n");
+        }
+        for path in &self.disallow {
+            let _ = writeln!(out, "Disallow: {path}");
+        }
+        for url in &self.sitemaps {
+            let _ = writeln!(out, "Sitemap: {url}");
+        }
+        out
+    }
+
+    /// Layer adding `X-Robots-Tag: noindex, nofollow` to responses when indexing is
+    /// disallowed, and doing nothing otherwise.
+    pub fn noindex_layer(&self) -> SetResponseHeaderLayer<Option<HeaderValue>> {
+        let value = (!self.index).then(|| HeaderValue::from_static("noindex, nofollow"));
+        SetResponseHeaderLayer::if_not_present(X_ROBOTS_TAG.clone(), value)
+    }
+}
+
+/// A `/robots.txt` route generated from `policy`.
+pub fn robots<S>(policy: RobotsPolicy) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let body = policy.robots_txt();
+    Router::new().route(
+        "/robots.txt",
+        get(move || async move { ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body) }),
+    )
+}
@@ -0,0 +1,184 @@
+//! Per-route request latency statistics.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::{Mutex, OnceLock},
+    time::Instant,
+};
+
+use axum::{
+    Json, Router,
+    extract::MatchedPath,
+    http::{Request, Response},
+    routing::get,
+};
+use serde::Serialize;
+use tower::{Layer, Service};
+
+use crate::metrics;
+
+// Latencies kept per route for percentiles, so they reflect recent traffic
+const SAMPLES_PER_ROUTE: usize = 1024;
+
+static STATS: OnceLock<Mutex<BTreeMap<String, RouteStats>>> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct RouteStats {
+    count: u64,
+    errors: u64,
+    latencies_us: VecDeque<u64>,
+}
+
+/// Request counts and latency percentiles for one route.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteSummary {
+    pub count: u64,
+    pub errors: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// Summaries for every route seen by a [`StatsLayer`], keyed by `METHOD /route/{param}`.
+pub fn route_stats() -> BTreeMap<String, RouteSummary> {
+    let Some(stats) = STATS.get() else {
+        return BTreeMap::new();
+    };
+    let stats = stats.lock().unwrap_or_else(|e| e.into_inner());
+    stats
+        .iter()
+        .map(|(route, stats)| {
+            let mut latencies: Vec<u64> = stats.latencies_us.iter().copied().collect();
+            latencies.sort_unstable();
+            (
+                route.clone(),
+                RouteSummary {
+                    count: stats.count,
+                    errors: stats.errors,
+                    p50_ms: percentile(&latencies, 0.50),
+                    p95_ms: percentile(&latencies, 0.95),
+                    p99_ms: percentile(&latencies, 0.99),
+                },
+            )
+        })
+        .collect()
+}
+
+/// A `/stats` route serving [`route_stats`] as JSON.
+pub fn stats<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().route(
+        "/stats",
+        get(|| async { Json(serde_json::json!({ "routes": route_stats() })) }),
+    )
+}
+
+fn record(route: String, status: u16, micros: u64) {
+    let stats = STATS.get_or_init(Default::default);
+    let mut stats = stats.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = stats.entry(route).or_default();
+    entry.count += 1;
+    if status >= 500 {
+        entry.errors += 1;
+    }
+    if entry.latencies_us.len() == SAMPLES_PER_ROUTE {
+        entry.latencies_us.pop_front();
+    }
+    entry.latencies_us.push_back(micros);
+}
+
+// Nearest-rank percentile of sorted microsecond samples, in milliseconds
+fn percentile(sorted: &[u64], quantile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() as f64 * quantile).ceil() as usize).clamp(1, sorted.len());
+    (sorted[rank - 1] as f64 / 1000.0 * 100.0).round() / 100.0
+}
+
+// StatsLayer factory
+#[derive(Debug, Clone, Default)]
+pub struct StatsLayer;
+
+impl StatsLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for StatsLayer {
+    type Service = StatsMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        StatsMiddleware { inner }
+    }
+}
+
+// A middleware that records request latency per matched route
+#[derive(Debug, Clone)]
+pub struct StatsMiddleware<S> {
+    inner: S,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for StatsMiddleware<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // Use the route template, not the raw path, to keep the number of keys bounded
+        let method = req.method().clone();
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_owned())
+            .unwrap_or_else(|| "unmatched".to_string());
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let res = inner.call(req).await?;
+            let elapsed = start.elapsed();
+
+            let status = res.status();
+            record(
+                format!("{method} {route}"),
+                status.as_u16(),
+                elapsed.as_micros() as u64,
+            );
+            metrics::counter("axtra_http_requests_total", "HTTP requests handled.").inc_with(&[
+                ("method", method.as_str()),
+                ("route", &route),
+                ("status", status.as_str()),
+            ]);
+            metrics::counter(
+                "axtra_http_request_duration_seconds_sum",
+                "Total time spent handling HTTP requests.",
+            )
+            .inc_by_with(
+                elapsed.as_secs_f64(),
+                &[("method", method.as_str()), ("route", &route)],
+            );
+
+            Ok(res)
+        })
+    }
+}